@@ -0,0 +1,168 @@
+pub const SCENE_MANIFEST_PATH: &str = "scenes.manifest.ron";
+// The same manifest, built in for running without an asset server
+const BUNDLED_MANIFEST: &[u8] = include_bytes!("../../assets/scenes.manifest.ron");
+
+////////////////////////////////////////////////////////////////
+
+use bevy::asset::{io::Reader, AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+use crate::assets::*;
+
+/// A rough shape for a model, in world units after scaling.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ColliderHint {
+    Cylinder { radius: f32, half_height: f32 },
+    Cuboid { half_extents: (f32, f32, f32) },
+}
+
+impl ColliderHint {
+    /// Radius of the circle the shape covers on the ground.
+    pub fn footprint(&self) -> f32 {
+        match self {
+            ColliderHint::Cylinder { radius, .. } => *radius,
+            ColliderHint::Cuboid { half_extents } => half_extents.0.hypot(half_extents.2),
+        }
+    }
+}
+
+fn default_scale() -> f32 {
+    GLOBAL_SCALE
+}
+
+/// One model in `scenes.manifest.ron`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct SceneManifestEntry {
+    pub id: SceneAssetId,
+    /// glTF file, relative to the assets folder.
+    pub file: String,
+    /// Index of the scene inside the file.
+    #[serde(default)]
+    pub scene: usize,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub collider: Option<ColliderHint>,
+}
+
+pub fn parse_manifest(bytes: &[u8]) -> Result<Vec<SceneManifestEntry>, ron::error::SpannedError> {
+    ron::de::from_bytes::<Vec<SceneManifestEntry>>(bytes)
+}
+
+/// The manifest that ships with the game.
+pub fn bundled_manifest() -> Vec<SceneManifestEntry> {
+    parse_manifest(BUNDLED_MANIFEST).expect("bundled scene manifest is invalid")
+}
+
+#[derive(Asset, TypePath, Debug)]
+pub struct SceneManifest {
+    pub entries: Vec<SceneManifestEntry>,
+    /// The scene of each entry, in the same order.
+    pub scenes: Vec<Handle<Scene>>,
+}
+
+#[derive(Debug)]
+pub enum SceneManifestLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for SceneManifestLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneManifestLoaderError::Io(error) => {
+                write!(f, "could not read scene manifest: {}", error)
+            }
+            SceneManifestLoaderError::Ron(error) => {
+                write!(f, "could not parse scene manifest: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneManifestLoaderError {}
+
+impl From<std::io::Error> for SceneManifestLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        SceneManifestLoaderError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for SceneManifestLoaderError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        SceneManifestLoaderError::Ron(error)
+    }
+}
+
+/// Loads `.manifest.ron` files, along with every scene they list.
+#[derive(Default)]
+pub struct SceneManifestLoader;
+
+impl AssetLoader for SceneManifestLoader {
+    type Asset = SceneManifest;
+    type Settings = ();
+    type Error = SceneManifestLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let entries = parse_manifest(&bytes)?;
+        let scenes = entries
+            .iter()
+            .map(|entry| {
+                load_context.load(GltfAssetLabel::Scene(entry.scene).from_asset(entry.file.clone()))
+            })
+            .collect();
+        Ok(SceneManifest { entries, scenes })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["manifest.ron"]
+    }
+}
+
+#[derive(Resource)]
+pub struct SceneManifestHandle(pub Handle<SceneManifest>);
+
+pub fn load_scene_manifest(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SceneManifestHandle(asset_server.load(SCENE_MANIFEST_PATH)));
+}
+
+/// Makes `SceneAssets` once the manifest and all its scenes are in, and again whenever the
+/// manifest changes.
+pub fn apply_scene_manifest(
+    mut commands: Commands,
+    mut manifest_events: EventReader<AssetEvent<SceneManifest>>,
+    manifest_handle: Res<SceneManifestHandle>,
+    manifests: Res<Assets<SceneManifest>>,
+    scene_assets: Option<ResMut<SceneAssets>>,
+) {
+    let updated = manifest_events.read().any(|event| match event {
+        AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => {
+            *id == manifest_handle.0.id()
+        }
+        _ => false,
+    });
+    if !updated {
+        return;
+    }
+    let Some(manifest) = manifests.get(&manifest_handle.0) else {
+        return;
+    };
+
+    let loaded = SceneAssets::from_manifest(&manifest.entries, manifest.scenes.iter().cloned());
+    match scene_assets {
+        Some(mut scene_assets) => *scene_assets = loaded,
+        None => commands.insert_resource(loaded),
+    }
+}
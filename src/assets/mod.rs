@@ -5,60 +5,116 @@ pub const GLOBAL_SCALE_VEC: Vec3 = Vec3::new(GLOBAL_SCALE, GLOBAL_SCALE, GLOBAL_
 
 use bevy::prelude::*;
 use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
 
-#[derive(Hash, Eq, PartialEq, Clone, Debug)]
-pub enum SceneAssetType {
-    StructureHouse,
-    StructureWoodHut,
-    TreePine,
-    TreeRound,
-    TreeDead,
-    Villager,
-    ResourceWood,
+use std::borrow::Borrow;
+use std::fmt;
+
+pub mod manifest;
+
+pub use manifest::*;
+
+/// Names a model in the scene manifest.
+#[derive(Hash, Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SceneAssetId(pub String);
+
+impl SceneAssetId {
+    pub fn new(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl Borrow<str> for SceneAssetId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SceneAssetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
 }
 
-#[derive(Resource)]
+#[derive(Clone, Debug)]
+pub struct SceneAsset {
+    pub handle: Handle<Scene>,
+    pub scale: f32,
+    pub tags: Vec<String>,
+    pub collider: Option<ColliderHint>,
+}
+
+/// Every model from the scene manifest, ready to spawn.
+#[derive(Resource, Clone)]
 pub struct SceneAssets {
-    pub handles: HashMap<SceneAssetType, Handle<Scene>>,
+    scenes: HashMap<SceneAssetId, SceneAsset>,
+    /// In manifest order, so random picks among them are the same on every run.
+    ids: Vec<SceneAssetId>,
 }
 
-pub fn load_assets(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let mut asset_handles = HashMap::new();
-
-    asset_handles.insert(
-        SceneAssetType::StructureHouse,
-        asset_server.load(GltfAssetLabel::Scene(0).from_asset("house.glb")),
-    );
-
-    asset_handles.insert(
-        SceneAssetType::TreePine,
-        asset_server.load(GltfAssetLabel::Scene(0).from_asset("tree.glb")),
-    );
-    asset_handles.insert(
-        SceneAssetType::TreeRound,
-        asset_server.load(GltfAssetLabel::Scene(1).from_asset("tree.glb")),
-    );
-    asset_handles.insert(
-        SceneAssetType::TreeDead,
-        asset_server.load(GltfAssetLabel::Scene(2).from_asset("tree.glb")),
-    );
-
-    asset_handles.insert(
-        SceneAssetType::Villager,
-        asset_server.load(GltfAssetLabel::Scene(0).from_asset("villager_man.glb")),
-    );
-
-    asset_handles.insert(
-        SceneAssetType::ResourceWood,
-        asset_server.load(GltfAssetLabel::Scene(0).from_asset("wood.glb")),
-    );
-
-    asset_handles.insert(
-        SceneAssetType::StructureWoodHut,
-        asset_server.load(GltfAssetLabel::Scene(0).from_asset("wood_hut.glb")),
-    );
-
-    commands.insert_resource(SceneAssets {
-        handles: asset_handles,
-    });
+impl SceneAssets {
+    pub fn from_manifest(
+        entries: &[SceneManifestEntry],
+        handles: impl IntoIterator<Item = Handle<Scene>>,
+    ) -> Self {
+        let mut scenes = HashMap::new();
+        let mut ids = Vec::new();
+        for (entry, handle) in entries.iter().zip(handles) {
+            scenes.insert(
+                entry.id.clone(),
+                SceneAsset {
+                    handle,
+                    scale: entry.scale,
+                    tags: entry.tags.clone(),
+                    collider: entry.collider.clone(),
+                },
+            );
+            ids.push(entry.id.clone());
+        }
+        Self { scenes, ids }
+    }
+
+    /// Empty handles for every scene in the bundled manifest, for running without a
+    /// renderer or any assets loaded.
+    pub fn placeholder() -> Self {
+        let entries = bundled_manifest();
+        let handles = entries
+            .iter()
+            .map(|_| Handle::default())
+            .collect::<Vec<_>>();
+        Self::from_manifest(&entries, handles)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&SceneAsset> {
+        self.scenes.get(id)
+    }
+
+    /// An empty scene for ids missing from the manifest, so a bad id shows nothing instead of
+    /// taking the game down. Loading already reports the ids the game needs.
+    pub fn scene(&self, id: &str) -> Handle<Scene> {
+        match self.get(id) {
+            Some(scene) => scene.handle.clone(),
+            None => {
+                warn!("scene {} is not in the manifest", id);
+                Handle::default()
+            }
+        }
+    }
+
+    /// Places the model at `position` with the scale from the manifest.
+    pub fn transform(&self, id: &str, position: Vec3) -> Transform {
+        let scale = self.get(id).map_or(GLOBAL_SCALE, |scene| scene.scale);
+        Transform::from_translation(position).with_scale(Vec3::splat(scale))
+    }
+
+    /// Every model with this tag, in manifest order.
+    pub fn tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = &'a SceneAssetId> {
+        self.ids.iter().filter(move |id| {
+            self.scenes[*id]
+                .tags
+                .iter()
+                .any(|scene_tag| scene_tag == tag)
+        })
+    }
 }
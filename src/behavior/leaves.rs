@@ -0,0 +1,321 @@
+use bevy::prelude::*;
+
+use crate::assets::*;
+use crate::behavior::node::*;
+use crate::behavior::runtime::BTContext;
+use crate::harvestable::harvestable::*;
+use crate::inventory::*;
+use crate::item::*;
+use crate::item_drop::*;
+use crate::jobs::*;
+use crate::navigation::*;
+use crate::random::SimulationRng;
+use crate::structure::{housing::Housing, registry::Structure, stockpile::*};
+use crate::villager::{actions::*, needs::Needs, villager::Villager};
+use crate::world::Terrain;
+
+fn position_of(world: &World, entity: Entity) -> Option<Vec3> {
+    world
+        .get::<Transform>(entity)
+        .map(|transform| transform.translation)
+}
+
+/// Available jobs of this kind, nearest first.
+fn jobs_by_distance(world: &World, villager: Entity, kind: JobKind) -> Vec<Entity> {
+    let Some(position) = position_of(world, villager) else {
+        return Vec::new();
+    };
+    let job_board = world.resource::<JobBoard>();
+
+    let mut jobs = job_board
+        .jobs()
+        .filter(|job| job.kind == kind && job_board.is_available(job.target, villager))
+        .filter_map(|job| {
+            Some((
+                job.target,
+                position_of(world, job.target)?.distance(position),
+            ))
+        })
+        .collect::<Vec<_>>();
+    jobs.sort_by(|a, b| a.1.total_cmp(&b.1));
+    jobs.into_iter().map(|(target, _)| target).collect()
+}
+
+/// Available haul jobs for wood, nearest first. Other items are left to the other villagers.
+fn wood_drops_by_distance(world: &World, villager: Entity) -> Vec<Entity> {
+    jobs_by_distance(world, villager, JobKind::Haul)
+        .into_iter()
+        .filter(|item_drop| {
+            world
+                .get::<ItemStack>(*item_drop)
+                .is_some_and(|item_stack| item_stack.item_type == ItemType::Wood)
+        })
+        .collect()
+}
+
+fn nearest_with<T: Component>(world: &mut World, villager: Entity) -> Option<Entity> {
+    let position = position_of(world, villager)?;
+    let mut candidates = world.query_filtered::<(Entity, &Transform), With<T>>();
+
+    candidates
+        .iter(world)
+        .map(|(entity, transform)| (entity, transform.translation.distance(position)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+fn nearest_wood_hut_with_room(world: &mut World, villager: Entity) -> Option<Entity> {
+    let position = position_of(world, villager)?;
+    let mut wood_huts = world.query_filtered::<(Entity, &Transform, &Children), With<Structure>>();
+
+    wood_huts
+        .iter(world)
+        .filter(|(_, _, children)| {
+            stockpile_of(children).is_some_and(|stockpile| {
+                matches!(
+                    (world.get::<ItemStack>(stockpile), world.get::<Stockpile>(stockpile)),
+                    (Some(stored), Some(stockpile))
+                        if stored.item_type == ItemType::Wood && stockpile.room(stored) > 0
+                )
+            })
+        })
+        .map(|(entity, transform, _)| (entity, transform.translation.distance(position)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+}
+
+fn claim_nearest_job(context: &mut BTContext, candidates: Vec<Entity>) -> BTStatus {
+    let mut job_board = context.world.resource_mut::<JobBoard>();
+    // Someone may have claimed the nearest one in the meantime, the next one will do
+    let target = candidates
+        .into_iter()
+        .find(|target| job_board.claim(*target, context.entity));
+
+    set_target(context, target)
+}
+
+fn set_target(context: &mut BTContext, target: Option<Entity>) -> BTStatus {
+    context.blackboard.target = target;
+    if target.is_some() {
+        BTStatus::Success
+    } else {
+        BTStatus::Failure
+    }
+}
+
+pub fn check_condition(condition: BTCondition, context: &mut BTContext) -> bool {
+    match condition {
+        BTCondition::HasTarget => context
+            .blackboard
+            .target
+            .is_some_and(|target| context.world.get_entity(target).is_ok()),
+        BTCondition::HoldingWood => context
+            .world
+            .get::<Inventory>(context.entity)
+            .is_some_and(|inventory| inventory.count(ItemType::Wood) > 0),
+        BTCondition::TreeAvailable => {
+            !jobs_by_distance(context.world, context.entity, JobKind::Harvest).is_empty()
+        }
+        BTCondition::WoodAvailable => {
+            !wood_drops_by_distance(context.world, context.entity).is_empty()
+        }
+    }
+}
+
+pub fn run_action(action: BTAction, context: &mut BTContext) -> BTStatus {
+    match action {
+        BTAction::FindTree => {
+            let trees = jobs_by_distance(context.world, context.entity, JobKind::Harvest);
+            claim_nearest_job(context, trees)
+        }
+        BTAction::FindWood => {
+            let wood_drops = wood_drops_by_distance(context.world, context.entity);
+            claim_nearest_job(context, wood_drops)
+        }
+        BTAction::FindWoodHut => {
+            let wood_hut = nearest_wood_hut_with_room(context.world, context.entity);
+            set_target(context, wood_hut)
+        }
+        BTAction::FindHouse => {
+            let house = nearest_with::<Housing>(context.world, context.entity);
+            set_target(context, house)
+        }
+        BTAction::WalkToTarget { proximity } => walk_to_target(context, proximity),
+        BTAction::Harvest => harvest_target(context),
+        BTAction::PickUpWood => pick_up_target(context),
+        BTAction::DepositWood => deposit_at_target(context),
+    }
+}
+
+fn walk_to_target(context: &mut BTContext, proximity: f32) -> BTStatus {
+    let world = &mut *context.world;
+    let Some((target, target_transform)) = context.blackboard.target.and_then(|target| {
+        world
+            .get::<Transform>(target)
+            .map(|target_transform| (target, *target_transform))
+    }) else {
+        return BTStatus::Failure;
+    };
+    let Some(movement_speed) = world.get::<Villager>(context.entity).map(|villager| {
+        let penalty = world
+            .get::<Needs>(context.entity)
+            .map_or(1.0, Needs::movement_penalty);
+        villager.movement_speed * penalty
+    }) else {
+        return BTStatus::Failure;
+    };
+    let Some(mut transform) = world.get::<Transform>(context.entity).copied() else {
+        return BTStatus::Failure;
+    };
+    if transform.translation.distance(target_transform.translation) < proximity {
+        return BTStatus::Success;
+    }
+    let Some(mut path) = world
+        .get_mut::<NavPath>(context.entity)
+        .map(|mut path| std::mem::take(&mut *path))
+    else {
+        return BTStatus::Failure;
+    };
+
+    walk_to(
+        &mut transform,
+        &mut path,
+        target,
+        &target_transform,
+        movement_speed,
+        world.resource::<NavGrid>(),
+        world.resource::<Terrain>(),
+        world.resource::<Time>(),
+    );
+    world.entity_mut(context.entity).insert((transform, path));
+    BTStatus::Running
+}
+
+fn harvest_target(context: &mut BTContext) -> BTStatus {
+    let world = &mut *context.world;
+    let Some(target) = context.blackboard.target else {
+        return BTStatus::Failure;
+    };
+    // Felled trees are despawned soon after they're marked
+    if world.get_entity(target).is_err() || world.get::<HarvestableDeathmark>(target).is_some() {
+        return BTStatus::Success;
+    }
+    let Some(harvesting_speed) = world.get::<Villager>(context.entity).map(|villager| {
+        let penalty = world
+            .get::<Needs>(context.entity)
+            .map_or(1.0, Needs::harvesting_penalty);
+        villager.harvesting_speed * penalty
+    }) else {
+        return BTStatus::Failure;
+    };
+    let time = *world.resource::<Time>();
+    let Some(mut harvestable) = world.get_mut::<Harvestable>(target) else {
+        return BTStatus::Failure;
+    };
+
+    harvest(&mut harvestable, harvesting_speed, &time);
+    BTStatus::Running
+}
+
+fn pick_up_target(context: &mut BTContext) -> BTStatus {
+    let world = &mut *context.world;
+    let Some(target) = context.blackboard.target else {
+        return BTStatus::Failure;
+    };
+    if world.get::<ItemDrop>(target).is_none()
+        || !world
+            .resource::<JobBoard>()
+            .is_reserved_by(target, context.entity)
+    {
+        return BTStatus::Failure;
+    }
+    let Some(ItemStack { item_type, count }) = world.get::<ItemStack>(target).copied() else {
+        return BTStatus::Failure;
+    };
+    let Some(mut inventory) = world.get_mut::<Inventory>(context.entity) else {
+        return BTStatus::Failure;
+    };
+
+    let picked_up = inventory.add(item_type, count);
+    if picked_up == 0 {
+        return BTStatus::Failure;
+    }
+    if picked_up == count {
+        if let Ok(target) = world.get_entity_mut(target) {
+            target.despawn_recursive();
+        }
+    } else {
+        // The rest stays on the ground for someone else
+        if let Some(mut item_stack) = world.get_mut::<ItemStack>(target) {
+            item_stack.count -= picked_up;
+        }
+        world
+            .resource_mut::<JobBoard>()
+            .release(target, context.entity);
+    }
+
+    context.blackboard.target = None;
+    BTStatus::Success
+}
+
+fn deposit_at_target(context: &mut BTContext) -> BTStatus {
+    let world = &mut *context.world;
+    let Some(target) = context.blackboard.target else {
+        return BTStatus::Failure;
+    };
+    let Some(stockpile_entity) = world.get::<Children>(target).and_then(stockpile_of) else {
+        return BTStatus::Failure;
+    };
+    let (Some(item_type), Some(stockpile)) = (
+        world
+            .get::<ItemStack>(stockpile_entity)
+            .map(|stored| stored.item_type),
+        world.get::<Stockpile>(stockpile_entity).copied(),
+    ) else {
+        return BTStatus::Failure;
+    };
+    let Some(count) = world
+        .get_mut::<Inventory>(context.entity)
+        .map(|mut inventory| inventory.take_all(item_type))
+    else {
+        return BTStatus::Failure;
+    };
+
+    let Some(mut stored) = world.get_mut::<ItemStack>(stockpile_entity) else {
+        return BTStatus::Failure;
+    };
+    let overflow = stockpile.deposit(&mut stored, count);
+    let full = stockpile.room(&stored) == 0;
+
+    if overflow > 0 {
+        // Whatever didn't fit goes on the ground next to the hut
+        let position = world
+            .get::<Transform>(target)
+            .map(overflow_position)
+            .unwrap_or_default();
+        let scene_assets = world.resource::<SceneAssets>().clone();
+        world.resource_scope(|world, registry: Mut<ItemRegistry>| {
+            world.resource_scope(|world, mut rng: Mut<SimulationRng>| {
+                let mut commands = world.commands();
+                spawn_item_drop(
+                    &mut commands,
+                    &registry,
+                    &scene_assets,
+                    item_type,
+                    position,
+                    overflow,
+                    rng.stream("deposit_at_target"),
+                );
+            });
+        });
+        world.flush();
+    }
+    if full {
+        world.send_event(StorageFull {
+            building: target,
+            item_type,
+            overflow,
+        });
+    }
+    BTStatus::Success
+}
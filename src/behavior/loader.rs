@@ -0,0 +1,70 @@
+use bevy::asset::{io::Reader, AssetLoader, LoadContext};
+use bevy::prelude::*;
+
+use std::fmt;
+
+use crate::behavior::node::BTNode;
+
+#[derive(Asset, TypePath, Debug)]
+pub struct BehaviorTreeAsset {
+    pub root: BTNode,
+}
+
+#[derive(Debug)]
+pub enum BehaviorTreeLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for BehaviorTreeLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BehaviorTreeLoaderError::Io(error) => {
+                write!(f, "could not read behavior tree: {}", error)
+            }
+            BehaviorTreeLoaderError::Ron(error) => {
+                write!(f, "could not parse behavior tree: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BehaviorTreeLoaderError {}
+
+impl From<std::io::Error> for BehaviorTreeLoaderError {
+    fn from(error: std::io::Error) -> Self {
+        BehaviorTreeLoaderError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for BehaviorTreeLoaderError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        BehaviorTreeLoaderError::Ron(error)
+    }
+}
+
+/// Loads `.bt.ron` files holding a single `BTNode`.
+#[derive(Default)]
+pub struct BehaviorTreeLoader;
+
+impl AssetLoader for BehaviorTreeLoader {
+    type Asset = BehaviorTreeAsset;
+    type Settings = ();
+    type Error = BehaviorTreeLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let root = ron::de::from_bytes::<BTNode>(&bytes)?;
+        Ok(BehaviorTreeAsset { root })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bt.ron"]
+    }
+}
@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+
+use crate::plugins::SimulationSet;
+
+pub mod leaves;
+pub mod loader;
+pub mod node;
+pub mod runtime;
+
+pub use loader::*;
+pub use node::*;
+pub use runtime::*;
+
+pub struct BehaviorPlugin;
+
+impl Plugin for BehaviorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<BehaviorTreeAsset>();
+        app.init_asset_loader::<BehaviorTreeLoader>();
+        app.add_systems(
+            Update,
+            (attach_loaded_behavior_trees, tick_behavior_trees)
+                .chain()
+                .in_set(SimulationSet),
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BTStatus {
+    Success,
+    Failure,
+    Running,
+}
+
+/// A behavior tree definition. Trees are plain data, so they can be built in code
+/// or deserialized from a `.bt.ron` file.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BTNode {
+    /// Runs children in order until one fails.
+    Sequence(Vec<BTNode>),
+    /// Runs children in order until one succeeds.
+    Selector(Vec<BTNode>),
+    Decorator(BTDecorator, Box<BTNode>),
+    Condition(BTCondition),
+    Action(BTAction),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BTDecorator {
+    Invert,
+    /// Turns a failure into a success. Running stays running.
+    AlwaysSucceed,
+    /// Runs the child until it has succeeded this many times.
+    Repeat(u32),
+    /// Runs the child until it fails, then succeeds.
+    UntilFailure,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BTCondition {
+    HasTarget,
+    HoldingWood,
+    TreeAvailable,
+    WoodAvailable,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BTAction {
+    /// Claims the nearest free tree and makes it the target.
+    FindTree,
+    /// Claims the nearest free wood drop and makes it the target.
+    FindWood,
+    FindWoodHut,
+    FindHouse,
+    WalkToTarget {
+        proximity: f32,
+    },
+    /// Runs until the target harvestable is destroyed.
+    Harvest,
+    PickUpWood,
+    DepositWood,
+}
+
+impl BTNode {
+    pub fn sequence(children: impl IntoIterator<Item = BTNode>) -> Self {
+        Self::Sequence(children.into_iter().collect())
+    }
+
+    pub fn selector(children: impl IntoIterator<Item = BTNode>) -> Self {
+        Self::Selector(children.into_iter().collect())
+    }
+
+    pub fn decorate(decorator: BTDecorator, child: BTNode) -> Self {
+        Self::Decorator(decorator, Box::new(child))
+    }
+
+    /// Number of nodes in this subtree, this one included.
+    pub fn size(&self) -> usize {
+        match self {
+            BTNode::Sequence(children) | BTNode::Selector(children) => {
+                1 + children.iter().map(BTNode::size).sum::<usize>()
+            }
+            BTNode::Decorator(_, child) => 1 + child.size(),
+            BTNode::Condition(_) | BTNode::Action(_) => 1,
+        }
+    }
+}
+
+/// Haul wood if any is lying around, otherwise chop a tree.
+pub fn woodcutter_behavior() -> BTNode {
+    use BTAction::*;
+    use BTCondition::*;
+
+    BTNode::selector([
+        BTNode::sequence([
+            BTNode::Condition(HoldingWood),
+            BTNode::Action(FindWoodHut),
+            BTNode::Action(WalkToTarget { proximity: 0.2 }),
+            BTNode::Action(DepositWood),
+        ]),
+        BTNode::sequence([
+            BTNode::Condition(WoodAvailable),
+            BTNode::Action(FindWood),
+            BTNode::Action(WalkToTarget { proximity: 0.2 }),
+            BTNode::Action(PickUpWood),
+        ]),
+        BTNode::sequence([
+            BTNode::Action(FindTree),
+            BTNode::Action(WalkToTarget { proximity: 0.2 }),
+            BTNode::Action(Harvest),
+        ]),
+    ])
+}
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+
+use std::sync::Arc;
+
+use crate::behavior::leaves::*;
+use crate::behavior::loader::BehaviorTreeAsset;
+use crate::behavior::node::*;
+use crate::jobs::*;
+
+/// What a behavior tree remembers between leaves and between frames.
+#[derive(Clone, Debug, Default)]
+pub struct Blackboard {
+    pub target: Option<Entity>,
+}
+
+/// Drives an agent with a behavior tree instead of the FSM state systems.
+/// The root is restarted whenever it finishes.
+#[derive(Component, Debug)]
+pub struct BehaviorTree {
+    root: Arc<BTNode>,
+    // Per node, in pre-order: the running child of composites, the finished count of repeats
+    memory: Vec<usize>,
+    pub blackboard: Blackboard,
+    pub last_status: Option<BTStatus>,
+}
+
+impl BehaviorTree {
+    pub fn new(root: BTNode) -> Self {
+        Self {
+            memory: vec![0; root.size()],
+            root: Arc::new(root),
+            blackboard: Blackboard::default(),
+            last_status: None,
+        }
+    }
+
+    pub fn root(&self) -> &BTNode {
+        &self.root
+    }
+}
+
+/// Waits for a behavior tree asset, then replaces itself with the loaded `BehaviorTree`.
+#[derive(Component)]
+pub struct BehaviorTreeHandle(pub Handle<BehaviorTreeAsset>);
+
+pub struct BTContext<'w> {
+    pub world: &'w mut World,
+    pub entity: Entity,
+    pub blackboard: &'w mut Blackboard,
+}
+
+fn tick_node(node: &BTNode, id: usize, memory: &mut [usize], context: &mut BTContext) -> BTStatus {
+    match node {
+        BTNode::Sequence(children) => {
+            let mut child_id = id + 1;
+            for (i, child) in children.iter().enumerate() {
+                if i >= memory[id] {
+                    match tick_node(child, child_id, memory, context) {
+                        BTStatus::Success => {}
+                        BTStatus::Running => {
+                            memory[id] = i;
+                            return BTStatus::Running;
+                        }
+                        BTStatus::Failure => {
+                            memory[id] = 0;
+                            return BTStatus::Failure;
+                        }
+                    }
+                }
+                child_id += child.size();
+            }
+            memory[id] = 0;
+            BTStatus::Success
+        }
+        BTNode::Selector(children) => {
+            let mut child_id = id + 1;
+            for (i, child) in children.iter().enumerate() {
+                if i >= memory[id] {
+                    match tick_node(child, child_id, memory, context) {
+                        BTStatus::Failure => {}
+                        BTStatus::Running => {
+                            memory[id] = i;
+                            return BTStatus::Running;
+                        }
+                        BTStatus::Success => {
+                            memory[id] = 0;
+                            return BTStatus::Success;
+                        }
+                    }
+                }
+                child_id += child.size();
+            }
+            memory[id] = 0;
+            BTStatus::Failure
+        }
+        BTNode::Decorator(decorator, child) => {
+            let status = tick_node(child, id + 1, memory, context);
+            match (decorator, status) {
+                (_, BTStatus::Running) => BTStatus::Running,
+                (BTDecorator::Invert, BTStatus::Success) => BTStatus::Failure,
+                (BTDecorator::Invert, BTStatus::Failure) => BTStatus::Success,
+                (BTDecorator::AlwaysSucceed, _) => BTStatus::Success,
+                (BTDecorator::Repeat(count), BTStatus::Success) => {
+                    memory[id] += 1;
+                    if memory[id] as u32 >= *count {
+                        memory[id] = 0;
+                        BTStatus::Success
+                    } else {
+                        BTStatus::Running
+                    }
+                }
+                (BTDecorator::Repeat(_), BTStatus::Failure) => {
+                    memory[id] = 0;
+                    BTStatus::Failure
+                }
+                (BTDecorator::UntilFailure, BTStatus::Success) => BTStatus::Running,
+                (BTDecorator::UntilFailure, BTStatus::Failure) => BTStatus::Success,
+            }
+        }
+        BTNode::Condition(condition) => {
+            if check_condition(*condition, context) {
+                BTStatus::Success
+            } else {
+                BTStatus::Failure
+            }
+        }
+        BTNode::Action(action) => run_action(*action, context),
+    }
+}
+
+pub fn attach_loaded_behavior_trees(
+    mut commands: Commands,
+    pending: Query<(Entity, &BehaviorTreeHandle), Without<BehaviorTree>>,
+    behavior_trees: Res<Assets<BehaviorTreeAsset>>,
+) {
+    for (entity, handle) in pending.iter() {
+        if let Some(behavior_tree) = behavior_trees.get(&handle.0) {
+            commands
+                .entity(entity)
+                .insert(BehaviorTree::new(behavior_tree.root.clone()))
+                .remove::<BehaviorTreeHandle>();
+        }
+    }
+}
+
+pub fn tick_behavior_trees(world: &mut World) {
+    let mut agents = world.query_filtered::<Entity, With<BehaviorTree>>();
+    let agents = agents.iter(world).collect::<Vec<_>>();
+
+    for entity in agents {
+        let (root, mut memory, mut blackboard) = {
+            let mut behavior_tree = world.get_mut::<BehaviorTree>(entity).unwrap();
+            (
+                behavior_tree.root.clone(),
+                std::mem::take(&mut behavior_tree.memory),
+                std::mem::take(&mut behavior_tree.blackboard),
+            )
+        };
+
+        let status = tick_node(
+            &root,
+            0,
+            &mut memory,
+            &mut BTContext {
+                world: &mut *world,
+                entity,
+                blackboard: &mut blackboard,
+            },
+        );
+
+        if status != BTStatus::Running {
+            // Whatever the tree claimed is done with, one way or another
+            blackboard.target = None;
+            world.resource_mut::<JobBoard>().release_all(entity);
+        }
+
+        if let Some(mut behavior_tree) = world.get_mut::<BehaviorTree>(entity) {
+            behavior_tree.memory = memory;
+            behavior_tree.blackboard = blackboard;
+            behavior_tree.last_status = Some(status);
+        }
+    }
+}
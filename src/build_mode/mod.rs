@@ -0,0 +1,232 @@
+// Number keys that pick structures, in the order of `StructureKind::ALL`
+const STRUCTURE_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+// How far the site markers sit above the ground, so they don't flicker into it
+const MARKER_HEIGHT: f32 = 0.02;
+// Ground further from the camera than this can't be built on
+const CURSOR_RANGE: f32 = 200.0;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::assets::SceneAssets;
+use crate::navigation::NavObstacle;
+use crate::plugins::SimulationSet;
+use crate::structure::{construction::*, registry::*};
+use crate::world::Terrain;
+
+/// Lets the player place structures. The number keys pick a structure, a ghost of it follows
+/// the cursor showing whether the spot is free, left click places a construction site there and
+/// right click puts the structure back.
+pub struct BuildModePlugin;
+
+impl Plugin for BuildModePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BuildMode>();
+        app.add_systems(Startup, setup_build_mode_assets);
+        app.add_systems(
+            Update,
+            (select_structure, update_ghost, tint_ghost, place_structure)
+                .chain()
+                .in_set(SimulationSet),
+        );
+        app.add_systems(Update, show_construction_sites.in_set(SimulationSet));
+    }
+}
+
+/// The structure the player is about to place, if any.
+#[derive(Resource, Default, Debug)]
+pub struct BuildMode {
+    pub selected: Option<StructureKind>,
+}
+
+#[derive(Resource)]
+struct BuildModeAssets {
+    footprint: Handle<Mesh>,
+    valid: Handle<StandardMaterial>,
+    invalid: Handle<StandardMaterial>,
+    site: Handle<StandardMaterial>,
+}
+
+/// The selected structure's model, following the cursor and drawn in `material`.
+#[derive(Component)]
+struct Ghost {
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_build_mode_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let translucent = |color: Color| StandardMaterial {
+        base_color: color,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    };
+
+    // Unit radius, scaled to each structure's footprint
+    commands.insert_resource(BuildModeAssets {
+        footprint: meshes.add(Cylinder::new(1.0, MARKER_HEIGHT)),
+        valid: materials.add(translucent(Color::srgba(0.2, 0.9, 0.3, 0.5))),
+        invalid: materials.add(translucent(Color::srgba(0.9, 0.2, 0.2, 0.5))),
+        site: materials.add(translucent(Color::srgba(0.6, 0.4, 0.2, 0.7))),
+    });
+}
+
+fn select_structure(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut build_mode: ResMut<BuildMode>,
+    ghosts: Query<Entity, With<Ghost>>,
+) {
+    let picked = StructureKind::ALL
+        .iter()
+        .zip(STRUCTURE_KEYS)
+        .find(|(_, key)| keyboard_input.just_pressed(*key));
+    let selected = if let Some((kind, _)) = picked {
+        Some(*kind)
+    } else if mouse_input.just_pressed(MouseButton::Right) {
+        None
+    } else {
+        return;
+    };
+
+    build_mode.selected = selected;
+    // A fresh ghost gets spawned with the new model
+    for ghost in ghosts.iter() {
+        commands.entity(ghost).despawn_recursive();
+    }
+}
+
+/// Where the cursor points on the ground, if it's over the window.
+fn cursor_on_ground(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    cameras: &Query<(&Camera, &GlobalTransform)>,
+    terrain: &Terrain,
+) -> Option<Vec3> {
+    let cursor = windows.get_single().ok()?.cursor_position()?;
+    let (camera, camera_transform) = cameras.iter().find(|(camera, _)| camera.is_active)?;
+    let ray = camera.viewport_to_world(camera_transform, cursor).ok()?;
+
+    terrain.cast_ray(ray, CURSOR_RANGE)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_ghost(
+    mut commands: Commands,
+    build_mode: Res<BuildMode>,
+    build_mode_assets: Res<BuildModeAssets>,
+    structures: Res<StructureRegistry>,
+    scene_assets: Res<SceneAssets>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    terrain: Res<Terrain>,
+    obstacles: Query<(&Transform, &NavObstacle), Without<Ghost>>,
+    mut ghosts: Query<(&mut Transform, &mut Ghost)>,
+) {
+    let Some(kind) = build_mode.selected else {
+        return;
+    };
+    let Some(position) = cursor_on_ground(&windows, &cameras, &terrain) else {
+        return;
+    };
+
+    let definition = structures.get(kind);
+    let footprint = definition.footprint;
+    let existing = obstacles
+        .iter()
+        .map(|(transform, obstacle)| (transform.translation, obstacle.radius));
+    let material = if is_site_clear(position, footprint, existing) {
+        build_mode_assets.valid.clone()
+    } else {
+        build_mode_assets.invalid.clone()
+    };
+    let transform = scene_assets.transform(definition.scene, position);
+
+    match ghosts.get_single_mut() {
+        Ok((mut ghost_transform, mut ghost)) => {
+            *ghost_transform = transform;
+            ghost.material = material;
+        }
+        Err(_) => {
+            commands.spawn((
+                SceneRoot(scene_assets.scene(definition.scene)),
+                transform,
+                Ghost { material },
+                Name::new("Ghost"),
+            ));
+        }
+    }
+}
+
+/// The model comes with its own materials, so every mesh of the ghost is drawn in the ghost
+/// material instead, also once the scene has only just spawned.
+fn tint_ghost(
+    ghosts: Query<(Entity, &Ghost)>,
+    children: Query<&Children>,
+    mut mesh_materials: Query<&mut MeshMaterial3d<StandardMaterial>>,
+) {
+    for (entity, ghost) in ghosts.iter() {
+        for descendant in children.iter_descendants(entity) {
+            if let Ok(mut mesh_material) = mesh_materials.get_mut(descendant) {
+                if mesh_material.0 != ghost.material {
+                    mesh_material.0 = ghost.material.clone();
+                }
+            }
+        }
+    }
+}
+
+fn place_structure(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    build_mode: Res<BuildMode>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    terrain: Res<Terrain>,
+    mut place_structure_events: EventWriter<PlaceStructure>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(kind) = build_mode.selected else {
+        return;
+    };
+    let Some(position) = cursor_on_ground(&windows, &cameras, &terrain) else {
+        return;
+    };
+
+    // Blocked spots are turned down by `place_construction_sites`
+    place_structure_events.send(PlaceStructure { kind, position });
+}
+
+/// Marks out the footprint of every construction site until it's built.
+fn show_construction_sites(
+    mut commands: Commands,
+    build_mode_assets: Res<BuildModeAssets>,
+    structures: Res<StructureRegistry>,
+    sites: Query<(Entity, &ConstructionSite), Added<ConstructionSite>>,
+) {
+    for (entity, site) in sites.iter() {
+        let footprint = structures.get(site.kind).footprint;
+        commands.entity(entity).with_child((
+            Mesh3d(build_mode_assets.footprint.clone()),
+            MeshMaterial3d(build_mode_assets.site.clone()),
+            Transform::from_xyz(0.0, MARKER_HEIGHT, 0.0)
+                .with_scale(Vec3::new(footprint, 1.0, footprint)),
+        ));
+    }
+}
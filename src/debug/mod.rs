@@ -0,0 +1,46 @@
+const QUICKSAVE_PATH: &str = "saves/quicksave.ron";
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use bevy_rapier3d::prelude::*;
+
+use crate::save::{LoadGame, SaveGame};
+
+/// Collider outlines, the world inspector and developer keys: Escape quits, F5 quicksaves
+/// and F9 loads the quicksave.
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            RapierDebugRenderPlugin::default(),
+            WorldInspectorPlugin::new(),
+        ));
+        app.add_systems(Update, (exit_on_escape, quicksave));
+    }
+}
+
+fn exit_on_escape(keyboard_input: Res<ButtonInput<KeyCode>>, mut exit: EventWriter<AppExit>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        exit.send(AppExit::Success);
+    }
+}
+
+fn quicksave(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut save_events: EventWriter<SaveGame>,
+    mut load_events: EventWriter<LoadGame>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        save_events.send(SaveGame {
+            path: QUICKSAVE_PATH.into(),
+        });
+    }
+    if keyboard_input.just_pressed(KeyCode::F9) {
+        load_events.send(LoadGame {
+            path: QUICKSAVE_PATH.into(),
+        });
+    }
+}
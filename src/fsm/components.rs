@@ -1,29 +1,69 @@
 use bevy::prelude::*;
 
-#[derive(Component, Default)]
-pub struct HasFSM;
+use crate::fsm::machine::*;
+use crate::villager::needs::Need;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum VillagerState {
+    Idle,
+    WalkingTo,
+    WalkingToHarvest,
+    Harvesting,
+    PickingUp,
+    BringingTo,
+    WalkingHome,
+    Eating,
+    Sleeping,
+    Fetching,
+    Supplying,
+    Building,
+}
+
+impl FSMStateId for VillagerState {}
+
+macro_rules! villager_state {
+    ($component:ty, $id:ident) => {
+        impl FSMState for $component {
+            type Id = VillagerState;
+            const ID: VillagerState = VillagerState::$id;
+        }
+    };
+}
 
 // FSM States
 
 #[derive(Component, Debug)]
-#[require(HasFSM)]
 pub struct FSMIdle;
 
 #[derive(Component, Debug)]
-#[require(HasFSM)]
 pub struct FSMWalkingTo {
     pub target: Entity,
     pub proximity: f32,
 }
 
-// #[derive(Component, Debug)]
-// #[require(HasFSM)]
-// pub struct FSMBuilding {
-//     pub target: Entity,
-// }
+/// Putting a construction site together once it has all its wood.
+#[derive(Component, Debug)]
+pub struct FSMBuilding {
+    pub target: Entity,
+    pub proximity: f32,
+}
+
+/// Heading to a wood hut to take out wood for a construction site.
+#[derive(Component, Debug)]
+pub struct FSMFetching {
+    pub target: Entity,
+    pub site: Entity,
+    pub proximity: f32,
+}
+
+/// Bringing fetched wood to a construction site.
+#[derive(Component, Debug)]
+pub struct FSMSupplying {
+    pub target: Entity,
+    pub proximity: f32,
+}
 
 #[derive(Component, Debug)]
-#[require(HasFSM)]
 pub struct FSMWalkingToHarvest {
     pub target: Entity,
     // Should be a property of the Harvestable
@@ -31,22 +71,49 @@ pub struct FSMWalkingToHarvest {
 }
 
 #[derive(Component, Debug)]
-#[require(HasFSM)]
 pub struct FSMHarvesting {
     pub target: Entity,
 }
 
 #[derive(Component, Debug)]
-#[require(HasFSM)]
 pub struct FSMPickingUp {
     pub target: Entity,
     pub proximity: f32,
 }
 
 #[derive(Component, Debug)]
-#[require(HasFSM)]
 pub struct FSMBringingTo {
     pub target: Entity,
-    pub held_resource: Option<Entity>,
     pub proximity: f32,
 }
+
+/// Heading to a house to take care of a need.
+#[derive(Component, Debug)]
+pub struct FSMWalkingHome {
+    pub target: Entity,
+    pub need: Need,
+    pub proximity: f32,
+}
+
+#[derive(Component, Debug)]
+pub struct FSMEating {
+    pub house: Entity,
+}
+
+#[derive(Component, Debug)]
+pub struct FSMSleeping {
+    pub house: Entity,
+}
+
+villager_state!(FSMIdle, Idle);
+villager_state!(FSMWalkingTo, WalkingTo);
+villager_state!(FSMWalkingToHarvest, WalkingToHarvest);
+villager_state!(FSMHarvesting, Harvesting);
+villager_state!(FSMPickingUp, PickingUp);
+villager_state!(FSMBringingTo, BringingTo);
+villager_state!(FSMWalkingHome, WalkingHome);
+villager_state!(FSMEating, Eating);
+villager_state!(FSMSleeping, Sleeping);
+villager_state!(FSMFetching, Fetching);
+villager_state!(FSMSupplying, Supplying);
+villager_state!(FSMBuilding, Building);
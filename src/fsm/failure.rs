@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+
+use crate::fsm::components::*;
+use crate::fsm::machine::FSMState;
+use crate::fsm::transitions::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TaskFailureReason {
+    /// The target entity no longer exists.
+    TargetDespawned,
+    /// The target was destroyed by someone else before the villager got to it.
+    TargetDestroyed,
+    /// The target exists but isn't something this state can work with.
+    InvalidTarget,
+    /// Another villager holds the reservation for the target.
+    TargetReserved,
+    /// There is nowhere to store what the villager would be carrying.
+    NoStorage,
+    /// None of the target fits in the villager's inventory.
+    CannotCarry,
+}
+
+impl TaskFailureReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskFailureReason::TargetDespawned => "target despawned",
+            TaskFailureReason::TargetDestroyed => "target destroyed",
+            TaskFailureReason::InvalidTarget => "invalid target",
+            TaskFailureReason::TargetReserved => "target reserved by someone else",
+            TaskFailureReason::NoStorage => "no storage",
+            TaskFailureReason::CannotCarry => "cannot carry it",
+        }
+    }
+}
+
+/// Sent when a villager gives up on its current state. The villager goes back to `FSMIdle`.
+#[derive(Event, Clone, Debug)]
+pub struct TaskFailed {
+    pub entity: Entity,
+    pub state: VillagerState,
+    pub target: Entity,
+    pub reason: TaskFailureReason,
+}
+
+pub fn fsm_fail_task<FSMFrom: FSMState<Id = VillagerState>>(
+    commands: &mut Commands,
+    task_failed_events: &mut EventWriter<TaskFailed>,
+    entity: Entity,
+    target: Entity,
+    reason: TaskFailureReason,
+) {
+    task_failed_events.send(TaskFailed {
+        entity,
+        state: FSMFrom::ID,
+        target,
+        reason,
+    });
+    fsm_transition_to::<FSMFrom>(commands, entity, FSMIdle, reason.as_str());
+}
@@ -0,0 +1,385 @@
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::time::Duration;
+
+/// The set of states one kind of agent can be in. Usually a fieldless enum.
+pub trait FSMStateId: Copy + Eq + Hash + Debug + Send + Sync + 'static {}
+
+/// A state component. Every state belongs to exactly one `FSMStateId` set,
+/// so transitions between states of different agent kinds don't compile.
+pub trait FSMState: Component + Debug {
+    type Id: FSMStateId;
+    const ID: Self::Id;
+}
+
+/// The single current state of an agent. Only changed through `fsm_transition_to`.
+#[derive(Component, Debug)]
+pub struct FSM<S: FSMStateId> {
+    current: S,
+}
+
+impl<S: FSMStateId> FSM<S> {
+    /// Bundle for spawning an agent in its initial state.
+    pub fn start<T: FSMState<Id = S>>(initial: T) -> (Self, T) {
+        (Self { current: T::ID }, initial)
+    }
+
+    pub fn current(&self) -> S {
+        self.current
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FSMHistoryEntry<S: FSMStateId> {
+    pub state: S,
+    pub at: Duration,
+    pub reason: &'static str,
+}
+
+/// The last `capacity` states an agent entered, oldest first.
+#[derive(Component, Debug)]
+pub struct FSMHistory<S: FSMStateId> {
+    capacity: usize,
+    entries: VecDeque<FSMHistoryEntry<S>>,
+}
+
+impl<S: FSMStateId> FSMHistory<S> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, entry: FSMHistoryEntry<S>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &FSMHistoryEntry<S>> {
+        self.entries.iter()
+    }
+
+    pub fn states(&self) -> impl Iterator<Item = S> + '_ {
+        self.entries.iter().map(|entry| entry.state)
+    }
+
+    pub fn latest(&self) -> Option<&FSMHistoryEntry<S>> {
+        self.entries.back()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// Sent for every accepted transition, after the enter hooks have run.
+#[derive(Event, Debug, Clone)]
+pub struct StateTransitioned<S: FSMStateId> {
+    pub entity: Entity,
+    pub from: S,
+    pub to: S,
+    pub at: Duration,
+    pub reason: &'static str,
+}
+
+/// Runs with the agent entity. Exit hooks run before the old state component is removed,
+/// enter hooks after the new one is inserted.
+pub type FSMHook = fn(&mut World, Entity);
+
+struct FSMStateEntry {
+    name: &'static str,
+    present: fn(&World, Entity) -> bool,
+    remove: fn(&mut World, Entity),
+    on_enter: Vec<FSMHook>,
+    on_exit: Vec<FSMHook>,
+}
+
+/// Declares the states and allowed transitions of one agent kind.
+#[derive(Resource)]
+pub struct FSMDefinition<S: FSMStateId> {
+    states: HashMap<S, FSMStateEntry>,
+    transitions: HashSet<(S, S)>,
+}
+
+impl<S: FSMStateId> Default for FSMDefinition<S> {
+    fn default() -> Self {
+        Self {
+            states: HashMap::new(),
+            transitions: HashSet::new(),
+        }
+    }
+}
+
+fn state_present<T: Component>(world: &World, entity: Entity) -> bool {
+    world.get::<T>(entity).is_some()
+}
+
+fn state_remove<T: Component>(world: &mut World, entity: Entity) {
+    if let Ok(mut entity) = world.get_entity_mut(entity) {
+        entity.remove::<T>();
+    }
+}
+
+impl<S: FSMStateId> FSMDefinition<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state<T: FSMState<Id = S>>(mut self) -> Self {
+        self.states.insert(
+            T::ID,
+            FSMStateEntry {
+                name: std::any::type_name::<T>(),
+                present: state_present::<T>,
+                remove: state_remove::<T>,
+                on_enter: Vec::new(),
+                on_exit: Vec::new(),
+            },
+        );
+        self
+    }
+
+    pub fn transition(mut self, from: S, to: S) -> Self {
+        self.transitions.insert((from, to));
+        self
+    }
+
+    pub fn transitions(mut self, from: S, to: &[S]) -> Self {
+        for to in to {
+            self.transitions.insert((from, *to));
+        }
+        self
+    }
+
+    pub fn on_enter(mut self, state: S, hook: FSMHook) -> Self {
+        self.entry_mut(state).on_enter.push(hook);
+        self
+    }
+
+    pub fn on_exit(mut self, state: S, hook: FSMHook) -> Self {
+        self.entry_mut(state).on_exit.push(hook);
+        self
+    }
+
+    pub fn has_state(&self, state: S) -> bool {
+        self.states.contains_key(&state)
+    }
+
+    pub fn is_allowed(&self, from: S, to: S) -> bool {
+        self.transitions.contains(&(from, to))
+    }
+
+    fn entry_mut(&mut self, state: S) -> &mut FSMStateEntry {
+        self.states
+            .get_mut(&state)
+            .unwrap_or_else(|| panic!("FSM state {:?} must be declared before adding hooks", state))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FSMTransitionError<S: FSMStateId> {
+    /// The entity is gone or was never given an `FSM` component.
+    MissingFSM,
+    /// The target state was not declared in the `FSMDefinition`.
+    UnknownState(S),
+    /// The caller expected the agent to be in `expected`, but it is in `actual`.
+    NotInState { expected: S, actual: S },
+    /// `from -> to` is not a declared transition.
+    NotAllowed { from: S, to: S },
+}
+
+/// Sent whenever a transition is refused. The agent stays in its current state.
+#[derive(Event, Debug)]
+pub struct FSMTransitionRejected<S: FSMStateId> {
+    pub entity: Entity,
+    pub error: FSMTransitionError<S>,
+}
+
+/// Moves `entity` from `FSMFrom` to `fsm_to`, running exit and enter hooks and
+/// recording the transition. Nothing changes if the transition is rejected.
+pub fn apply_transition<FSMFrom, FSMTo>(
+    world: &mut World,
+    entity: Entity,
+    fsm_to: FSMTo,
+    reason: &'static str,
+) -> Result<(), FSMTransitionError<FSMFrom::Id>>
+where
+    FSMFrom: FSMState,
+    FSMTo: FSMState<Id = FSMFrom::Id>,
+{
+    let current = world
+        .get::<FSM<FSMFrom::Id>>(entity)
+        .ok_or(FSMTransitionError::MissingFSM)?
+        .current;
+
+    let definition = world.resource::<FSMDefinition<FSMFrom::Id>>();
+    if !definition.has_state(FSMTo::ID) {
+        return Err(FSMTransitionError::UnknownState(FSMTo::ID));
+    }
+    if current != FSMFrom::ID || world.get::<FSMFrom>(entity).is_none() {
+        return Err(FSMTransitionError::NotInState {
+            expected: FSMFrom::ID,
+            actual: current,
+        });
+    }
+    if !definition.is_allowed(FSMFrom::ID, FSMTo::ID) {
+        return Err(FSMTransitionError::NotAllowed {
+            from: FSMFrom::ID,
+            to: FSMTo::ID,
+        });
+    }
+
+    let on_exit = definition.states[&FSMFrom::ID].on_exit.clone();
+    let on_enter = definition.states[&FSMTo::ID].on_enter.clone();
+
+    for hook in on_exit {
+        hook(world, entity);
+    }
+
+    let mut entity_mut = world.entity_mut(entity);
+    entity_mut.remove::<FSMFrom>();
+    entity_mut.insert(fsm_to);
+    entity_mut.get_mut::<FSM<FSMFrom::Id>>().unwrap().current = FSMTo::ID;
+
+    for hook in on_enter {
+        hook(world, entity);
+    }
+
+    let at = world
+        .get_resource::<Time>()
+        .map(|time| time.elapsed())
+        .unwrap_or_default();
+
+    if let Some(mut history) = world.get_mut::<FSMHistory<FSMFrom::Id>>(entity) {
+        if history.is_empty() {
+            history.push(FSMHistoryEntry {
+                state: FSMFrom::ID,
+                at,
+                reason: "spawned",
+            });
+        }
+        history.push(FSMHistoryEntry {
+            state: FSMTo::ID,
+            at,
+            reason,
+        });
+    }
+
+    world.send_event(StateTransitioned {
+        entity,
+        from: FSMFrom::ID,
+        to: FSMTo::ID,
+        at,
+        reason,
+    });
+
+    Ok(())
+}
+
+/// Catches state components that were inserted or removed behind the FSM's back.
+/// Stray states are removed; a missing current state is only reported.
+pub fn fsm_check_single_state<S: FSMStateId>(world: &mut World) {
+    let mut agents = world.query::<(Entity, &FSM<S>)>();
+    let definition = world.resource::<FSMDefinition<S>>();
+
+    let mut strays = Vec::new();
+    for (entity, fsm) in agents.iter(world) {
+        for (state, entry) in definition.states.iter() {
+            let present = (entry.present)(world, entity);
+            if *state == fsm.current() && !present {
+                error!(
+                    "{:?} is in {:?} but has no {} component",
+                    entity, state, entry.name
+                );
+            }
+            if *state != fsm.current() && present {
+                error!(
+                    "{:?} is in {:?} but also has {}, removing it",
+                    entity,
+                    fsm.current(),
+                    entry.name
+                );
+                strays.push((entity, entry.remove));
+            }
+        }
+    }
+
+    for (entity, remove) in strays {
+        remove(world, entity);
+    }
+}
+
+/// Agents that haven't transitioned yet still get their starting state in the history.
+#[allow(clippy::type_complexity)]
+pub fn fsm_record_initial_state<S: FSMStateId>(
+    mut agents: Query<(&FSM<S>, &mut FSMHistory<S>), Added<FSMHistory<S>>>,
+    time: Res<Time>,
+) {
+    for (fsm, mut history) in &mut agents {
+        if history.is_empty() {
+            history.push(FSMHistoryEntry {
+                state: fsm.current(),
+                at: time.elapsed(),
+                reason: "spawned",
+            });
+        }
+    }
+}
+
+pub fn fsm_log_transitions<S: FSMStateId>(
+    mut transitioned_events: EventReader<StateTransitioned<S>>,
+) {
+    for event in transitioned_events.read() {
+        debug!(
+            "{:?} transitioned from {:?} to {:?} ({})",
+            event.entity, event.from, event.to, event.reason
+        );
+    }
+}
+
+pub fn fsm_report_rejected_transitions<S: FSMStateId>(
+    mut rejected_events: EventReader<FSMTransitionRejected<S>>,
+) {
+    for event in rejected_events.read() {
+        warn!(
+            "Rejected transition of {:?}: {:?}",
+            event.entity, event.error
+        );
+    }
+}
+
+pub trait AppFSMExt {
+    /// Registers an agent kind's state machine.
+    fn add_fsm<S: FSMStateId>(&mut self, definition: FSMDefinition<S>) -> &mut Self;
+}
+
+impl AppFSMExt for App {
+    fn add_fsm<S: FSMStateId>(&mut self, definition: FSMDefinition<S>) -> &mut Self {
+        self.insert_resource(definition)
+            .add_event::<FSMTransitionRejected<S>>()
+            .add_event::<StateTransitioned<S>>()
+            .add_systems(First, fsm_record_initial_state::<S>)
+            .add_systems(
+                Last,
+                (
+                    fsm_log_transitions::<S>,
+                    fsm_report_rejected_transitions::<S>,
+                    fsm_check_single_state::<S>,
+                ),
+            )
+    }
+}
@@ -1,23 +1,96 @@
 pub mod components;
-pub mod transitions;
+pub mod failure;
+pub mod machine;
 pub mod states;
+pub mod transitions;
 
+use crate::jobs::release_jobs_on_idle;
+use crate::plugins::SimulationSet;
 use bevy::prelude::*;
+use components::*;
+use machine::*;
 use states::*;
 
-pub use components::FSMIdle;
+pub fn villager_fsm_definition() -> FSMDefinition<VillagerState> {
+    use VillagerState::*;
+
+    FSMDefinition::new()
+        .state::<FSMIdle>()
+        .state::<FSMWalkingTo>()
+        .state::<FSMWalkingToHarvest>()
+        .state::<FSMHarvesting>()
+        .state::<FSMPickingUp>()
+        .state::<FSMBringingTo>()
+        .state::<FSMWalkingHome>()
+        .state::<FSMEating>()
+        .state::<FSMSleeping>()
+        .state::<FSMFetching>()
+        .state::<FSMSupplying>()
+        .state::<FSMBuilding>()
+        .transitions(
+            Idle,
+            &[
+                WalkingTo,
+                WalkingToHarvest,
+                PickingUp,
+                BringingTo,
+                WalkingHome,
+                Fetching,
+                Building,
+            ],
+        )
+        .transition(WalkingTo, Idle)
+        .transitions(WalkingToHarvest, &[Harvesting, Idle])
+        .transition(Harvesting, Idle)
+        .transitions(PickingUp, &[PickingUp, BringingTo, Idle])
+        .transitions(BringingTo, &[BringingTo, Idle])
+        .transitions(WalkingHome, &[Eating, Sleeping, Idle])
+        .transition(Eating, Idle)
+        .transition(Sleeping, Idle)
+        .transitions(Fetching, &[Supplying, Idle])
+        .transitions(Supplying, &[Building, Idle])
+        .transition(Building, Idle)
+        .on_enter(Idle, release_jobs_on_idle)
+}
 
 pub struct FSMPlugin;
 
 impl Plugin for FSMPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (
-            fsm_update_idle,
-            fsm_update_walking_to,
-            fsm_update_walking_to_harvest,
-            fsm_update_harvesting,
-            fsm_update_picking_up,
-            fsm_update_bringing_to,
-        ));
+        app.add_fsm(villager_fsm_definition());
+        app.add_event::<failure::TaskFailed>();
+        app.add_systems(
+            Update,
+            (
+                fsm_update_idle,
+                fsm_update_walking_to,
+                fsm_update_walking_to_harvest,
+                fsm_update_harvesting,
+                fsm_update_picking_up,
+                fsm_update_bringing_to,
+                fsm_update_walking_home,
+                fsm_update_eating,
+                fsm_update_sleeping,
+                fsm_update_fetching,
+                fsm_update_supplying,
+                fsm_update_building,
+            )
+                .in_set(SimulationSet),
+        );
+        // Before the state systems, so an interrupted state doesn't also finish this frame.
+        // Bringing wood is never interrupted, the delivery is almost done by then, and
+        // neither is supplying a construction site
+        app.add_systems(
+            PreUpdate,
+            (
+                fsm_interrupt_for_needs::<FSMWalkingTo>,
+                fsm_interrupt_for_needs::<FSMWalkingToHarvest>,
+                fsm_interrupt_for_needs::<FSMHarvesting>,
+                fsm_interrupt_for_needs::<FSMPickingUp>,
+                fsm_interrupt_for_needs::<FSMFetching>,
+                fsm_interrupt_for_needs::<FSMBuilding>,
+            )
+                .in_set(SimulationSet),
+        );
     }
-}
\ No newline at end of file
+}
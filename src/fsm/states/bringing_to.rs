@@ -1,51 +1,163 @@
 use bevy::prelude::*;
 
+use crate::assets::*;
 use crate::fsm::components::*;
+use crate::fsm::failure::*;
 use crate::fsm::transitions::*;
+use crate::inventory::*;
+use crate::item::*;
 use crate::item_drop::*;
-use crate::structure::wood_hut::WoodHut;
+use crate::navigation::*;
+use crate::random::SimulationRng;
+use crate::structure::{registry::Structure, stockpile::*};
 use crate::villager::actions::*;
-use crate::villager::villager::Villager;
+use crate::villager::{needs::Needs, villager::Villager};
+use crate::world::Terrain;
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn fsm_update_bringing_to(
     mut commands: Commands,
-    mut walker: Query<(Entity, &mut Transform, &Villager, &FSMBringingTo)>,
-    mut wood_pile: Query<&mut WoodPile>,
+    mut walker: Query<(
+        Entity,
+        &mut Transform,
+        &mut NavPath,
+        &Villager,
+        &Needs,
+        &mut Inventory,
+        &FSMBringingTo,
+    )>,
+    mut stockpiles: Query<(&mut ItemStack, &Stockpile)>,
     transforms: Query<&Transform, Without<FSMBringingTo>>,
-    children: Query<&Children, With<WoodHut>>,
+    storage: Query<(Entity, &Transform, &Children), (With<Structure>, Without<FSMBringingTo>)>,
     time: Res<Time>,
+    terrain: Res<Terrain>,
+    nav_grid: Res<NavGrid>,
+    mut rng: ResMut<SimulationRng>,
+    registry: Res<ItemRegistry>,
+    scene_assets: Res<SceneAssets>,
+    mut task_failed_events: EventWriter<TaskFailed>,
+    mut storage_full_events: EventWriter<StorageFull>,
 ) {
-    for (entity, mut transform, villager, fsm_bringing_to) in &mut walker {
-        if let Ok(target_transform) = transforms.get(fsm_bringing_to.target) {
-            walk_to(
-                &mut transform,
-                target_transform,
-                villager.movement_speed,
-                &time,
-            );
-            if transform.translation.distance(target_transform.translation)
-                < fsm_bringing_to.proximity
-            {
-                if let Some(held_resource) = fsm_bringing_to.held_resource {
-                    let held_wood_count = if let Ok(held_wood_pile) = wood_pile.get(held_resource) {
-                        held_wood_pile.count
-                    } else {
-                        0
-                    };
+    for (entity, mut transform, mut path, villager, needs, mut inventory, fsm_bringing_to) in
+        &mut walker
+    {
+        let target = transforms
+            .get(fsm_bringing_to.target)
+            .ok()
+            .map(|target_transform| {
+                (
+                    target_transform,
+                    storage
+                        .get(fsm_bringing_to.target)
+                        .ok()
+                        .and_then(|(_, _, children)| stockpile_of(children))
+                        .filter(|stockpile| stockpiles.contains(*stockpile)),
+                )
+            });
 
-                    let wood_hut_first_child = *children.get(fsm_bringing_to.target).unwrap().first().unwrap();
-
-                    println!("target wood pile: {:?} {:?}", wood_hut_first_child, wood_pile.get_mut(wood_hut_first_child));
+        let (target_transform, stockpile) = match target {
+            Some((target_transform, Some(stockpile))) => (target_transform, stockpile),
+            failed => {
+                let reason = if failed.is_none() {
+                    TaskFailureReason::TargetDespawned
+                } else {
+                    TaskFailureReason::NoStorage
+                };
+                // Put the items down instead of losing them
+                drop_inventory(
+                    &mut commands,
+                    &registry,
+                    &scene_assets,
+                    &mut inventory,
+                    transform.translation + Vec3::new(0.0, 0.5, 0.0),
+                    rng.stream("fsm_update_bringing_to"),
+                );
+                fsm_fail_task::<FSMBringingTo>(
+                    &mut commands,
+                    &mut task_failed_events,
+                    entity,
+                    fsm_bringing_to.target,
+                    reason,
+                );
+                continue;
+            }
+        };
 
-                    if let Ok(mut wood_hut_pile) = wood_pile.get_mut(wood_hut_first_child) {
-                        println!("wood_hut_pile: {:?}", wood_hut_pile);
-                        wood_hut_pile.count += held_wood_count;
-                    }
+        walk_to(
+            &mut transform,
+            &mut path,
+            fsm_bringing_to.target,
+            target_transform,
+            villager.movement_speed * needs.movement_penalty(),
+            &nav_grid,
+            &terrain,
+            &time,
+        );
+        if transform.translation.distance(target_transform.translation) < fsm_bringing_to.proximity
+        {
+            if let Ok((mut stored, stockpile)) = stockpiles.get_mut(stockpile) {
+                let item_type = stored.item_type;
+                let overflow = stockpile.deposit(&mut stored, inventory.take_all(item_type));
+                if overflow > 0 {
+                    // Whatever didn't fit goes on the ground next to the building
+                    spawn_item_drop(
+                        &mut commands,
+                        &registry,
+                        &scene_assets,
+                        item_type,
+                        overflow_position(target_transform),
+                        overflow,
+                        rng.stream("fsm_update_bringing_to"),
+                    );
+                }
+                if stockpile.room(&stored) == 0 {
+                    storage_full_events.send(StorageFull {
+                        building: fsm_bringing_to.target,
+                        item_type,
+                        overflow,
+                    });
+                }
+            }
 
-                    commands.entity(held_resource).despawn_recursive();
+            // Other items go on to storage that takes them, or on the ground if none does
+            if !inventory.is_empty() {
+                let next_storage = storage
+                    .iter()
+                    .filter(|(building, _, _)| *building != fsm_bringing_to.target)
+                    .filter(|(_, _, children)| {
+                        stockpile_of(children)
+                            .and_then(|stockpile| stockpiles.get(stockpile).ok())
+                            .is_some_and(|(stored, stockpile)| {
+                                inventory.count(stored.item_type) > 0 && stockpile.room(stored) > 0
+                            })
+                    })
+                    .min_by(|a, b| {
+                        let a_dist = a.1.translation.distance(transform.translation);
+                        let b_dist = b.1.translation.distance(transform.translation);
+                        a_dist.total_cmp(&b_dist)
+                    });
+                if let Some((next_storage, _, _)) = next_storage {
+                    fsm_transition_to::<FSMBringingTo>(
+                        &mut commands,
+                        entity,
+                        FSMBringingTo {
+                            target: next_storage,
+                            proximity: fsm_bringing_to.proximity,
+                        },
+                        "delivering the rest",
+                    );
+                    continue;
                 }
-                fsm_transition_to::<FSMBringingTo>(&mut commands, entity, FSMIdle);
+                drop_inventory(
+                    &mut commands,
+                    &registry,
+                    &scene_assets,
+                    &mut inventory,
+                    transform.translation + Vec3::new(0.0, 0.5, 0.0),
+                    rng.stream("fsm_update_bringing_to"),
+                );
             }
+            fsm_transition_to::<FSMBringingTo>(&mut commands, entity, FSMIdle, "delivered");
         }
     }
 }
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+
+use crate::fsm::components::*;
+use crate::fsm::failure::*;
+use crate::fsm::transitions::*;
+use crate::navigation::*;
+use crate::structure::construction::*;
+use crate::villager::{actions::*, needs::Needs, villager::Villager};
+use crate::world::Terrain;
+
+#[allow(clippy::too_many_arguments)]
+pub fn fsm_update_building(
+    mut commands: Commands,
+    mut walker: Query<(
+        Entity,
+        &mut Transform,
+        &mut NavPath,
+        &Villager,
+        &Needs,
+        &FSMBuilding,
+    )>,
+    mut sites: Query<(&Transform, &mut ConstructionSite), Without<FSMBuilding>>,
+    time: Res<Time>,
+    terrain: Res<Terrain>,
+    nav_grid: Res<NavGrid>,
+    mut structure_completed_events: EventReader<StructureCompleted>,
+    mut task_failed_events: EventWriter<TaskFailed>,
+) {
+    let structure_completed_events = structure_completed_events.read().collect::<Vec<_>>();
+
+    for (entity, mut transform, mut path, villager, needs, fsm_building) in &mut walker {
+        if structure_completed_events
+            .iter()
+            .any(|event| event.site == fsm_building.target)
+        {
+            fsm_transition_to::<FSMBuilding>(
+                &mut commands,
+                entity,
+                FSMIdle,
+                "construction finished",
+            );
+            continue;
+        }
+
+        let Ok((target_transform, mut site)) = sites.get_mut(fsm_building.target) else {
+            fsm_fail_task::<FSMBuilding>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_building.target,
+                TaskFailureReason::TargetDespawned,
+            );
+            continue;
+        };
+        if !site.is_supplied() {
+            fsm_fail_task::<FSMBuilding>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_building.target,
+                TaskFailureReason::InvalidTarget,
+            );
+            continue;
+        }
+
+        if transform.translation.distance(target_transform.translation) >= fsm_building.proximity {
+            walk_to(
+                &mut transform,
+                &mut path,
+                fsm_building.target,
+                target_transform,
+                villager.movement_speed * needs.movement_penalty(),
+                &nav_grid,
+                &terrain,
+                &time,
+            );
+            continue;
+        }
+
+        // Building is handiwork just like chopping, so it goes at the same pace
+        site.progress += time.delta_secs() * villager.harvesting_speed * needs.harvesting_penalty();
+    }
+}
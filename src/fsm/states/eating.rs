@@ -0,0 +1,29 @@
+use bevy::prelude::*;
+
+use crate::fsm::components::*;
+use crate::fsm::failure::*;
+use crate::fsm::transitions::*;
+use crate::structure::housing::Housing;
+use crate::villager::needs::*;
+
+/// Hunger itself is restored by `decay_needs`, this only decides when the meal is over.
+pub fn fsm_update_eating(
+    mut commands: Commands,
+    eaters: Query<(Entity, &Needs, &FSMEating)>,
+    houses: Query<(), With<Housing>>,
+    mut task_failed_events: EventWriter<TaskFailed>,
+) {
+    for (entity, needs, fsm_eating) in &eaters {
+        if !houses.contains(fsm_eating.house) {
+            fsm_fail_task::<FSMEating>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_eating.house,
+                TaskFailureReason::TargetDespawned,
+            );
+        } else if needs.hunger >= 1.0 {
+            fsm_transition_to::<FSMEating>(&mut commands, entity, FSMIdle, "finished eating");
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+use crate::fsm::components::*;
+use crate::fsm::failure::*;
+use crate::fsm::transitions::*;
+use crate::inventory::*;
+use crate::item::*;
+use crate::navigation::*;
+use crate::structure::{construction::ConstructionSite, registry::Structure, stockpile::*};
+use crate::villager::{actions::*, needs::Needs, villager::Villager};
+use crate::world::Terrain;
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn fsm_update_fetching(
+    mut commands: Commands,
+    mut walker: Query<(
+        Entity,
+        &mut Transform,
+        &mut NavPath,
+        &Villager,
+        &Needs,
+        &mut Inventory,
+        &FSMFetching,
+    )>,
+    wood_huts: Query<(&Transform, &Children), (With<Structure>, Without<FSMFetching>)>,
+    mut stockpiles: Query<&mut ItemStack, With<Stockpile>>,
+    sites: Query<&ConstructionSite>,
+    time: Res<Time>,
+    terrain: Res<Terrain>,
+    nav_grid: Res<NavGrid>,
+    mut task_failed_events: EventWriter<TaskFailed>,
+) {
+    for (entity, mut transform, mut path, villager, needs, mut inventory, fsm_fetching) in
+        &mut walker
+    {
+        let Ok(site) = sites.get(fsm_fetching.site) else {
+            fsm_fail_task::<FSMFetching>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_fetching.site,
+                TaskFailureReason::TargetDespawned,
+            );
+            continue;
+        };
+        let Ok((target_transform, children)) = wood_huts.get(fsm_fetching.target) else {
+            fsm_fail_task::<FSMFetching>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_fetching.target,
+                TaskFailureReason::TargetDespawned,
+            );
+            continue;
+        };
+        let Some(stockpile) =
+            stockpile_of(children).filter(|stockpile| stockpiles.contains(*stockpile))
+        else {
+            fsm_fail_task::<FSMFetching>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_fetching.target,
+                TaskFailureReason::NoStorage,
+            );
+            continue;
+        };
+
+        walk_to(
+            &mut transform,
+            &mut path,
+            fsm_fetching.target,
+            target_transform,
+            villager.movement_speed * needs.movement_penalty(),
+            &nav_grid,
+            &terrain,
+            &time,
+        );
+        if transform.translation.distance(target_transform.translation) >= fsm_fetching.proximity {
+            continue;
+        }
+
+        // Only as much as the site still needs, and as much as the villager can carry
+        let Ok(mut stored) = stockpiles.get_mut(stockpile) else {
+            continue;
+        };
+        let wanted = site.wood_needed().min(stored.count);
+        let taken = if stored.item_type == ItemType::Wood {
+            inventory.add(ItemType::Wood, wanted)
+        } else {
+            0
+        };
+        if taken == 0 {
+            fsm_fail_task::<FSMFetching>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_fetching.target,
+                TaskFailureReason::NoStorage,
+            );
+            continue;
+        }
+        stored.count -= taken;
+        fsm_transition_to::<FSMFetching>(
+            &mut commands,
+            entity,
+            FSMSupplying {
+                target: fsm_fetching.site,
+                proximity: fsm_fetching.proximity,
+            },
+            "took wood for a construction site",
+        );
+    }
+}
@@ -1,30 +1,74 @@
+use bevy::ecs::entity::Entities;
 use bevy::prelude::*;
 
 use crate::harvestable::harvestable::*;
-use crate::villager::villager::Villager;
+use crate::jobs::*;
+use crate::villager::{actions::*, needs::Needs, villager::Villager};
 
 use crate::fsm::components::*;
+use crate::fsm::failure::*;
 use crate::fsm::transitions::*;
 
+#[allow(clippy::too_many_arguments)]
 pub fn fsm_update_harvesting(
     mut commands: Commands,
-    gatherer: Query<(Entity, &FSMHarvesting, &Villager)>,
+    gatherer: Query<(Entity, &FSMHarvesting, &Villager, &Needs)>,
     mut harvestables: Query<&mut Harvestable>,
     time: Res<Time>,
+    job_board: Res<JobBoard>,
     mut harvestable_destroyed_events: EventReader<HarvestableDestroyed>,
-
+    mut task_failed_events: EventWriter<TaskFailed>,
+    entities: &Entities,
 ) {
     let harvestable_destroyed_events = harvestable_destroyed_events.read().collect::<Vec<_>>();
 
-    for (entity, fsm_gathering, villager) in &gatherer {
-        if let Ok(mut harvestable) = harvestables.get_mut(fsm_gathering.target) {
-            harvestable.health -= time.delta_secs() * villager.harvesting_speed;
+    for (entity, fsm_gathering, villager, needs) in &gatherer {
+        if harvestable_destroyed_events
+            .iter()
+            .any(|event| event.entity == fsm_gathering.target)
+        {
+            fsm_transition_to::<FSMHarvesting>(
+                &mut commands,
+                entity,
+                FSMIdle,
+                "harvestable destroyed",
+            );
+            continue;
         }
 
-        for event in harvestable_destroyed_events.iter() {
-            if event.entity == fsm_gathering.target {
-                fsm_transition_to::<FSMHarvesting>(&mut commands, entity, FSMIdle);
-            }
+        if harvestables.contains(fsm_gathering.target)
+            && !job_board.is_reserved_by(fsm_gathering.target, entity)
+        {
+            fsm_fail_task::<FSMHarvesting>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_gathering.target,
+                TaskFailureReason::TargetReserved,
+            );
+            continue;
+        }
+
+        if let Ok(mut harvestable) = harvestables.get_mut(fsm_gathering.target) {
+            harvest(
+                &mut harvestable,
+                villager.harvesting_speed * needs.harvesting_penalty(),
+                &time,
+            );
+        } else {
+            // Gone without a HarvestableDestroyed, e.g. fell into the underworld
+            let reason = if entities.contains(fsm_gathering.target) {
+                TaskFailureReason::InvalidTarget
+            } else {
+                TaskFailureReason::TargetDespawned
+            };
+            fsm_fail_task::<FSMHarvesting>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_gathering.target,
+                reason,
+            );
         }
     }
-}
\ No newline at end of file
+}
@@ -1,100 +1,248 @@
 use bevy::prelude::*;
-
-use rand::distr::weighted::WeightedIndex;
-use rand::distr::Distribution;
-use rand::Rng;
+use bevy::utils::HashMap;
 
 use crate::fsm::components::*;
 use crate::fsm::transitions::*;
+use crate::goap::GoapAgent;
 
 use crate::harvestable::tree::*;
+use crate::inventory::*;
+use crate::item::*;
 use crate::item_drop::*;
-use crate::structure::house::*;
-
-#[derive(Clone)]
-enum Decision {
-    WalkToHouse,
-    ChopTree,
-    PickUpWood,
-}
-
-const DECISIONS: [Decision; 3] = [
-    Decision::WalkToHouse,
-    Decision::ChopTree,
-    Decision::PickUpWood,
-];
-const WEIGHTS: [u32; 3] = [1, 2, 3];
+use crate::jobs::*;
+use crate::structure::construction::*;
+use crate::structure::housing::*;
+use crate::structure::registry::Structure;
+use crate::structure::stockpile::*;
+use crate::villager::{needs::*, population::Home, utility::*};
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn fsm_update_idle(
     mut commands: Commands,
-    idlers: Query<(Entity, &Transform), With<FSMIdle>>,
-    houses: Query<(Entity, &Transform), (With<House>, Without<FSMIdle>)>,
+    idlers: Query<
+        (
+            Entity,
+            &Transform,
+            &VillagerTraits,
+            &Needs,
+            &Inventory,
+            Option<&Home>,
+        ),
+        (With<FSMIdle>, Without<GoapAgent>),
+    >,
+    houses: Query<(Entity, &Transform), (With<Housing>, Without<FSMIdle>)>,
     trees: Query<(Entity, &Transform), (With<Tree>, Without<FSMIdle>)>,
-    wood_resources: Query<(Entity, &Transform, &WoodPile), (Without<FSMIdle>, With<ItemDrop>)>,
+    item_drops: Query<(Entity, &Transform, &ItemStack), (Without<FSMIdle>, With<ItemDrop>)>,
+    structures: Query<(Entity, &Transform, &Children), (With<Structure>, Without<FSMIdle>)>,
+    stockpiles: Query<(&ItemStack, &Stockpile)>,
+    construction_sites: Query<(Entity, &Transform, &ConstructionSite), Without<FSMIdle>>,
+    mut job_board: ResMut<JobBoard>,
 ) {
-    let houses_iter = houses.iter().collect::<Vec<_>>();
-    let trees_iter = trees.iter().collect::<Vec<_>>();
-    let wood_resources_iter = wood_resources.iter().collect::<Vec<_>>();
+    if idlers.is_empty() {
+        return;
+    }
+
+    let houses_iter = houses
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation))
+        .collect::<Vec<_>>();
+    let trees_iter = trees
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation))
+        .collect::<Vec<_>>();
+    // Every structure with storage, with what it holds, how much that can grow to and how
+    // much more fits
+    let storage = structures
+        .iter()
+        .filter_map(|(entity, transform, children)| {
+            let (stored, stockpile) = stockpiles.get(stockpile_of(children)?).ok()?;
+            Some((
+                entity,
+                transform.translation,
+                *stored,
+                stockpile.capacity,
+                stockpile.room(stored),
+            ))
+        })
+        .collect::<Vec<_>>();
+    let has_room_for = |item_type: ItemType| {
+        storage
+            .iter()
+            .any(|(_, _, stored, _, room)| stored.item_type == item_type && *room > 0)
+    };
+
+    // Only items with somewhere to go are worth picking up
+    let item_drops_iter = item_drops
+        .iter()
+        .filter(|(_, _, item_stack)| has_room_for(item_stack.item_type))
+        .map(|(entity, transform, item_stack)| {
+            (entity, transform.translation, item_stack.item_type)
+        })
+        .collect::<Vec<_>>();
+
+    let stocked_wood_huts_iter = storage
+        .iter()
+        .filter(|(_, _, stored, _, _)| stored.item_type == ItemType::Wood && stored.count > 0)
+        .map(|(entity, position, _, _, _)| (*entity, *position))
+        .collect::<Vec<_>>();
+    let construction_sites_iter = construction_sites
+        .iter()
+        .map(|(entity, transform, site)| (entity, transform.translation, site.wood_needed()))
+        .collect::<Vec<_>>();
 
-    let dist = WeightedIndex::new(&WEIGHTS).unwrap();
-    let mut rng = rand::rng();
+    let mut storage_totals = HashMap::<ItemType, (u32, u32)>::new();
+    for (_, _, stored, capacity, _) in storage.iter() {
+        let totals = storage_totals.entry(stored.item_type).or_default();
+        totals.0 += stored.count;
+        totals.1 += capacity;
+    }
+    let storage_fill = storage_totals
+        .into_iter()
+        .filter(|(_, (_, capacity))| *capacity > 0)
+        .map(|(item_type, (stored, capacity))| {
+            (item_type, (stored as f32 / capacity as f32).min(1.0))
+        })
+        .collect::<HashMap<_, _>>();
+
+    for (entity, transform, traits, needs, inventory, home) in &idlers {
+        let home = home.and_then(|home| {
+            houses_iter
+                .iter()
+                .find(|(house, _)| *house == home.house)
+                .copied()
+        });
 
-    let decision = DECISIONS[dist.sample(&mut rng)].clone();
+        let item_drops_iter = item_drops_iter
+            .iter()
+            .filter(|(_, _, item_type)| inventory.room_for(*item_type) > 0)
+            .copied()
+            .collect::<Vec<_>>();
+        let deliverable_storage_iter = storage
+            .iter()
+            .filter(|(_, _, stored, _, room)| *room > 0 && inventory.count(stored.item_type) > 0)
+            .map(|(entity, position, _, _, _)| (*entity, *position))
+            .collect::<Vec<_>>();
 
-    for (entity, transform) in &idlers {
-        match decision {
-            Decision::WalkToHouse => {
-                let (target_house, _target_house_transform) =
-                    houses_iter[rand::rng().random_range(0..houses_iter.len())];
+        let mut decision = decide(&DecisionContext {
+            villager: entity,
+            position: transform.translation,
+            traits: *traits,
+            needs: *needs,
+            houses: &houses_iter,
+            home,
+            trees: &trees_iter,
+            item_drops: &item_drops_iter,
+            deliverable_storage: &deliverable_storage_iter,
+            stocked_wood_huts: &stocked_wood_huts_iter,
+            construction_sites: &construction_sites_iter,
+            carried: inventory.total_count(),
+            storage_fill: &storage_fill,
+            job_board: &job_board,
+        });
 
+        // Only start on work whose job is really ours, otherwise fall back to the next best
+        decision.chosen = decision.ranked().into_iter().find(|candidate| {
+            candidate
+                .action
+                .job_target()
+                .is_none_or(|target| job_board.claim(target, entity))
+        });
+
+        match decision.chosen.map(|chosen| chosen.action) {
+            Some(IdleAction::WalkHome { house }) => {
                 fsm_transition_to::<FSMIdle>(
                     &mut commands,
                     entity,
                     FSMWalkingTo {
-                        target: target_house,
+                        target: house,
                         proximity: 0.2,
                     },
+                    "decided to walk home",
                 );
             }
-            Decision::ChopTree => {
-                if trees_iter.len() > 0 {
-                    let (target_tree, _target_tree_transform) =
-                        trees_iter[rand::rng().random_range(0..trees_iter.len())];
-
-                    fsm_transition_to::<FSMIdle>(
-                        &mut commands,
-                        entity,
-                        FSMWalkingToHarvest {
-                            target: target_tree,
-                            proximity: 0.2,
-                        },
-                    );
-                }
+            Some(IdleAction::ChopTree { tree }) => {
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMWalkingToHarvest {
+                        target: tree,
+                        proximity: 0.2,
+                    },
+                    "decided to chop a tree",
+                );
             }
-            Decision::PickUpWood => {
-                if wood_resources_iter.len() > 0 {
-                    // find closest wood pile
-                    let target_wood = wood_resources_iter
-                        .iter()
-                        .min_by(|a, b| {
-                            let a_dist = a.1.translation.distance(transform.translation);
-                            let b_dist = b.1.translation.distance(transform.translation);
-                            a_dist.partial_cmp(&b_dist).unwrap()
-                        })
-                        .unwrap();
-
-                    let (target_wood, _target_wood_transform, _target_wood_pile) = *target_wood;
-
-                    fsm_transition_to::<FSMIdle>(
-                        &mut commands,
-                        entity,
-                        FSMPickingUp {
-                            target: target_wood,
-                            proximity: 0.2,
-                        },
-                    );
-                }
+            Some(IdleAction::HaulItems { item_drop }) => {
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMPickingUp {
+                        target: item_drop,
+                        proximity: 0.2,
+                    },
+                    "decided to pick up items",
+                );
+            }
+            Some(IdleAction::DeliverItems { storage }) => {
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMBringingTo {
+                        target: storage,
+                        proximity: 0.2,
+                    },
+                    "decided to deliver carried items",
+                );
+            }
+            Some(IdleAction::SupplySite { site, wood_hut }) => {
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMFetching {
+                        target: wood_hut,
+                        site,
+                        proximity: 0.2,
+                    },
+                    "decided to fetch wood for a construction site",
+                );
+            }
+            Some(IdleAction::BuildSite { site }) => {
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMBuilding {
+                        target: site,
+                        proximity: 0.2,
+                    },
+                    "decided to work on a construction site",
+                );
             }
+            Some(IdleAction::Eat { house }) => {
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMWalkingHome {
+                        target: house,
+                        need: Need::Hunger,
+                        proximity: 0.2,
+                    },
+                    "decided to eat",
+                );
+            }
+            Some(IdleAction::Sleep { house }) => {
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMWalkingHome {
+                        target: house,
+                        need: Need::Energy,
+                        proximity: 0.2,
+                    },
+                    "decided to sleep",
+                );
+            }
+            None => {}
         }
+
+        commands.entity(entity).insert(decision);
     }
 }
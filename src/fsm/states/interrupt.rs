@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+use crate::fsm::components::*;
+use crate::fsm::machine::FSMState;
+use crate::fsm::transitions::*;
+use crate::structure::housing::Housing;
+use crate::villager::needs::*;
+
+/// Sends villagers in state `S` back to idle once a need becomes critical, so they can
+/// go take care of it. Only registered for states that can be dropped halfway.
+pub fn fsm_interrupt_for_needs<S: FSMState<Id = VillagerState>>(
+    mut commands: Commands,
+    villagers: Query<(Entity, &Needs), With<S>>,
+    houses: Query<(), With<Housing>>,
+) {
+    // Without a house there's nowhere to go, so keep working
+    if houses.is_empty() {
+        return;
+    }
+
+    for (entity, needs) in &villagers {
+        if needs.critical().is_some() {
+            fsm_transition_to::<S>(
+                &mut commands,
+                entity,
+                FSMIdle,
+                "interrupted by a critical need",
+            );
+        }
+    }
+}
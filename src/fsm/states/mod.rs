@@ -15,3 +15,24 @@ pub use picking_up::*;
 
 pub mod bringing_to;
 pub use bringing_to::*;
+
+pub mod walking_home;
+pub use walking_home::*;
+
+pub mod eating;
+pub use eating::*;
+
+pub mod sleeping;
+pub use sleeping::*;
+
+pub mod fetching;
+pub use fetching::*;
+
+pub mod supplying;
+pub use supplying::*;
+
+pub mod building;
+pub use building::*;
+
+pub mod interrupt;
+pub use interrupt::*;
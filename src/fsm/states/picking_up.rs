@@ -1,63 +1,189 @@
-use bevy::prelude::*;
+// After a pickup, other items of the same type this close are picked up too before hauling
+const GATHER_RADIUS: f32 = 2.0;
+
+////////////////////////////////////////////////////////////////
 
-use rand::Rng;
+use bevy::ecs::entity::Entities;
+use bevy::prelude::*;
 
 use crate::fsm::components::*;
+use crate::fsm::failure::*;
 use crate::fsm::transitions::*;
 
+use crate::inventory::*;
+use crate::item::*;
 use crate::item_drop::*;
+use crate::jobs::*;
 
-use crate::structure::wood_hut::WoodHut;
-use crate::villager::{actions::*, villager::Villager};
-
-use crate::assets::*;
+use crate::navigation::*;
+use crate::structure::{registry::Structure, stockpile::*};
+use crate::villager::{actions::*, needs::Needs, villager::Villager};
+use crate::world::Terrain;
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn fsm_update_picking_up(
     mut commands: Commands,
-    mut walker: Query<(Entity, &mut Transform, &Villager, &FSMPickingUp)>,
-    wood_huts: Query<(Entity, &Transform), (With<WoodHut>, Without<FSMPickingUp>)>,
-    wood_resources: Query<(Entity, &Transform, &WoodPile), (Without<FSMPickingUp>, With<ItemDrop>)>,
+    mut walker: Query<(
+        Entity,
+        &mut Transform,
+        &mut NavPath,
+        &Villager,
+        &Needs,
+        &mut Inventory,
+        &FSMPickingUp,
+    )>,
+    storage: Query<(Entity, &Transform, &Children), (With<Structure>, Without<FSMPickingUp>)>,
+    stockpiles: Query<(&ItemStack, &Stockpile), Without<ItemDrop>>,
+    mut item_drops: Query<(&Transform, &mut ItemStack), (Without<FSMPickingUp>, With<ItemDrop>)>,
     time: Res<Time>,
-    scene_assets: Res<SceneAssets>,
+    terrain: Res<Terrain>,
+    nav_grid: Res<NavGrid>,
+    mut job_board: ResMut<JobBoard>,
+    mut task_failed_events: EventWriter<TaskFailed>,
+    entities: &Entities,
 ) {
-    let wood_huts_iter = wood_huts.iter().collect::<Vec<_>>();
-
-    for (entity, mut transform, villager, fsm_picking_up) in &mut walker {
-        if let Ok((target_entity, target_transform, target_wood_pile)) = wood_resources.get(fsm_picking_up.target) {
-            walk_to(&mut transform, target_transform, villager.movement_speed, &time);
-
-            println!("distance vs proximity: {:?} {:?}", transform.translation.distance(target_transform.translation), fsm_picking_up.proximity);
-            if transform.translation.distance(target_transform.translation) < fsm_picking_up.proximity {
-                // Bring to random wood_hut
-                let (target_wood_hut, _target_wood_hut_transform) =
-                    wood_huts_iter[rand::rng().random_range(0..wood_huts_iter.len())];
-
-                // Make villager hold the wood pile
-                let mut held_wood_entity = Entity::PLACEHOLDER;
-
-                commands.entity(entity).with_children(|children| {
-                    held_wood_entity = children
-                        .spawn((
-                            WoodPileModel::new(&scene_assets, target_wood_pile.count),
-                            Transform::from_translation(Vec3::new(0.0, 0.95, 0.0)),
-                        ))
-                        .id()
-                });
-
-                fsm_transition_to::<FSMPickingUp>(
-                    &mut commands,
-                    entity,
-                    FSMBringingTo {
-                        target: target_wood_hut,
-                        held_resource: Some(held_wood_entity),
-                        proximity: 0.2,
-                    },
-                );
+    for (entity, mut transform, mut path, villager, needs, mut inventory, fsm_picking_up) in
+        &mut walker
+    {
+        let target_entity = fsm_picking_up.target;
+        let Ok((target_transform, target_stack)) = item_drops.get(target_entity) else {
+            let reason = if entities.contains(target_entity) {
+                TaskFailureReason::InvalidTarget
+            } else {
+                TaskFailureReason::TargetDespawned
+            };
+            fsm_fail_task::<FSMPickingUp>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                target_entity,
+                reason,
+            );
+            continue;
+        };
+        let (target_transform, item_type) = (*target_transform, target_stack.item_type);
+
+        if !job_board.is_reserved_by(target_entity, entity) {
+            fsm_fail_task::<FSMPickingUp>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                target_entity,
+                TaskFailureReason::TargetReserved,
+            );
+            continue;
+        }
+
+        // Bring to the nearest storage for this type of item that still has room
+        let Some((target_storage, _, _)) = storage
+            .iter()
+            .filter(|(_, _, children)| {
+                stockpile_of(children)
+                    .and_then(|stockpile| stockpiles.get(stockpile).ok())
+                    .is_some_and(|(stored, stockpile)| {
+                        stored.item_type == item_type && stockpile.room(stored) > 0
+                    })
+            })
+            .min_by(|a, b| {
+                let a_dist = a.1.translation.distance(transform.translation);
+                let b_dist = b.1.translation.distance(transform.translation);
+                a_dist.total_cmp(&b_dist)
+            })
+        else {
+            fsm_fail_task::<FSMPickingUp>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                target_entity,
+                TaskFailureReason::NoStorage,
+            );
+            continue;
+        };
+
+        walk_to(
+            &mut transform,
+            &mut path,
+            target_entity,
+            &target_transform,
+            villager.movement_speed * needs.movement_penalty(),
+            &nav_grid,
+            &terrain,
+            &time,
+        );
 
+        if transform.translation.distance(target_transform.translation) >= fsm_picking_up.proximity
+        {
+            continue;
+        }
+
+        // Take what fits, the rest stays on the ground for someone else
+        if let Ok((_, mut target_stack)) = item_drops.get_mut(target_entity) {
+            let picked_up = inventory.add(item_type, target_stack.count);
+            if picked_up == target_stack.count {
                 commands.entity(target_entity).despawn_recursive();
+            } else {
+                target_stack.count -= picked_up;
+                job_board.release(target_entity, entity);
             }
+        }
+
+        // Nothing fit, so there is nothing to bring anywhere
+        if inventory.is_empty() {
+            fsm_fail_task::<FSMPickingUp>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                target_entity,
+                TaskFailureReason::CannotCarry,
+            );
+            continue;
+        }
+
+        let position = transform.translation;
+        let mut nearby_drops = if inventory.room_for(item_type) > 0 {
+            job_board
+                .jobs()
+                .filter(|job| {
+                    job.kind == JobKind::Haul
+                        && job.target != target_entity
+                        && job_board.is_available(job.target, entity)
+                })
+                .filter_map(|job| {
+                    let (drop_transform, drop_stack) = item_drops.get(job.target).ok()?;
+                    (drop_stack.item_type == item_type)
+                        .then(|| (job.target, drop_transform.translation.distance(position)))
+                })
+                .filter(|(_, distance)| *distance < GATHER_RADIUS)
+                .collect::<Vec<_>>()
+        } else {
+            Vec::new()
+        };
+        nearby_drops.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let next_drop = nearby_drops
+            .into_iter()
+            .map(|(item_drop, _)| item_drop)
+            .find(|item_drop| job_board.claim(*item_drop, entity));
+
+        if let Some(next_drop) = next_drop {
+            fsm_transition_to::<FSMPickingUp>(
+                &mut commands,
+                entity,
+                FSMPickingUp {
+                    target: next_drop,
+                    proximity: fsm_picking_up.proximity,
+                },
+                "picking up more items",
+            );
         } else {
-            fsm_transition_to::<FSMPickingUp>(&mut commands, entity, FSMIdle);
+            fsm_transition_to::<FSMPickingUp>(
+                &mut commands,
+                entity,
+                FSMBringingTo {
+                    target: target_storage,
+                    proximity: 0.2,
+                },
+                "picked up items",
+            );
         }
     }
 }
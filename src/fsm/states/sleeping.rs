@@ -0,0 +1,29 @@
+use bevy::prelude::*;
+
+use crate::fsm::components::*;
+use crate::fsm::failure::*;
+use crate::fsm::transitions::*;
+use crate::structure::housing::Housing;
+use crate::villager::needs::*;
+
+/// Energy itself is restored by `decay_needs`, this only decides when to wake up.
+pub fn fsm_update_sleeping(
+    mut commands: Commands,
+    sleepers: Query<(Entity, &Needs, &FSMSleeping)>,
+    houses: Query<(), With<Housing>>,
+    mut task_failed_events: EventWriter<TaskFailed>,
+) {
+    for (entity, needs, fsm_sleeping) in &sleepers {
+        if !houses.contains(fsm_sleeping.house) {
+            fsm_fail_task::<FSMSleeping>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_sleeping.house,
+                TaskFailureReason::TargetDespawned,
+            );
+        } else if needs.energy >= 1.0 {
+            fsm_transition_to::<FSMSleeping>(&mut commands, entity, FSMIdle, "woke up");
+        }
+    }
+}
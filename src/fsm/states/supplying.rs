@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+use crate::fsm::components::*;
+use crate::fsm::failure::*;
+use crate::fsm::transitions::*;
+use crate::inventory::*;
+use crate::item::*;
+use crate::navigation::*;
+use crate::structure::construction::ConstructionSite;
+use crate::villager::{actions::*, needs::Needs, villager::Villager};
+use crate::world::Terrain;
+
+/// Anything left over once the site has all it needs stays in the inventory, idle
+/// villagers bring it back to a hut.
+pub fn fsm_update_supplying(
+    mut commands: Commands,
+    mut walker: Query<(
+        Entity,
+        &mut Transform,
+        &mut NavPath,
+        &Villager,
+        &Needs,
+        &mut Inventory,
+        &FSMSupplying,
+    )>,
+    mut sites: Query<(&Transform, &mut ConstructionSite), Without<FSMSupplying>>,
+    time: Res<Time>,
+    terrain: Res<Terrain>,
+    nav_grid: Res<NavGrid>,
+    mut task_failed_events: EventWriter<TaskFailed>,
+) {
+    for (entity, mut transform, mut path, villager, needs, mut inventory, fsm_supplying) in
+        &mut walker
+    {
+        let Ok((target_transform, mut site)) = sites.get_mut(fsm_supplying.target) else {
+            fsm_fail_task::<FSMSupplying>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_supplying.target,
+                TaskFailureReason::TargetDespawned,
+            );
+            continue;
+        };
+
+        walk_to(
+            &mut transform,
+            &mut path,
+            fsm_supplying.target,
+            target_transform,
+            villager.movement_speed * needs.movement_penalty(),
+            &nav_grid,
+            &terrain,
+            &time,
+        );
+        if transform.translation.distance(target_transform.translation) >= fsm_supplying.proximity {
+            continue;
+        }
+
+        let supplied = inventory.take(ItemType::Wood, site.wood_needed());
+        site.delivered += supplied;
+        if site.is_supplied() {
+            fsm_transition_to::<FSMSupplying>(
+                &mut commands,
+                entity,
+                FSMBuilding {
+                    target: fsm_supplying.target,
+                    proximity: fsm_supplying.proximity,
+                },
+                "supplied the construction site",
+            );
+        } else {
+            fsm_transition_to::<FSMSupplying>(
+                &mut commands,
+                entity,
+                FSMIdle,
+                "delivered wood to the site",
+            );
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+
+use crate::fsm::components::*;
+use crate::fsm::failure::*;
+use crate::fsm::transitions::*;
+use crate::navigation::*;
+use crate::structure::housing::Housing;
+use crate::villager::{actions::*, needs::*, villager::Villager};
+use crate::world::Terrain;
+
+pub fn fsm_update_walking_home(
+    mut commands: Commands,
+    mut walker: Query<(
+        Entity,
+        &mut Transform,
+        &mut NavPath,
+        &Villager,
+        &Needs,
+        &FSMWalkingHome,
+    )>,
+    houses: Query<&Transform, (With<Housing>, Without<FSMWalkingHome>)>,
+    time: Res<Time>,
+    terrain: Res<Terrain>,
+    nav_grid: Res<NavGrid>,
+    mut task_failed_events: EventWriter<TaskFailed>,
+) {
+    for (entity, mut transform, mut path, villager, needs, fsm_walking_home) in &mut walker {
+        let Ok(house_transform) = houses.get(fsm_walking_home.target) else {
+            fsm_fail_task::<FSMWalkingHome>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_walking_home.target,
+                TaskFailureReason::TargetDespawned,
+            );
+            continue;
+        };
+
+        walk_to(
+            &mut transform,
+            &mut path,
+            fsm_walking_home.target,
+            house_transform,
+            villager.movement_speed * needs.movement_penalty(),
+            &nav_grid,
+            &terrain,
+            &time,
+        );
+        if transform.translation.distance(house_transform.translation) < fsm_walking_home.proximity
+        {
+            let house = fsm_walking_home.target;
+            match fsm_walking_home.need {
+                Need::Hunger => fsm_transition_to::<FSMWalkingHome>(
+                    &mut commands,
+                    entity,
+                    FSMEating { house },
+                    "arrived home to eat",
+                ),
+                Need::Energy => fsm_transition_to::<FSMWalkingHome>(
+                    &mut commands,
+                    entity,
+                    FSMSleeping { house },
+                    "arrived home to sleep",
+                ),
+            }
+        }
+    }
+}
@@ -1,23 +1,54 @@
 use bevy::prelude::*;
 
-use crate::fsm::transitions::*;
 use crate::fsm::components::*;
+use crate::fsm::failure::*;
+use crate::fsm::transitions::*;
 use crate::villager::actions::*;
 
-use crate::villager::villager::Villager;
+use crate::navigation::*;
+use crate::villager::{needs::Needs, villager::Villager};
+use crate::world::Terrain;
 
 pub fn fsm_update_walking_to(
     mut commands: Commands,
-    mut walker: Query<(Entity, &mut Transform, &Villager, &FSMWalkingTo)>,
+    mut walker: Query<(
+        Entity,
+        &mut Transform,
+        &mut NavPath,
+        &Villager,
+        &Needs,
+        &FSMWalkingTo,
+    )>,
     transforms: Query<&Transform, Without<FSMWalkingTo>>,
     time: Res<Time>,
+    terrain: Res<Terrain>,
+    nav_grid: Res<NavGrid>,
+    mut task_failed_events: EventWriter<TaskFailed>,
 ) {
-    for (entity, mut transform, villager, fsm_walking) in &mut walker {
+    for (entity, mut transform, mut path, villager, needs, fsm_walking) in &mut walker {
         if let Ok(target_transform) = transforms.get(fsm_walking.target) {
-            walk_to(&mut transform, target_transform, villager.movement_speed, &time);
-            if transform.translation.distance(target_transform.translation) < fsm_walking.proximity {
-                fsm_transition_to::<FSMWalkingTo>(&mut commands, entity, FSMIdle  );
+            walk_to(
+                &mut transform,
+                &mut path,
+                fsm_walking.target,
+                target_transform,
+                villager.movement_speed * needs.movement_penalty(),
+                &nav_grid,
+                &terrain,
+                &time,
+            );
+            if transform.translation.distance(target_transform.translation) < fsm_walking.proximity
+            {
+                fsm_transition_to::<FSMWalkingTo>(&mut commands, entity, FSMIdle, "arrived");
             }
+        } else {
+            fsm_fail_task::<FSMWalkingTo>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_walking.target,
+                TaskFailureReason::TargetDespawned,
+            );
         }
     }
-}
\ No newline at end of file
+}
@@ -1,35 +1,105 @@
 use bevy::prelude::*;
 
-use crate::fsm::transitions::*;
 use crate::fsm::components::*;
-use crate::harvestable::HarvestableDestroyed;
+use crate::fsm::failure::*;
+use crate::fsm::transitions::*;
+use crate::harvestable::harvestable::*;
+use crate::jobs::*;
 use crate::villager::actions::*;
 
-use crate::villager::villager::Villager;
+use crate::navigation::*;
+use crate::villager::{needs::Needs, villager::Villager};
+use crate::world::Terrain;
 
+#[allow(clippy::too_many_arguments)]
 pub fn fsm_update_walking_to_harvest(
     mut commands: Commands,
-    mut walker: Query<(Entity, &mut Transform, &Villager, &FSMWalkingToHarvest)>,
+    mut walker: Query<(
+        Entity,
+        &mut Transform,
+        &mut NavPath,
+        &Villager,
+        &Needs,
+        &FSMWalkingToHarvest,
+    )>,
     transforms: Query<&Transform, Without<FSMWalkingToHarvest>>,
+    harvestables: Query<(), With<Harvestable>>,
     time: Res<Time>,
+    terrain: Res<Terrain>,
+    nav_grid: Res<NavGrid>,
+    job_board: Res<JobBoard>,
     mut harvestable_destroyed_events: EventReader<HarvestableDestroyed>,
+    mut task_failed_events: EventWriter<TaskFailed>,
 ) {
     let harvestable_destroyed_events = harvestable_destroyed_events.read().collect::<Vec<_>>();
 
-    for (entity, mut transform, villager, fsm_walking) in &mut walker {
-        if let Ok(target_transform) = transforms.get(fsm_walking.target) {
-            walk_to(&mut transform, target_transform, villager.movement_speed, &time);
-            if transform.translation.distance(target_transform.translation) < fsm_walking.proximity {
-                fsm_transition_to::<FSMWalkingToHarvest>(&mut commands, entity, FSMHarvesting {
-                    target: fsm_walking.target,
-                });
-            }
+    for (entity, mut transform, mut path, villager, needs, fsm_walking) in &mut walker {
+        if harvestable_destroyed_events
+            .iter()
+            .any(|event| event.entity == fsm_walking.target)
+        {
+            fsm_fail_task::<FSMWalkingToHarvest>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_walking.target,
+                TaskFailureReason::TargetDestroyed,
+            );
+            continue;
         }
 
-        for event in harvestable_destroyed_events.iter() {
-            if event.entity == fsm_walking.target {
-                fsm_transition_to::<FSMWalkingToHarvest>(&mut commands, entity, FSMIdle);
-            }
+        let Ok(target_transform) = transforms.get(fsm_walking.target) else {
+            fsm_fail_task::<FSMWalkingToHarvest>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_walking.target,
+                TaskFailureReason::TargetDespawned,
+            );
+            continue;
+        };
+
+        if !harvestables.contains(fsm_walking.target) {
+            fsm_fail_task::<FSMWalkingToHarvest>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_walking.target,
+                TaskFailureReason::InvalidTarget,
+            );
+            continue;
+        }
+
+        if !job_board.is_reserved_by(fsm_walking.target, entity) {
+            fsm_fail_task::<FSMWalkingToHarvest>(
+                &mut commands,
+                &mut task_failed_events,
+                entity,
+                fsm_walking.target,
+                TaskFailureReason::TargetReserved,
+            );
+            continue;
+        }
+
+        walk_to(
+            &mut transform,
+            &mut path,
+            fsm_walking.target,
+            target_transform,
+            villager.movement_speed * needs.movement_penalty(),
+            &nav_grid,
+            &terrain,
+            &time,
+        );
+        if transform.translation.distance(target_transform.translation) < fsm_walking.proximity {
+            fsm_transition_to::<FSMWalkingToHarvest>(
+                &mut commands,
+                entity,
+                FSMHarvesting {
+                    target: fsm_walking.target,
+                },
+                "arrived at harvestable",
+            );
         }
     }
-}
\ No newline at end of file
+}
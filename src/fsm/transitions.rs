@@ -1,14 +1,18 @@
 use bevy::prelude::*;
 
-use std::any::type_name;
-use std::fmt::Debug;
+use crate::fsm::machine::*;
 
-pub fn fsm_transition_to<FSMFrom: Bundle>(
+pub fn fsm_transition_to<FSMFrom: FSMState>(
     commands: &mut Commands,
     entity: Entity,
-    fsm_to: impl Bundle + Debug
+    fsm_to: impl FSMState<Id = FSMFrom::Id>,
+    reason: &'static str,
 ) {
-    commands.entity(entity).remove::<FSMFrom>();
-    println!("Transitioned from {:?} to {:?}", type_name::<FSMFrom>(), &fsm_to);
-    commands.entity(entity).insert(fsm_to);
-}
\ No newline at end of file
+    commands
+        .entity(entity)
+        .queue(move |entity: Entity, world: &mut World| {
+            if let Err(error) = apply_transition::<FSMFrom, _>(world, entity, fsm_to, reason) {
+                world.send_event(FSMTransitionRejected { entity, error });
+            }
+        });
+}
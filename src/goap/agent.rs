@@ -0,0 +1,270 @@
+// Closer than this to something counts as being near it
+const NEAR_DISTANCE: f32 = 0.5;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+
+use std::collections::VecDeque;
+
+use crate::fsm::components::*;
+use crate::fsm::failure::TaskFailed;
+use crate::fsm::transitions::*;
+use crate::goap::planner::*;
+use crate::harvestable::harvestable::*;
+use crate::inventory::*;
+use crate::item::*;
+use crate::item_drop::ItemDrop;
+use crate::jobs::*;
+use crate::structure::{housing::Housing, registry::Structure, stockpile::*};
+use crate::villager::needs::Needs;
+
+/// Makes a villager pursue a goal with a plan instead of deciding from scratch whenever
+/// it is idle. Each plan step is carried out by the regular FSM states.
+#[derive(Component, Debug)]
+pub struct GoapAgent {
+    pub goal: GoapGoal,
+    pub plan: VecDeque<GoapAction>,
+    /// The step being carried out and the entity it targets.
+    pub current: Option<(GoapAction, Entity)>,
+    pub needs_replan: bool,
+}
+
+impl GoapAgent {
+    pub fn new(goal: GoapGoal) -> Self {
+        Self {
+            goal,
+            plan: VecDeque::new(),
+            current: None,
+            needs_replan: true,
+        }
+    }
+}
+
+fn nearest(position: Vec3, targets: impl Iterator<Item = (Entity, Vec3)>) -> Option<(Entity, f32)> {
+    targets
+        .map(|(entity, target)| (entity, position.distance(target)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Claims the nearest target whose job is still free, moving on to the next nearest
+/// whenever a claim fails.
+fn claim_nearest(
+    job_board: &mut JobBoard,
+    villager: Entity,
+    position: Vec3,
+    mut targets: Vec<(Entity, Vec3)>,
+) -> Option<Entity> {
+    targets.sort_by(|a, b| position.distance(a.1).total_cmp(&position.distance(b.1)));
+    targets
+        .into_iter()
+        .map(|(target, _)| target)
+        .find(|target| job_board.claim(*target, villager))
+}
+
+pub fn goap_replan_on_harvestable_destroyed(
+    mut agents: Query<&mut GoapAgent>,
+    mut harvestable_destroyed_events: EventReader<HarvestableDestroyed>,
+) {
+    for event in harvestable_destroyed_events.read() {
+        for mut agent in agents.iter_mut() {
+            if agent
+                .current
+                .is_some_and(|(_, target)| target == event.entity)
+            {
+                agent.needs_replan = true;
+            }
+        }
+    }
+}
+
+pub fn goap_replan_on_task_failed(
+    mut agents: Query<&mut GoapAgent>,
+    mut task_failed_events: EventReader<TaskFailed>,
+) {
+    for event in task_failed_events.read() {
+        if let Ok(mut agent) = agents.get_mut(event.entity) {
+            agent.needs_replan = true;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn goap_update_idle(
+    mut commands: Commands,
+    mut idlers: Query<(Entity, &Transform, &Needs, &Inventory, &mut GoapAgent), With<FSMIdle>>,
+    houses: Query<(Entity, &Transform), (With<Housing>, Without<FSMIdle>)>,
+    wood_huts: Query<(Entity, &Transform, &Children), (With<Structure>, Without<FSMIdle>)>,
+    stockpiles: Query<(&ItemStack, &Stockpile)>,
+    transforms: Query<&Transform, Without<FSMIdle>>,
+    item_drops: Query<&ItemStack, With<ItemDrop>>,
+    mut job_board: ResMut<JobBoard>,
+) {
+    let hut_wood = wood_huts
+        .iter()
+        .filter_map(|(_, _, children)| stockpiles.get(stockpile_of(children)?).ok())
+        .filter(|(stored, _)| stored.item_type == ItemType::Wood)
+        .map(|(stored, _)| stored.count)
+        .sum::<u32>();
+
+    for (entity, transform, needs, inventory, mut agent) in &mut idlers {
+        let position = transform.translation;
+        agent.current = None;
+
+        let available = |kind: JobKind| {
+            job_board
+                .jobs()
+                .filter(|job| job.kind == kind && job_board.is_available(job.target, entity))
+                .filter_map(|job| Some((job.target, transforms.get(job.target).ok()?.translation)))
+                .collect::<Vec<_>>()
+        };
+        let trees = available(JobKind::Harvest);
+        // The plan only knows about wood, other items are left to the other villagers
+        let wood_drops = available(JobKind::Haul)
+            .into_iter()
+            .filter(|(item_drop, _)| {
+                item_drops
+                    .get(*item_drop)
+                    .is_ok_and(|item_stack| item_stack.item_type == ItemType::Wood)
+            })
+            .collect::<Vec<_>>();
+        let nearest_house = nearest(
+            position,
+            houses
+                .iter()
+                .map(|(house, transform)| (house, transform.translation)),
+        );
+
+        // Needs come before any goal, the plan is picked up again afterwards
+        if let (Some(need), Some((house, _))) = (needs.critical(), nearest_house) {
+            agent.needs_replan = true;
+            fsm_transition_to::<FSMIdle>(
+                &mut commands,
+                entity,
+                FSMWalkingHome {
+                    target: house,
+                    need,
+                    proximity: 0.2,
+                },
+                "taking care of a critical need",
+            );
+            continue;
+        }
+
+        // Full huts don't count, there is nothing left to bring them
+        let nearest_hut = nearest(
+            position,
+            wood_huts
+                .iter()
+                .filter(|(_, _, children)| {
+                    stockpile_of(children)
+                        .and_then(|stockpile| stockpiles.get(stockpile).ok())
+                        .is_some_and(|(stored, stockpile)| {
+                            stored.item_type == ItemType::Wood && stockpile.room(stored) > 0
+                        })
+                })
+                .map(|(hut, transform, _)| (hut, transform.translation)),
+        );
+
+        let state = GoapState {
+            has_wood: inventory.count(ItemType::Wood) > 0,
+            near_tree: nearest(position, trees.iter().copied())
+                .is_some_and(|(_, distance)| distance < NEAR_DISTANCE),
+            near_hut: nearest_hut.is_some_and(|(_, distance)| distance < NEAR_DISTANCE),
+            near_house: nearest_house.is_some_and(|(_, distance)| distance < NEAR_DISTANCE),
+            trees_available: trees.len() as u32,
+            // Hauling only makes sense with somewhere to haul to
+            wood_drops: if nearest_hut.is_some() {
+                wood_drops.len() as u32
+            } else {
+                0
+            },
+            hut_wood,
+        };
+
+        if agent.needs_replan || agent.plan.is_empty() {
+            agent.plan = plan(state, agent.goal).unwrap_or_default().into();
+            agent.needs_replan = false;
+        }
+
+        let Some(action) = agent.plan.pop_front() else {
+            continue;
+        };
+
+        match action {
+            GoapAction::WalkToTree | GoapAction::ChopTree => {
+                // Walking to a tree ends in chopping it, so the chop step comes along
+                if agent.plan.front() == Some(&GoapAction::ChopTree) {
+                    agent.plan.pop_front();
+                }
+                let Some(tree) = claim_nearest(&mut job_board, entity, position, trees) else {
+                    agent.needs_replan = true;
+                    continue;
+                };
+                agent.current = Some((action, tree));
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMWalkingToHarvest {
+                        target: tree,
+                        proximity: 0.2,
+                    },
+                    "following plan: chop a tree",
+                );
+            }
+            GoapAction::PickUpWood => {
+                // Picking up wood ends in bringing it to a hut
+                if agent.plan.front() == Some(&GoapAction::BringWoodToHut) {
+                    agent.plan.pop_front();
+                }
+                let Some(wood) = claim_nearest(&mut job_board, entity, position, wood_drops) else {
+                    agent.needs_replan = true;
+                    continue;
+                };
+                agent.current = Some((action, wood));
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMPickingUp {
+                        target: wood,
+                        proximity: 0.2,
+                    },
+                    "following plan: haul wood",
+                );
+            }
+            GoapAction::WalkHome => {
+                let Some((house, _)) = nearest_house else {
+                    agent.needs_replan = true;
+                    continue;
+                };
+                agent.current = Some((action, house));
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMWalkingTo {
+                        target: house,
+                        proximity: 0.2,
+                    },
+                    "following plan: walk home",
+                );
+            }
+            GoapAction::BringWoodToHut => {
+                // Left over from an interrupted haul
+                let Some((wood_hut, _)) = nearest_hut else {
+                    agent.needs_replan = true;
+                    continue;
+                };
+                agent.current = Some((action, wood_hut));
+                fsm_transition_to::<FSMIdle>(
+                    &mut commands,
+                    entity,
+                    FSMBringingTo {
+                        target: wood_hut,
+                        proximity: 0.2,
+                    },
+                    "following plan: deliver carried wood",
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use bevy::prelude::*;
+
+pub mod agent;
+pub mod planner;
+
+pub use agent::*;
+pub use planner::*;
+
+use crate::fsm::states::fsm_update_idle;
+use crate::plugins::SimulationSet;
+
+pub struct GoapPlugin;
+
+impl Plugin for GoapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                goap_replan_on_harvestable_destroyed,
+                goap_replan_on_task_failed,
+                goap_update_idle,
+            )
+                .chain()
+                .before(fsm_update_idle)
+                .in_set(SimulationSet),
+        );
+    }
+}
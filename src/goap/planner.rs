@@ -0,0 +1,234 @@
+// Planning estimates, the real numbers come from the world when replanning
+const WOOD_PER_DROP_ESTIMATE: u32 = 3;
+// Counts above this are all the same to the planner, which keeps the search small
+const COUNT_CAP: u32 = 8;
+const MAX_EXPANSIONS: usize = 4096;
+
+////////////////////////////////////////////////////////////////
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+/// The symbolic view of the world a villager plans over.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct GoapState {
+    pub has_wood: bool,
+    pub near_tree: bool,
+    pub near_hut: bool,
+    pub near_house: bool,
+    pub trees_available: u32,
+    pub wood_drops: u32,
+    pub hut_wood: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum GoapGoal {
+    HutWoodAtLeast(u32),
+    AtHome,
+}
+
+impl GoapGoal {
+    pub fn is_satisfied(&self, state: &GoapState) -> bool {
+        match self {
+            GoapGoal::HutWoodAtLeast(count) => state.hut_wood >= *count,
+            GoapGoal::AtHome => state.near_house,
+        }
+    }
+
+    /// Lower bound on the remaining cost, so the search stays admissible. Every trip ends in
+    /// bringing wood to a hut and starts with picking it up, unless it is already carried.
+    pub fn heuristic(&self, state: &GoapState) -> u32 {
+        match self {
+            GoapGoal::HutWoodAtLeast(count) => {
+                let trips = count
+                    .saturating_sub(state.hut_wood)
+                    .div_ceil(WOOD_PER_DROP_ESTIMATE);
+                let pick_ups = if state.has_wood {
+                    trips.saturating_sub(1)
+                } else {
+                    trips
+                };
+                trips * GoapAction::BringWoodToHut.cost() + pick_ups * GoapAction::PickUpWood.cost()
+            }
+            GoapGoal::AtHome => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GoapAction {
+    WalkToTree,
+    ChopTree,
+    PickUpWood,
+    BringWoodToHut,
+    WalkHome,
+}
+
+pub const GOAP_ACTIONS: [GoapAction; 5] = [
+    GoapAction::WalkToTree,
+    GoapAction::ChopTree,
+    GoapAction::PickUpWood,
+    GoapAction::BringWoodToHut,
+    GoapAction::WalkHome,
+];
+
+impl GoapAction {
+    pub fn cost(&self) -> u32 {
+        match self {
+            GoapAction::WalkToTree => 2,
+            GoapAction::ChopTree => 3,
+            GoapAction::PickUpWood => 1,
+            GoapAction::BringWoodToHut => 2,
+            GoapAction::WalkHome => 2,
+        }
+    }
+
+    pub fn is_possible(&self, state: &GoapState) -> bool {
+        match self {
+            GoapAction::WalkToTree => !state.near_tree && state.trees_available > 0,
+            GoapAction::ChopTree => state.near_tree && state.trees_available > 0,
+            GoapAction::PickUpWood => !state.has_wood && state.wood_drops > 0,
+            GoapAction::BringWoodToHut => state.has_wood,
+            GoapAction::WalkHome => !state.near_house,
+        }
+    }
+
+    pub fn apply(&self, state: &GoapState) -> GoapState {
+        let mut next = *state;
+        match self {
+            GoapAction::WalkToTree => {
+                next.near_tree = true;
+                next.near_hut = false;
+                next.near_house = false;
+            }
+            GoapAction::ChopTree => {
+                // The tree is gone, so there is nothing left to be near
+                next.near_tree = false;
+                next.trees_available -= 1;
+                next.wood_drops = (next.wood_drops + 1).min(COUNT_CAP);
+            }
+            GoapAction::PickUpWood => {
+                next.has_wood = true;
+                next.wood_drops -= 1;
+                next.near_tree = false;
+                next.near_hut = false;
+                next.near_house = false;
+            }
+            GoapAction::BringWoodToHut => {
+                next.has_wood = false;
+                next.near_hut = true;
+                next.hut_wood += WOOD_PER_DROP_ESTIMATE;
+            }
+            GoapAction::WalkHome => {
+                next.near_tree = false;
+                next.near_hut = false;
+                next.near_house = true;
+            }
+        }
+        next
+    }
+}
+
+#[derive(PartialEq, Eq)]
+struct OpenNode {
+    estimate: u32,
+    cost: u32,
+    state: GoapState,
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, cheapest estimate first
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| other.cost.cmp(&self.cost))
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn path_to(
+    came_from: &HashMap<GoapState, (GoapState, GoapAction)>,
+    state: GoapState,
+) -> Vec<GoapAction> {
+    let mut actions = Vec::new();
+    let mut current = state;
+    while let Some((previous, action)) = came_from.get(&current) {
+        actions.push(*action);
+        current = *previous;
+    }
+    actions.reverse();
+    actions
+}
+
+/// A* over `GoapState`. Returns the cheapest sequence of actions that satisfies the goal,
+/// or an empty plan if it already is satisfied. If the goal can't be reached with what is
+/// currently in the world, returns the plan that gets closest to it, or `None` if no
+/// action helps at all.
+pub fn plan(start: GoapState, goal: GoapGoal) -> Option<Vec<GoapAction>> {
+    let mut start = start;
+    start.trees_available = start.trees_available.min(COUNT_CAP);
+    start.wood_drops = start.wood_drops.min(COUNT_CAP);
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<GoapState, (GoapState, GoapAction)> = HashMap::new();
+    let mut best_cost: HashMap<GoapState, u32> = HashMap::new();
+    let mut closed: HashSet<GoapState> = HashSet::new();
+
+    // Closest state to the goal seen so far, by heuristic and then by cost
+    let mut closest = (goal.heuristic(&start), 0, start);
+
+    best_cost.insert(start, 0);
+    open.push(OpenNode {
+        estimate: goal.heuristic(&start),
+        cost: 0,
+        state: start,
+    });
+
+    while let Some(OpenNode { cost, state, .. }) = open.pop() {
+        if goal.is_satisfied(&state) {
+            return Some(path_to(&came_from, state));
+        }
+        if (goal.heuristic(&state), cost) < (closest.0, closest.1) {
+            closest = (goal.heuristic(&state), cost, state);
+        }
+
+        if !closed.insert(state) {
+            continue;
+        }
+        if closed.len() > MAX_EXPANSIONS {
+            break;
+        }
+
+        for action in GOAP_ACTIONS
+            .iter()
+            .filter(|action| action.is_possible(&state))
+        {
+            let next = action.apply(&state);
+            let next_cost = cost + action.cost();
+            if best_cost.get(&next).is_none_or(|best| next_cost < *best) {
+                best_cost.insert(next, next_cost);
+                came_from.insert(next, (state, *action));
+                open.push(OpenNode {
+                    estimate: next_cost + goal.heuristic(&next),
+                    cost: next_cost,
+                    state: next,
+                });
+            }
+        }
+    }
+
+    if closest.2 == start {
+        None
+    } else {
+        Some(path_to(&came_from, closest.2))
+    }
+}
@@ -21,10 +21,8 @@ pub fn check_harvestable_destroyed(
 ) {
     for (entity, harvestable, harvestable_to_destroy) in harvestables.iter() {
         if harvestable_to_destroy.is_none() && harvestable.health <= 0.0 {
-            harvestable_destroyed_events.send(HarvestableDestroyed {
-                entity,
-            });
+            harvestable_destroyed_events.send(HarvestableDestroyed { entity });
             commands.entity(entity).insert(HarvestableDeathmark);
         }
     }
-}
\ No newline at end of file
+}
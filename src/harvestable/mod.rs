@@ -1,5 +1,9 @@
 use bevy::prelude::*;
 
+use crate::plugins::SimulationSet;
+use crate::world::stream_chunks;
+
+#[allow(clippy::module_inception)]
 pub mod harvestable;
 pub mod tree;
 
@@ -10,6 +14,32 @@ pub struct HarvestablePlugin;
 impl Plugin for HarvestablePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<HarvestableDestroyed>();
-        app.add_systems(PostUpdate, check_harvestable_destroyed);
+        app.add_systems(
+            PostUpdate,
+            check_harvestable_destroyed.in_set(SimulationSet),
+        );
+        app.add_systems(
+            Update,
+            tree::check_tree_should_be_destroyed.in_set(SimulationSet),
+        );
+    }
+}
+
+/// Trees taking root in new chunks and growing back over time.
+pub struct ForestPlugin;
+
+impl Plugin for ForestPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<tree::UnloadedTrees>();
+        app.add_systems(
+            Update,
+            (
+                tree::populate_new_chunks,
+                tree::tick_grow_tree,
+                // The chunk's trees are despawned with the commands of `stream_chunks`
+                tree::remember_unloaded_trees.after_ignore_deferred(stream_chunks),
+            )
+                .in_set(SimulationSet),
+        );
     }
 }
@@ -1,72 +1,207 @@
-// On average, this many trees will spawn per second
-const TREE_GROW_RATE: f32 = 0.6;
-
-// This should be removed when we do chunking stuff anyway
-const WORLD_RADIUS: f32 = 6.4;
+// On average, a tree tries to sprout this many times per second in each loaded chunk
+const TREE_GROW_RATE: f32 = 0.4;
+// Chunks stop growing trees once they have this many
+const MAX_TREES_PER_CHUNK: usize = 12;
+// A freshly loaded chunk that is all forest starts with about this many trees
+const INITIAL_TREES_PER_CHUNK: usize = 8;
+// Villagers walk around this much of the trunk, unless the manifest gives a collider
+const TREE_FOOTPRINT: f32 = 0.2;
+// Trees are picked from the manifest models with this tag
+pub const TREE_TAG: &str = "tree";
 
 ////////////////////////////////////////////////////////////////
 
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 use rand::Rng;
 
 use crate::assets::*;
 
 use crate::harvestable::harvestable::*;
+use crate::item::*;
 use crate::item_drop::*;
+use crate::navigation::NavObstacle;
+use crate::random::SimulationRng;
+use crate::world::*;
 
 #[derive(Component)]
-#[require(Harvestable)]
-pub struct Tree;
-
-const TREE_TYPES: [SceneAssetType; 3] = [
-    SceneAssetType::TreePine,
-    SceneAssetType::TreeRound,
-    SceneAssetType::TreeDead,
-];
-
-pub fn spawn_tree(commands: &mut Commands, scene_assets: &SceneAssets, position: Vec3) {
-    let tree_type = &TREE_TYPES[rand::rng().random_range(0..TREE_TYPES.len())];
-    commands.spawn((
-        SceneRoot(scene_assets.handles.get(tree_type).unwrap().clone()),
-        Transform::from_xyz(position.x, position.y, position.z).with_scale(GLOBAL_SCALE_VEC),
-        Tree,
-        Harvestable {
-            health: 2.0,
-            // max_health: 2.0,
-        },
-        Name::new("Tree"),
-    ));
+#[require(Harvestable, ChunkLocal)]
+pub struct Tree {
+    pub kind: SceneAssetId,
+}
+
+/// Trees of unloaded chunks as they were left, so felled trees stay felled and harvest
+/// progress isn't lost when the chunk is loaded again.
+#[derive(Resource, Default)]
+pub struct UnloadedTrees {
+    chunks: HashMap<ChunkCoord, Vec<(SceneAssetId, Transform, f32)>>,
+}
+
+impl UnloadedTrees {
+    /// Trees of an unloaded chunk as (kind, transform, health). A chunk whose trees were all
+    /// felled is remembered with none.
+    pub fn remember(&mut self, coord: ChunkCoord, trees: Vec<(SceneAssetId, Transform, f32)>) {
+        self.chunks.insert(coord, trees);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ChunkCoord, &[(SceneAssetId, Transform, f32)])> {
+        self.chunks
+            .iter()
+            .map(|(coord, trees)| (*coord, trees.as_slice()))
+    }
+}
+
+pub fn spawn_tree(
+    commands: &mut Commands,
+    scene_assets: &SceneAssets,
+    position: Vec3,
+    rng: &mut impl Rng,
+) -> Option<Entity> {
+    let kinds = scene_assets.tagged(TREE_TAG).collect::<Vec<_>>();
+    if kinds.is_empty() {
+        return None;
+    }
+    let kind = kinds[rng.random_range(0..kinds.len())].clone();
+    Some(spawn_tree_of_kind(commands, scene_assets, kind, position))
+}
+
+pub fn spawn_tree_of_kind(
+    commands: &mut Commands,
+    scene_assets: &SceneAssets,
+    kind: SceneAssetId,
+    position: Vec3,
+) -> Entity {
+    let footprint = scene_assets
+        .get(&kind.0)
+        .and_then(|scene| scene.collider.as_ref())
+        .map_or(TREE_FOOTPRINT, ColliderHint::footprint);
+    commands
+        .spawn((
+            SceneRoot(scene_assets.scene(&kind.0)),
+            scene_assets.transform(&kind.0, position),
+            Tree { kind },
+            Harvestable {
+                health: 2.0,
+                // max_health: 2.0,
+            },
+            NavObstacle { radius: footprint },
+            Name::new("Tree"),
+        ))
+        .id()
+}
+
+/// Trees only take root with a chance of the forest density there, which leaves clearings.
+fn sprout_position(terrain: &Terrain, coord: ChunkCoord, rng: &mut impl Rng) -> Option<Vec3> {
+    let position = coord.random_position(rng);
+    (rng.random::<f32>() < terrain.forest_density(position.x, position.z))
+        .then(|| terrain.on_surface(position))
+}
+
+pub fn populate_new_chunks(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+    terrain: Res<Terrain>,
+    mut rng: ResMut<SimulationRng>,
+    mut unloaded_trees: ResMut<UnloadedTrees>,
+    chunks: Query<&Chunk, (Added<Chunk>, Without<RestoredChunk>)>,
+) {
+    let rng = rng.stream("populate_new_chunks");
+    for chunk in chunks.iter() {
+        if let Some(trees) = unloaded_trees.chunks.remove(&chunk.coord) {
+            for (kind, transform, health) in trees {
+                let tree =
+                    spawn_tree_of_kind(&mut commands, &scene_assets, kind, transform.translation);
+                commands
+                    .entity(tree)
+                    .insert((transform, Harvestable { health }));
+            }
+            continue;
+        }
+        for _ in 0..INITIAL_TREES_PER_CHUNK {
+            if let Some(position) = sprout_position(&terrain, chunk.coord, rng) {
+                spawn_tree(&mut commands, &scene_assets, position, rng);
+            }
+        }
+    }
 }
 
-pub fn tick_grow_tree(mut commands: Commands, scene_assets: Res<SceneAssets>, time: Res<Time>) {
-    if rand::rng().random::<f32>() > TREE_GROW_RATE * time.delta_secs() {
-        return;
+/// Runs before the unloaded chunk's trees are despawned.
+#[allow(clippy::type_complexity)]
+pub fn remember_unloaded_trees(
+    mut unloaded_trees: ResMut<UnloadedTrees>,
+    mut chunk_unloaded_events: EventReader<ChunkUnloaded>,
+    trees: Query<(&Transform, &Tree, &Harvestable), Without<HarvestableDeathmark>>,
+) {
+    for event in chunk_unloaded_events.read() {
+        let remembered = trees
+            .iter()
+            .filter(|(transform, _, _)| {
+                ChunkCoord::from_position(transform.translation) == event.coord
+            })
+            .map(|(transform, tree, harvestable)| {
+                (tree.kind.clone(), *transform, harvestable.health)
+            })
+            .collect();
+        unloaded_trees.remember(event.coord, remembered);
     }
+}
 
-    let r = WORLD_RADIUS * rand::rng().random::<f32>();
-    let theta = rand::rng().random::<f32>() * 2.0 * std::f32::consts::PI;
+pub fn tick_grow_tree(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+    time: Res<Time>,
+    terrain: Res<Terrain>,
+    mut rng: ResMut<SimulationRng>,
+    chunks: Query<&Chunk>,
+    trees: Query<&Transform, With<Tree>>,
+) {
+    let mut trees_per_chunk = HashMap::<ChunkCoord, usize>::new();
+    for transform in trees.iter() {
+        *trees_per_chunk
+            .entry(ChunkCoord::from_position(transform.translation))
+            .or_default() += 1;
+    }
 
-    let x = r * theta.cos();
-    let z = r * theta.sin();
+    let rng = rng.stream("tick_grow_tree");
+    for chunk in chunks.iter() {
+        if trees_per_chunk.get(&chunk.coord).copied().unwrap_or(0) >= MAX_TREES_PER_CHUNK {
+            continue;
+        }
+        if rng.random::<f32>() > TREE_GROW_RATE * time.delta_secs() {
+            continue;
+        }
 
-    let random_position = Vec3::new(x, 0.0, z);
-    spawn_tree(&mut commands, &scene_assets, random_position);
+        if let Some(position) = sprout_position(&terrain, chunk.coord, rng) {
+            spawn_tree(&mut commands, &scene_assets, position, rng);
+        }
+    }
 }
 
 pub fn check_tree_should_be_destroyed(
     mut commands: Commands,
+    registry: Res<ItemRegistry>,
     scene_assets: Res<SceneAssets>,
+    mut rng: ResMut<SimulationRng>,
     trees: Query<(Entity, &Transform, Option<&HarvestableDeathmark>), With<Tree>>,
 ) {
+    let rng = rng.stream("check_tree_should_be_destroyed");
     for (entity, transform, harvestable_deathmark) in trees.iter() {
         if harvestable_deathmark.is_some() {
             commands.entity(entity).despawn_recursive();
-            spawn_wood(
+            // Four stacks of wood don't look good, so 4 is converted to 3.
+            let count = match rng.random_range(1..=5) {
+                4 => 3,
+                count => count,
+            };
+            spawn_item_drop(
                 &mut commands,
+                &registry,
                 &scene_assets,
+                ItemType::Wood,
                 transform.translation + Vec3::new(0.0, 0.5, 0.0),
-                rand::rng().random_range(1..=5),
+                count,
+                rng,
             );
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,66 @@
+// Used for anything not given on the command line
+const DEFAULT_SECONDS: f32 = 600.0;
+const DEFAULT_TIMESTEP: f32 = 1.0 / 30.0;
+const DEFAULT_SEED: u64 = 1;
+
+////////////////////////////////////////////////////////////////
+
+use std::path::PathBuf;
+
+pub const USAGE: &str =
+    "usage: village --headless [--seconds N] [--timestep SECONDS] [--seed N] [--output PATH]";
+
+/// How a headless run is set up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeadlessConfig {
+    /// Simulated seconds to run for.
+    pub seconds: f32,
+    /// Simulated seconds per frame. Every frame is exactly this long.
+    pub timestep: f32,
+    pub seed: u64,
+    /// Where to write the statistics as RON, on top of printing them.
+    pub output: Option<PathBuf>,
+}
+
+impl Default for HeadlessConfig {
+    fn default() -> Self {
+        Self {
+            seconds: DEFAULT_SECONDS,
+            timestep: DEFAULT_TIMESTEP,
+            seed: DEFAULT_SEED,
+            output: None,
+        }
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {}", flag, value))
+}
+
+impl HeadlessConfig {
+    /// Reads the flags after the program name. `--headless` itself is accepted and ignored.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut config = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => {}
+                "--seconds" => config.seconds = parse_value(&arg, args.next())?,
+                "--timestep" => config.timestep = parse_value(&arg, args.next())?,
+                "--seed" => config.seed = parse_value(&arg, args.next())?,
+                "--output" => config.output = Some(parse_value(&arg, args.next())?),
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+        if config.seconds < 0.0 {
+            return Err("--seconds can't be negative".to_string());
+        }
+        if config.timestep <= 0.0 {
+            return Err("--timestep must be positive".to_string());
+        }
+        Ok(config)
+    }
+}
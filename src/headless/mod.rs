@@ -0,0 +1,7 @@
+pub mod config;
+pub mod runner;
+pub mod stats;
+
+pub use config::*;
+pub use runner::*;
+pub use stats::*;
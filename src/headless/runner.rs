@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::headless::{config::*, stats::*};
+use crate::plugins::{HeadlessPlugins, SimulationPlugins};
+use crate::scenario::spawn_starting_village;
+
+/// The simulation without a window, renderer or models, stepping exactly `timestep`
+/// seconds per frame.
+pub fn headless_app(config: &HeadlessConfig) -> App {
+    let timestep = Duration::from_secs_f32(config.timestep);
+    let mut app = App::new();
+    app.add_plugins((HeadlessPlugins, SimulationPlugins { seed: config.seed }))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(timestep))
+        .init_resource::<SimulationStats>()
+        .add_systems(Startup, spawn_starting_village)
+        .add_systems(Last, (track_time, track_events, track_stockpiles));
+
+    // Long timesteps would otherwise be cut short
+    app.world_mut()
+        .resource_mut::<Time<Virtual>>()
+        .set_max_delta(timestep.max(Duration::from_millis(250)));
+    app
+}
+
+/// Runs the simulation for `config.seconds` and returns what happened.
+pub fn run_headless(config: &HeadlessConfig) -> SimulationStats {
+    let mut app = headless_app(config);
+    let frames = (config.seconds / config.timestep).ceil() as u64;
+    // The first frame doesn't advance time
+    for _ in 0..=frames {
+        app.update();
+    }
+    app.world().resource::<SimulationStats>().clone()
+}
+
+pub fn write_stats(path: &Path, stats: &SimulationStats) -> std::io::Result<()> {
+    let text = ron::ser::to_string_pretty(stats, ron::ser::PrettyConfig::default())
+        .map_err(std::io::Error::other)?;
+    std::fs::write(path, text)
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::fsm::{components::VillagerState, failure::TaskFailed, machine::FSM};
+use crate::harvestable::{harvestable::HarvestableDestroyed, tree::Tree};
+use crate::item::*;
+use crate::structure::stockpile::Stockpile;
+
+/// Balance numbers collected over a headless run.
+#[derive(Resource, Default, Clone, Debug, Serialize)]
+pub struct SimulationStats {
+    pub seconds: f32,
+    /// Wood put into storage, not counting what was already there.
+    pub wood_gathered: u32,
+    pub trees_felled: u32,
+    pub task_failures: u32,
+    /// Seconds spent in each state, summed over all FSM villagers.
+    pub state_seconds: BTreeMap<String, f32>,
+}
+
+impl fmt::Display for SimulationStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Simulated {:.1}s", self.seconds)?;
+        writeln!(f, "  wood gathered: {}", self.wood_gathered)?;
+        writeln!(f, "  trees felled:  {}", self.trees_felled)?;
+        writeln!(f, "  task failures: {}", self.task_failures)?;
+        writeln!(f, "  villager time by state:")?;
+        let total = self.state_seconds.values().sum::<f32>().max(f32::EPSILON);
+        for (state, seconds) in self.state_seconds.iter() {
+            writeln!(
+                f,
+                "    {:<18}{:>9.1}s {:>5.1}%",
+                state,
+                seconds,
+                seconds / total * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn track_time(
+    mut stats: ResMut<SimulationStats>,
+    villagers: Query<&FSM<VillagerState>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+    stats.seconds += delta;
+    for fsm in villagers.iter() {
+        *stats
+            .state_seconds
+            .entry(format!("{:?}", fsm.current()))
+            .or_default() += delta;
+    }
+}
+
+pub fn track_events(
+    mut stats: ResMut<SimulationStats>,
+    mut harvestable_destroyed_events: EventReader<HarvestableDestroyed>,
+    mut task_failed_events: EventReader<TaskFailed>,
+    trees: Query<(), With<Tree>>,
+) {
+    for event in harvestable_destroyed_events.read() {
+        if trees.contains(event.entity) {
+            stats.trees_felled += 1;
+        }
+    }
+    stats.task_failures += task_failed_events.read().count() as u32;
+}
+
+/// Counts every increase of a wood stockpile, starting from what it held when first seen.
+#[allow(clippy::type_complexity)]
+pub fn track_stockpiles(
+    mut stats: ResMut<SimulationStats>,
+    stockpiles: Query<(Entity, &ItemStack), (With<Stockpile>, Changed<ItemStack>)>,
+    mut last_counts: Local<HashMap<Entity, u32>>,
+) {
+    for (entity, stored) in stockpiles.iter() {
+        if let Some(last_count) = last_counts.insert(entity, stored.count) {
+            if stored.item_type == ItemType::Wood {
+                stats.wood_gathered += stored.count.saturating_sub(last_count);
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+// What a villager can carry at once
+const VILLAGER_MAX_COUNT: u32 = 8;
+const VILLAGER_MAX_WEIGHT: f32 = 10.0;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+
+use crate::assets::*;
+use crate::item::*;
+
+/// Items carried by an entity, limited both by item count and by total weight.
+#[derive(Component, Clone, Debug)]
+pub struct Inventory {
+    stacks: Vec<ItemStack>,
+    pub max_count: u32,
+    pub max_weight: f32,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self::new(VILLAGER_MAX_COUNT, VILLAGER_MAX_WEIGHT)
+    }
+}
+
+impl Inventory {
+    pub fn new(max_count: u32, max_weight: f32) -> Self {
+        Self {
+            stacks: Vec::new(),
+            max_count,
+            max_weight,
+        }
+    }
+
+    pub fn stacks(&self) -> &[ItemStack] {
+        &self.stacks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stacks.is_empty()
+    }
+
+    pub fn count(&self, item_type: ItemType) -> u32 {
+        self.stacks
+            .iter()
+            .filter(|stack| stack.item_type == item_type)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    pub fn total_count(&self) -> u32 {
+        self.stacks.iter().map(|stack| stack.count).sum()
+    }
+
+    pub fn total_weight(&self) -> f32 {
+        self.stacks
+            .iter()
+            .map(|stack| stack.item_type.weight() * stack.count as f32)
+            .sum()
+    }
+
+    /// How many more items of this type fit.
+    pub fn room_for(&self, item_type: ItemType) -> u32 {
+        let by_count = self.max_count.saturating_sub(self.total_count());
+        let by_weight =
+            ((self.max_weight - self.total_weight()) / item_type.weight()).max(0.0) as u32;
+        by_count.min(by_weight)
+    }
+
+    /// Adds as many as fit and returns how many that was.
+    pub fn add(&mut self, item_type: ItemType, count: u32) -> u32 {
+        let added = count.min(self.room_for(item_type));
+        if added == 0 {
+            return 0;
+        }
+
+        match self
+            .stacks
+            .iter_mut()
+            .find(|stack| stack.item_type == item_type)
+        {
+            Some(stack) => stack.count += added,
+            None => self.stacks.push(ItemStack::new(item_type, added)),
+        }
+        added
+    }
+
+    /// Removes up to `count` items of this type and returns how many were removed.
+    pub fn take(&mut self, item_type: ItemType, count: u32) -> u32 {
+        let Some(index) = self
+            .stacks
+            .iter()
+            .position(|stack| stack.item_type == item_type)
+        else {
+            return 0;
+        };
+
+        let stack = &mut self.stacks[index];
+        let taken = count.min(stack.count);
+        stack.count -= taken;
+        if stack.count == 0 {
+            self.stacks.remove(index);
+        }
+        taken
+    }
+
+    pub fn take_all(&mut self, item_type: ItemType) -> u32 {
+        self.take(item_type, u32::MAX)
+    }
+
+    /// Empties the inventory, returning everything that was in it.
+    pub fn clear(&mut self) -> Vec<ItemStack> {
+        std::mem::take(&mut self.stacks)
+    }
+}
+
+/// Marks the model on a villager's head that shows what it is carrying.
+#[derive(Component)]
+pub struct CarriedModel;
+
+/// The carried model follows the inventory, it is never the other way around.
+/// Only the first stack is shown.
+pub fn sync_carried_models(
+    mut commands: Commands,
+    carriers: Query<(Entity, &Inventory, Option<&Children>), Changed<Inventory>>,
+    mut carried_models: Query<&mut ItemStack, With<CarriedModel>>,
+    registry: Res<ItemRegistry>,
+    scene_assets: Res<SceneAssets>,
+) {
+    for (entity, inventory, children) in &carriers {
+        let shown = inventory.stacks().first().copied();
+        let carried_model = children
+            .into_iter()
+            .flatten()
+            .find(|child| carried_models.contains(**child))
+            .copied();
+
+        if let Some(carried_model) = carried_model {
+            let Ok(mut carried_stack) = carried_models.get_mut(carried_model) else {
+                continue;
+            };
+            match shown {
+                Some(shown) if shown.item_type == carried_stack.item_type => {
+                    if carried_stack.count != shown.count {
+                        carried_stack.count = shown.count;
+                    }
+                    continue;
+                }
+                _ => commands.entity(carried_model).despawn_recursive(),
+            }
+        }
+
+        if let Some(shown) = shown {
+            commands.entity(entity).with_children(|children| {
+                let mut carried_model = children.spawn((
+                    shown,
+                    Transform::from_translation(Vec3::new(0.0, 0.95, 0.0)),
+                    CarriedModel,
+                ));
+                insert_item_model(&mut carried_model, &registry, &scene_assets, shown);
+            });
+        }
+    }
+}
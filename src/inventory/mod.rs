@@ -0,0 +1,16 @@
+use bevy::prelude::*;
+
+use crate::plugins::SimulationSet;
+
+#[allow(clippy::module_inception)]
+pub mod inventory;
+
+pub use inventory::*;
+
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, sync_carried_models.in_set(SimulationSet));
+    }
+}
@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+
+use crate::plugins::SimulationSet;
+
+pub mod registry;
+pub mod stack;
+
+pub use registry::*;
+pub use stack::*;
+
+pub struct ItemPlugin;
+
+impl Plugin for ItemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreStartup, load_item_registry);
+        app.add_systems(PostStartup, update_item_stacks);
+        app.add_systems(PostUpdate, update_item_stacks.in_set(SimulationSet));
+    }
+}
@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::ops::RangeInclusive;
+
+/// Everything that can lie on the ground, be carried around or be stored.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum ItemType {
+    Wood,
+    Stone,
+    Food,
+    Planks,
+}
+
+impl ItemType {
+    /// How much one item counts against a carry weight limit.
+    pub fn weight(&self) -> f32 {
+        match self {
+            ItemType::Wood => 1.0,
+            ItemType::Stone => 2.0,
+            ItemType::Food => 0.5,
+            ItemType::Planks => 1.5,
+        }
+    }
+}
+
+pub enum ItemModel {
+    /// A glTF scene whose children are the pieces of a stack, in order.
+    Scene(&'static str),
+    /// The same mesh repeated at each position of the layout.
+    Mesh {
+        mesh: Handle<Mesh>,
+        material: Handle<StandardMaterial>,
+        layout: Vec<Transform>,
+    },
+}
+
+/// A collider that a dropped stack has while its count is in `counts`.
+pub struct ItemCollider {
+    pub counts: RangeInclusive<u32>,
+    pub collider: Collider,
+    pub transform: Transform,
+}
+
+pub struct ItemDefinition {
+    pub name: &'static str,
+    pub model: ItemModel,
+    /// Most items that fit in one stack. Stacks only ever show this many pieces.
+    pub stack_size: u32,
+    pub colliders: Vec<ItemCollider>,
+}
+
+impl ItemDefinition {
+    pub fn colliders_for(&self, count: u32) -> impl Iterator<Item = &ItemCollider> {
+        self.colliders
+            .iter()
+            .filter(move |item_collider| item_collider.counts.contains(&count))
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ItemRegistry {
+    definitions: HashMap<ItemType, ItemDefinition>,
+}
+
+impl ItemRegistry {
+    pub fn register(&mut self, item_type: ItemType, definition: ItemDefinition) -> &mut Self {
+        self.definitions.insert(item_type, definition);
+        self
+    }
+
+    /// Panics for unregistered items, every `ItemType` is expected to be registered.
+    pub fn get(&self, item_type: ItemType) -> &ItemDefinition {
+        self.definitions
+            .get(&item_type)
+            .unwrap_or_else(|| panic!("item {:?} is not registered", item_type))
+    }
+
+    pub fn definitions(&self) -> impl Iterator<Item = &ItemDefinition> {
+        self.definitions.values()
+    }
+
+    pub fn stack_size(&self, item_type: ItemType) -> u32 {
+        self.get(item_type).stack_size
+    }
+}
+
+fn lying_down(x: f32, y: f32, z: f32) -> Transform {
+    Transform::from_rotation(Quat::from_rotation_x(-std::f32::consts::PI / 2.0))
+        .with_translation(Vec3::new(x, y, z))
+}
+
+fn wood() -> ItemDefinition {
+    ItemDefinition {
+        name: "Wood",
+        model: ItemModel::Scene("wood"),
+        stack_size: 32,
+        colliders: vec![
+            ItemCollider {
+                counts: 1..=1,
+                collider: Collider::cuboid(0.085, 0.34, 0.085),
+                transform: lying_down(0.0, 0.0, 0.0),
+            },
+            ItemCollider {
+                counts: 2..=u32::MAX,
+                collider: Collider::cylinder(0.34, 0.085),
+                transform: lying_down(0.0, 0.0, 0.0),
+            },
+            ItemCollider {
+                counts: 2..=u32::MAX,
+                collider: Collider::cylinder(0.34, 0.085),
+                transform: lying_down(-0.16, 0.0, 0.0),
+            },
+            ItemCollider {
+                counts: 3..=u32::MAX,
+                collider: Collider::cylinder(0.34, 0.085),
+                transform: lying_down(-0.08, 0.12, 0.0),
+            },
+            ItemCollider {
+                counts: 5..=u32::MAX,
+                collider: Collider::cylinder(0.34, 0.085),
+                transform: lying_down(0.16, 0.0, 0.0),
+            },
+            ItemCollider {
+                counts: 5..=u32::MAX,
+                collider: Collider::cylinder(0.34, 0.085),
+                transform: lying_down(0.08, 0.12, 0.0),
+            },
+        ],
+    }
+}
+
+fn stone(meshes: &mut Assets<Mesh>, materials: &mut Assets<StandardMaterial>) -> ItemDefinition {
+    ItemDefinition {
+        name: "Stone",
+        model: ItemModel::Mesh {
+            mesh: meshes.add(Sphere::new(0.1)),
+            material: materials.add(Color::srgb_u8(130, 130, 140)),
+            layout: vec![
+                Transform::from_xyz(0.0, 0.0, 0.0),
+                Transform::from_xyz(0.18, 0.0, 0.05),
+                Transform::from_xyz(0.08, 0.0, -0.16),
+                Transform::from_xyz(0.09, 0.14, -0.04),
+            ],
+        },
+        stack_size: 16,
+        colliders: vec![ItemCollider {
+            counts: 1..=u32::MAX,
+            collider: Collider::ball(0.14),
+            transform: Transform::from_xyz(0.09, 0.0, -0.04),
+        }],
+    }
+}
+
+fn food(meshes: &mut Assets<Mesh>, materials: &mut Assets<StandardMaterial>) -> ItemDefinition {
+    ItemDefinition {
+        name: "Food",
+        model: ItemModel::Mesh {
+            mesh: meshes.add(Sphere::new(0.07)),
+            material: materials.add(Color::srgb_u8(220, 60, 40)),
+            layout: vec![
+                Transform::from_xyz(0.0, 0.0, 0.0),
+                Transform::from_xyz(0.14, 0.0, 0.0),
+                Transform::from_xyz(0.07, 0.0, 0.12),
+                Transform::from_xyz(0.07, 0.1, 0.04),
+                Transform::from_xyz(-0.07, 0.0, 0.12),
+            ],
+        },
+        stack_size: 20,
+        colliders: vec![ItemCollider {
+            counts: 1..=u32::MAX,
+            collider: Collider::cuboid(0.14, 0.07, 0.12),
+            transform: Transform::from_xyz(0.04, 0.0, 0.06),
+        }],
+    }
+}
+
+fn planks(meshes: &mut Assets<Mesh>, materials: &mut Assets<StandardMaterial>) -> ItemDefinition {
+    ItemDefinition {
+        name: "Planks",
+        model: ItemModel::Mesh {
+            mesh: meshes.add(Cuboid::new(0.16, 0.04, 0.68)),
+            material: materials.add(Color::srgb_u8(190, 140, 80)),
+            layout: vec![
+                Transform::from_xyz(0.0, 0.0, 0.0),
+                Transform::from_xyz(0.18, 0.0, 0.0),
+                Transform::from_xyz(0.09, 0.05, 0.0),
+                Transform::from_xyz(-0.09, 0.05, 0.0),
+                Transform::from_xyz(0.0, 0.1, 0.0),
+            ],
+        },
+        stack_size: 24,
+        colliders: vec![ItemCollider {
+            counts: 1..=u32::MAX,
+            collider: Collider::cuboid(0.17, 0.06, 0.34),
+            transform: Transform::from_xyz(0.09, 0.04, 0.0),
+        }],
+    }
+}
+
+impl ItemRegistry {
+    /// Every item the game knows about. New items get a definition here.
+    pub fn new(meshes: &mut Assets<Mesh>, materials: &mut Assets<StandardMaterial>) -> Self {
+        let mut registry = Self::default();
+        registry
+            .register(ItemType::Wood, wood())
+            .register(ItemType::Stone, stone(meshes, materials))
+            .register(ItemType::Food, food(meshes, materials))
+            .register(ItemType::Planks, planks(meshes, materials));
+        registry
+    }
+}
+
+pub fn load_item_registry(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ItemRegistry::new(&mut meshes, &mut materials));
+}
@@ -0,0 +1,88 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::assets::*;
+use crate::item::registry::*;
+
+/// A number of items of one type in one place: on the ground, in storage or on a villager's head.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ItemStack {
+    pub item_type: ItemType,
+    pub count: u32,
+}
+
+impl ItemStack {
+    pub fn new(item_type: ItemType, count: u32) -> Self {
+        Self { item_type, count }
+    }
+}
+
+/// Holds the pieces of a mesh item model.
+#[derive(Component)]
+pub struct ItemModelRoot;
+
+/// Adds the model for the stack's item type. The pieces always end up under the last child,
+/// either the scene's root or an `ItemModelRoot`, so other children have to be spawned first.
+pub fn insert_item_model(
+    entity: &mut EntityCommands,
+    registry: &ItemRegistry,
+    scene_assets: &SceneAssets,
+    stack: ItemStack,
+) {
+    match &registry.get(stack.item_type).model {
+        ItemModel::Scene(scene) => {
+            entity.insert(SceneRoot(scene_assets.scene(scene)));
+        }
+        ItemModel::Mesh {
+            mesh,
+            material,
+            layout,
+        } => {
+            entity.with_children(|this| {
+                this.spawn((ItemModelRoot, Transform::default(), Visibility::default()))
+                    .with_children(|model_root| {
+                        for (i, transform) in layout.iter().enumerate() {
+                            model_root.spawn((
+                                Mesh3d(mesh.clone()),
+                                MeshMaterial3d(material.clone()),
+                                *transform,
+                                piece_visibility(i, stack.count),
+                            ));
+                        }
+                    });
+            });
+        }
+    }
+}
+
+fn piece_visibility(index: usize, count: u32) -> Visibility {
+    if index as u32 >= count {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    }
+}
+
+pub fn update_item_stacks(
+    item_stacks: Query<(Entity, &ItemStack), Changed<ItemStack>>,
+    item_stacks_added: Query<(Entity, &ItemStack), Added<ItemStack>>,
+    children: Query<&Children>,
+    mut commands: Commands,
+) {
+    for (item_stack_entity, item_stack) in item_stacks.iter().chain(item_stacks_added.iter()) {
+        let Some(model_root) = children
+            .get(item_stack_entity)
+            .ok()
+            .and_then(|item_stack_children| item_stack_children.last())
+        else {
+            continue;
+        };
+        if let Ok(pieces) = children.get(*model_root) {
+            for (i, piece) in pieces.iter().enumerate() {
+                commands
+                    .entity(*piece)
+                    .insert(piece_visibility(i, item_stack.count));
+            }
+        }
+    }
+}
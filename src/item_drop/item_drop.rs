@@ -1,4 +1,75 @@
 use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::assets::*;
+use crate::item::*;
 
 #[derive(Component)]
-pub struct ItemDrop;
\ No newline at end of file
+pub struct ItemDrop;
+
+/// Throws a stack of items onto the ground. Stacks larger than the item's stack size are
+/// split into several drops.
+pub fn spawn_item_drop(
+    commands: &mut Commands,
+    registry: &ItemRegistry,
+    scene_assets: &SceneAssets,
+    item_type: ItemType,
+    position: Vec3,
+    count: u32,
+    rng: &mut impl Rng,
+) {
+    let stack_size = registry.stack_size(item_type);
+    let mut remaining = count;
+
+    while remaining > 0 {
+        let stack = ItemStack::new(item_type, remaining.min(stack_size));
+        remaining -= stack.count;
+
+        let item_drop = place_item_drop(
+            commands,
+            registry,
+            scene_assets,
+            stack,
+            Transform::from_translation(position).with_scale(GLOBAL_SCALE_VEC),
+        );
+        commands.entity(item_drop).insert(Velocity {
+            linvel: Vec3::new(
+                rng.random_range(-1.0..=1.0),
+                2.0,
+                rng.random_range(-1.0..=1.0),
+            ),
+            angvel: Vec3::new(
+                rng.random_range(-6.0..=6.0),
+                rng.random_range(-6.0..=6.0),
+                rng.random_range(-6.0..=6.0),
+            ),
+        });
+    }
+}
+
+/// Puts a single stack down exactly at `transform`, without throwing it.
+pub fn place_item_drop(
+    commands: &mut Commands,
+    registry: &ItemRegistry,
+    scene_assets: &SceneAssets,
+    stack: ItemStack,
+    transform: Transform,
+) -> Entity {
+    let definition = registry.get(stack.item_type);
+    let mut item_drop = commands.spawn((
+        stack,
+        ItemDrop,
+        transform,
+        RigidBody::Dynamic,
+        Restitution::coefficient(0.5),
+        Name::new(definition.name),
+    ));
+    item_drop.with_children(|this| {
+        for item_collider in definition.colliders_for(stack.count) {
+            this.spawn((item_collider.collider.clone(), item_collider.transform));
+        }
+    });
+    insert_item_model(&mut item_drop, registry, scene_assets, stack);
+    item_drop.id()
+}
@@ -1,5 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod item_drop;
-pub mod wood;
 
 pub use item_drop::*;
-pub use wood::*;
@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::harvestable::harvestable::*;
+use crate::item_drop::*;
+use crate::structure::construction::ConstructionSite;
+use crate::villager::villager::Villager;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum JobKind {
+    Harvest,
+    Haul,
+    Build,
+}
+
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub kind: JobKind,
+    pub target: Entity,
+    pub reserved_by: Option<Entity>,
+}
+
+/// Every piece of work in the village, keyed by the entity the work is done on.
+/// A villager must claim a job before going for its target, and other villagers
+/// leave claimed jobs alone.
+#[derive(Resource, Default)]
+pub struct JobBoard {
+    jobs: HashMap<Entity, Job>,
+    priorities: HashMap<JobKind, f32>,
+}
+
+impl JobBoard {
+    pub fn post(&mut self, kind: JobKind, target: Entity) {
+        self.jobs.entry(target).or_insert(Job {
+            kind,
+            target,
+            reserved_by: None,
+        });
+    }
+
+    pub fn remove(&mut self, target: Entity) -> Option<Job> {
+        self.jobs.remove(&target)
+    }
+
+    pub fn get(&self, target: Entity) -> Option<&Job> {
+        self.jobs.get(&target)
+    }
+
+    pub fn jobs(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.values()
+    }
+
+    /// Whether `villager` may claim the job on `target`.
+    pub fn is_available(&self, target: Entity, villager: Entity) -> bool {
+        self.jobs
+            .get(&target)
+            .is_some_and(|job| job.reserved_by.is_none_or(|holder| holder == villager))
+    }
+
+    pub fn is_reserved_by(&self, target: Entity, villager: Entity) -> bool {
+        self.jobs
+            .get(&target)
+            .is_some_and(|job| job.reserved_by == Some(villager))
+    }
+
+    /// Reserves the job on `target` for `villager`. Fails if there is no such job or
+    /// someone else holds it.
+    pub fn claim(&mut self, target: Entity, villager: Entity) -> bool {
+        match self.jobs.get_mut(&target) {
+            Some(job) if job.reserved_by.is_none_or(|holder| holder == villager) => {
+                job.reserved_by = Some(villager);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn release(&mut self, target: Entity, villager: Entity) {
+        if let Some(job) = self.jobs.get_mut(&target) {
+            if job.reserved_by == Some(villager) {
+                job.reserved_by = None;
+            }
+        }
+    }
+
+    pub fn release_all(&mut self, villager: Entity) {
+        for job in self.jobs.values_mut() {
+            if job.reserved_by == Some(villager) {
+                job.reserved_by = None;
+            }
+        }
+    }
+
+    /// Multiplies the utility score of every job of this kind. Defaults to 1.0.
+    pub fn priority(&self, kind: JobKind) -> f32 {
+        self.priorities.get(&kind).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_priority(&mut self, kind: JobKind, priority: f32) {
+        self.priorities.insert(kind, priority);
+    }
+}
+
+pub fn post_jobs(
+    mut job_board: ResMut<JobBoard>,
+    harvestables: Query<Entity, (Added<Harvestable>, Without<HarvestableDeathmark>)>,
+    item_drops: Query<Entity, Added<ItemDrop>>,
+    construction_sites: Query<Entity, Added<ConstructionSite>>,
+) {
+    for entity in harvestables.iter() {
+        job_board.post(JobKind::Harvest, entity);
+    }
+    for entity in item_drops.iter() {
+        job_board.post(JobKind::Haul, entity);
+    }
+    for entity in construction_sites.iter() {
+        job_board.post(JobKind::Build, entity);
+    }
+}
+
+pub fn remove_finished_jobs(
+    mut job_board: ResMut<JobBoard>,
+    mut harvestable_destroyed_events: EventReader<HarvestableDestroyed>,
+    mut removed_harvestables: RemovedComponents<Harvestable>,
+    mut removed_item_drops: RemovedComponents<ItemDrop>,
+    mut removed_construction_sites: RemovedComponents<ConstructionSite>,
+) {
+    for event in harvestable_destroyed_events.read() {
+        job_board.remove(event.entity);
+    }
+    for entity in removed_harvestables
+        .read()
+        .chain(removed_item_drops.read())
+        .chain(removed_construction_sites.read())
+    {
+        job_board.remove(entity);
+    }
+}
+
+pub fn release_despawned_villager_jobs(
+    mut job_board: ResMut<JobBoard>,
+    mut removed_villagers: RemovedComponents<Villager>,
+) {
+    for villager in removed_villagers.read() {
+        job_board.release_all(villager);
+    }
+}
+
+/// FSM enter hook: a villager that goes back to idle has finished or given up on
+/// whatever it had claimed.
+pub fn release_jobs_on_idle(world: &mut World, entity: Entity) {
+    if let Some(mut job_board) = world.get_resource_mut::<JobBoard>() {
+        job_board.release_all(entity);
+    }
+}
@@ -0,0 +1,25 @@
+use bevy::prelude::*;
+
+use crate::plugins::SimulationSet;
+
+pub mod job_board;
+
+pub use job_board::*;
+
+pub struct JobsPlugin;
+
+impl Plugin for JobsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<JobBoard>();
+        app.add_systems(
+            PreUpdate,
+            (post_jobs, remove_finished_jobs)
+                .chain()
+                .in_set(SimulationSet),
+        );
+        app.add_systems(
+            PostUpdate,
+            release_despawned_villager_jobs.in_set(SimulationSet),
+        );
+    }
+}
@@ -0,0 +1,22 @@
+pub mod assets;
+pub mod behavior;
+pub mod build_mode;
+pub mod debug;
+pub mod fsm;
+pub mod goap;
+pub mod harvestable;
+pub mod headless;
+pub mod inventory;
+pub mod item;
+pub mod item_drop;
+pub mod jobs;
+pub mod loading;
+pub mod navigation;
+pub mod plugins;
+pub mod random;
+pub mod rendering;
+pub mod save;
+pub mod scenario;
+pub mod structure;
+pub mod villager;
+pub mod world;
@@ -0,0 +1,137 @@
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+
+use crate::assets::*;
+use crate::harvestable::tree::TREE_TAG;
+use crate::item::*;
+use crate::structure::registry::*;
+use crate::villager::villager::VILLAGER_SCENE;
+
+/// Where the game is at. Everything in `SimulationSet` waits for `InGame`.
+#[derive(States, Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GameState {
+    /// Waiting on the scene manifest and every model in it.
+    #[default]
+    Loading,
+    InGame,
+}
+
+/// Holds the game in `GameState::Loading` until every model has loaded and the manifest has
+/// the ones the game spawns. Quits with an error naming the model if any of that goes wrong.
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>();
+        app.enable_state_scoped_entities::<GameState>();
+        app.add_systems(OnEnter(GameState::Loading), show_loading_screen);
+        app.add_systems(
+            Update,
+            check_scenes_loaded.run_if(in_state(GameState::Loading)),
+        );
+    }
+}
+
+fn show_loading_screen(mut commands: Commands) {
+    commands.spawn((
+        Text::new("Loading..."),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(12.0),
+            right: Val::Px(12.0),
+            ..default()
+        },
+        StateScoped(GameState::Loading),
+    ));
+}
+
+/// The models the game spawns by id that the manifest doesn't have, described for the player.
+pub fn missing_scenes(
+    scene_assets: &SceneAssets,
+    structures: &StructureRegistry,
+    items: &ItemRegistry,
+) -> Vec<String> {
+    let mut missing = Vec::new();
+    for kind in StructureKind::ALL {
+        let definition = structures.get(kind);
+        if scene_assets.get(definition.scene).is_none() {
+            missing.push(format!("\"{}\" for {}", definition.scene, definition.name));
+        }
+    }
+    for definition in items.definitions() {
+        if let ItemModel::Scene(scene) = definition.model {
+            if scene_assets.get(scene).is_none() {
+                missing.push(format!("\"{}\" for {}", scene, definition.name));
+            }
+        }
+    }
+    if scene_assets.get(VILLAGER_SCENE).is_none() {
+        missing.push(format!("\"{}\" for villagers", VILLAGER_SCENE));
+    }
+    if scene_assets.tagged(TREE_TAG).next().is_none() {
+        missing.push(format!("any model tagged \"{}\" for trees", TREE_TAG));
+    }
+    missing
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_scenes_loaded(
+    asset_server: Res<AssetServer>,
+    manifest_handle: Option<Res<SceneManifestHandle>>,
+    manifests: Option<Res<Assets<SceneManifest>>>,
+    scene_assets: Option<Res<SceneAssets>>,
+    structures: Res<StructureRegistry>,
+    items: Res<ItemRegistry>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    // Without a manifest to load, whatever `SceneAssets` was put in place is all there is
+    if let (Some(manifest_handle), Some(manifests)) = (manifest_handle, manifests) {
+        if let Some(LoadState::Failed(error)) = asset_server.get_load_state(&manifest_handle.0) {
+            error!("could not load {}: {}", SCENE_MANIFEST_PATH, error);
+            exit.send(AppExit::error());
+            return;
+        }
+        let Some(manifest) = manifests.get(&manifest_handle.0) else {
+            return;
+        };
+
+        let mut failed = false;
+        let mut loading = false;
+        for (entry, scene) in manifest.entries.iter().zip(&manifest.scenes) {
+            match asset_server.get_load_state(scene) {
+                Some(LoadState::Loaded) => {}
+                Some(LoadState::Failed(error)) => {
+                    error!(
+                        "could not load scene {} of {} for model \"{}\": {}",
+                        entry.scene, entry.file, entry.id, error
+                    );
+                    failed = true;
+                }
+                _ => loading = true,
+            }
+        }
+        if failed {
+            exit.send(AppExit::error());
+            return;
+        }
+        if loading {
+            return;
+        }
+    }
+
+    let Some(scene_assets) = scene_assets else {
+        return;
+    };
+    let missing = missing_scenes(&scene_assets, &structures, &items);
+    if !missing.is_empty() {
+        for scene in &missing {
+            error!("{} has no model {}", SCENE_MANIFEST_PATH, scene);
+        }
+        exit.send(AppExit::error());
+        return;
+    }
+
+    info!("all models loaded");
+    next_state.set(GameState::InGame);
+}
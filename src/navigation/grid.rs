@@ -0,0 +1,122 @@
+// Side length of a navigation cell, in world units
+pub const CELL_SIZE: f32 = 0.5;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+/// Something villagers have to walk around, covering a circle of `radius` around its
+/// position.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct NavObstacle {
+    pub radius: f32,
+}
+
+/// Which cells are blocked, and by what. Cells that aren't blocked are walkable; the grid
+/// has no edges, so it covers chunks as they stream in.
+#[derive(Resource, Default)]
+pub struct NavGrid {
+    blocked: HashMap<IVec2, Vec<Entity>>,
+    footprints: HashMap<Entity, Vec<IVec2>>,
+    version: u32,
+}
+
+impl NavGrid {
+    pub fn cell_of(position: Vec3) -> IVec2 {
+        IVec2::new(
+            (position.x / CELL_SIZE).floor() as i32,
+            (position.z / CELL_SIZE).floor() as i32,
+        )
+    }
+
+    /// The middle of a cell, at height 0.
+    pub fn center_of(cell: IVec2) -> Vec3 {
+        Vec3::new(
+            (cell.x as f32 + 0.5) * CELL_SIZE,
+            0.0,
+            (cell.y as f32 + 0.5) * CELL_SIZE,
+        )
+    }
+
+    /// Goes up whenever an obstacle is added, moved or removed.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Every cell touched by the circle is blocked, so villagers keep clear of the edge.
+    pub fn add_obstacle(&mut self, entity: Entity, position: Vec3, radius: f32) {
+        self.remove_obstacle(entity);
+
+        let reach = radius + CELL_SIZE / 2.0;
+        let min = Self::cell_of(position - Vec3::new(reach, 0.0, reach));
+        let max = Self::cell_of(position + Vec3::new(reach, 0.0, reach));
+        let mut footprint = Vec::new();
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                let cell = IVec2::new(x, y);
+                // Small obstacles at least block the cell they stand in
+                if Self::center_of(cell).xz().distance(position.xz()) < reach
+                    || cell == Self::cell_of(position)
+                {
+                    self.blocked.entry(cell).or_default().push(entity);
+                    footprint.push(cell);
+                }
+            }
+        }
+        self.footprints.insert(entity, footprint);
+        self.version += 1;
+    }
+
+    pub fn remove_obstacle(&mut self, entity: Entity) {
+        let Some(footprint) = self.footprints.remove(&entity) else {
+            return;
+        };
+        for cell in footprint {
+            if let Some(obstacles) = self.blocked.get_mut(&cell) {
+                obstacles.retain(|obstacle| *obstacle != entity);
+                if obstacles.is_empty() {
+                    self.blocked.remove(&cell);
+                }
+            }
+        }
+        self.version += 1;
+    }
+
+    pub fn obstacles_at(&self, cell: IVec2) -> &[Entity] {
+        self.blocked.get(&cell).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether a cell is free, not counting the obstacles in `ignored`.
+    pub fn is_walkable(&self, cell: IVec2, ignored: &[Entity]) -> bool {
+        self.obstacles_at(cell)
+            .iter()
+            .all(|obstacle| ignored.contains(obstacle))
+    }
+
+    /// Whether the straight line between two points only crosses walkable cells.
+    pub fn is_line_walkable(&self, from: Vec3, to: Vec3, ignored: &[Entity]) -> bool {
+        let steps = (from.xz().distance(to.xz()) / (CELL_SIZE / 4.0)).ceil() as u32;
+        (0..=steps.max(1)).all(|step| {
+            let position = from.lerp(to, step as f32 / steps.max(1) as f32);
+            self.is_walkable(Self::cell_of(position), ignored)
+        })
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_nav_grid(
+    mut nav_grid: ResMut<NavGrid>,
+    obstacles: Query<
+        (Entity, &Transform, &NavObstacle),
+        Or<(Changed<NavObstacle>, Changed<Transform>)>,
+    >,
+    mut removed_obstacles: RemovedComponents<NavObstacle>,
+) {
+    for entity in removed_obstacles.read() {
+        nav_grid.remove_obstacle(entity);
+    }
+    for (entity, transform, obstacle) in obstacles.iter() {
+        nav_grid.add_obstacle(entity, transform.translation, obstacle.radius);
+    }
+}
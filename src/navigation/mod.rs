@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+
+use crate::plugins::SimulationSet;
+
+pub mod grid;
+pub mod path;
+pub mod steering;
+
+pub use grid::*;
+pub use path::*;
+pub use steering::*;
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGrid>();
+        app.add_systems(PreUpdate, update_nav_grid.in_set(SimulationSet));
+        app.add_systems(
+            PostUpdate,
+            separate_agents
+                .before(TransformSystem::TransformPropagate)
+                .in_set(SimulationSet),
+        );
+    }
+}
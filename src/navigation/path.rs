@@ -0,0 +1,260 @@
+// Most cells a single search looks at before settling for getting closer
+const MAX_SEARCH_CELLS: usize = 4096;
+// A waypoint counts as reached this close to it
+const WAYPOINT_RADIUS: f32 = 0.1;
+// How far the target can move before the path is planned again
+const REPLAN_DISTANCE: f32 = CELL_SIZE;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::navigation::grid::*;
+
+const NEIGHBORS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+struct Open {
+    cell: IVec2,
+    estimate: f32,
+}
+
+impl PartialEq for Open {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Open {}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Open {
+    // Reversed, so the heap pops the lowest estimate first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| (other.cell.x, other.cell.y).cmp(&(self.cell.x, self.cell.y)))
+    }
+}
+
+fn octile_distance(from: IVec2, to: IVec2) -> f32 {
+    let delta = (to - from).abs();
+    let (short, long) = (delta.min_element() as f32, delta.max_element() as f32);
+    long + (std::f32::consts::SQRT_2 - 1.0) * short
+}
+
+/// A path from `from` to `to` as a list of cells, not including the starting one.
+/// Diagonal steps never cut the corner of a blocked cell. If `to` can't be reached within
+/// the search budget, this is the path to the cell that got closest, and `false`.
+pub fn find_path(
+    nav_grid: &NavGrid,
+    from: Vec3,
+    to: Vec3,
+    ignored: &[Entity],
+) -> (Vec<IVec2>, bool) {
+    let start = NavGrid::cell_of(from);
+    let goal = NavGrid::cell_of(to);
+
+    let mut open = BinaryHeap::new();
+    let mut came_from = HashMap::<IVec2, IVec2>::new();
+    let mut cost = HashMap::<IVec2, f32>::new();
+    let mut closed = HashSet::<IVec2>::new();
+    open.push(Open {
+        cell: start,
+        estimate: octile_distance(start, goal),
+    });
+    cost.insert(start, 0.0);
+
+    let mut closest = (start, octile_distance(start, goal));
+    let mut reached = false;
+    while let Some(Open { cell, .. }) = open.pop() {
+        if cell == goal {
+            closest = (goal, 0.0);
+            reached = true;
+            break;
+        }
+        if !closed.insert(cell) {
+            continue;
+        }
+        if closed.len() > MAX_SEARCH_CELLS {
+            break;
+        }
+        let remaining = octile_distance(cell, goal);
+        if remaining < closest.1 {
+            closest = (cell, remaining);
+        }
+
+        for offset in NEIGHBORS {
+            let next = cell + offset;
+            // The goal is fine to step into, whatever stands there is what we came for
+            if next != goal && !nav_grid.is_walkable(next, ignored) {
+                continue;
+            }
+            if offset.x != 0
+                && offset.y != 0
+                && !(nav_grid.is_walkable(cell + IVec2::new(offset.x, 0), ignored)
+                    && nav_grid.is_walkable(cell + IVec2::new(0, offset.y), ignored))
+            {
+                continue;
+            }
+
+            let next_cost = cost[&cell] + octile_distance(cell, next);
+            if cost.get(&next).is_some_and(|known| *known <= next_cost) {
+                continue;
+            }
+            cost.insert(next, next_cost);
+            came_from.insert(next, cell);
+            open.push(Open {
+                cell: next,
+                estimate: next_cost + octile_distance(next, goal),
+            });
+        }
+    }
+
+    let mut cells = vec![closest.0];
+    while let Some(previous) = came_from.get(cells.last().unwrap()) {
+        cells.push(*previous);
+    }
+    cells.pop();
+    cells.reverse();
+    (cells, reached)
+}
+
+/// The route a villager is following. Planned on demand by `next_waypoint`, so it never
+/// has to be set up.
+#[derive(Component, Default, Debug)]
+pub struct NavPath {
+    target: Option<Entity>,
+    goal: Vec3,
+    waypoints: VecDeque<Vec3>,
+    /// The target, and whatever we started out standing in, can be walked through.
+    ignored: Vec<Entity>,
+    /// Whether the waypoints end at the goal, or only as close as the search got.
+    complete: bool,
+    /// Grid version the path was planned on. A goal that couldn't be reached isn't
+    /// searched for again until the grid changes.
+    planned_on: u32,
+    version: u32,
+}
+
+impl NavPath {
+    /// What the path was last planned to.
+    pub fn target(&self) -> Option<Entity> {
+        self.target
+    }
+
+    pub fn waypoints(&self) -> impl Iterator<Item = &Vec3> {
+        self.waypoints.iter()
+    }
+
+    fn is_stale(&self, nav_grid: &NavGrid, from: Vec3, target: Entity, goal: Vec3) -> bool {
+        if self.target != Some(target) || self.goal.xz().distance(goal.xz()) > REPLAN_DISTANCE {
+            return true;
+        }
+        if !self.complete && self.waypoints.is_empty() {
+            return self.planned_on != nav_grid.version();
+        }
+        // Only worth planning again if something now stands in the way
+        self.version != nav_grid.version() && !self.is_route_walkable(nav_grid, from, goal)
+    }
+
+    fn is_route_walkable(&self, nav_grid: &NavGrid, from: Vec3, goal: Vec3) -> bool {
+        let mut position = from;
+        for waypoint in self.waypoints.iter() {
+            if !nav_grid.is_line_walkable(position, *waypoint, &self.ignored) {
+                return false;
+            }
+            position = *waypoint;
+        }
+        !self.complete || nav_grid.is_line_walkable(position, goal, &self.ignored)
+    }
+
+    fn plan(&mut self, nav_grid: &NavGrid, from: Vec3, target: Entity, goal: Vec3) {
+        let mut ignored = vec![target];
+        ignored.extend_from_slice(nav_grid.obstacles_at(NavGrid::cell_of(from)));
+
+        self.target = Some(target);
+        self.goal = goal;
+        self.waypoints.clear();
+        self.complete = true;
+        self.planned_on = nav_grid.version();
+        if !nav_grid.is_line_walkable(from, goal, &ignored) {
+            let (cells, complete) = find_path(nav_grid, from, goal, &ignored);
+            // Nowhere closer to go, so walk straight at it
+            self.complete = complete || cells.is_empty();
+
+            let mut points = cells
+                .iter()
+                .map(|cell| NavGrid::center_of(*cell))
+                .collect::<Vec<_>>();
+            if complete {
+                if let Some(last) = points.last_mut() {
+                    *last = goal;
+                }
+            }
+
+            // Cut straight past every waypoint that can be seen beyond
+            let mut position = from;
+            let mut index = 0;
+            while index < points.len() {
+                let mut furthest = index;
+                for (candidate, point) in points.iter().enumerate().skip(index + 1) {
+                    if !nav_grid.is_line_walkable(position, *point, &ignored) {
+                        break;
+                    }
+                    furthest = candidate;
+                }
+                position = points[furthest];
+                self.waypoints.push_back(position);
+                index = furthest + 1;
+            }
+            // The goal itself is followed wherever the target goes
+            if complete {
+                self.waypoints.pop_back();
+            }
+        }
+        self.ignored = ignored;
+    }
+
+    /// Where to head next on the way to `target` standing at `goal`, planning again when
+    /// the target moved or something got in the way. Returns the goal once nothing is left
+    /// to walk around.
+    pub fn next_waypoint(
+        &mut self,
+        nav_grid: &NavGrid,
+        from: Vec3,
+        target: Entity,
+        goal: Vec3,
+    ) -> Vec3 {
+        if self.is_stale(nav_grid, from, target, goal) {
+            self.plan(nav_grid, from, target, goal);
+        }
+        self.version = nav_grid.version();
+        while self
+            .waypoints
+            .front()
+            .is_some_and(|waypoint| waypoint.xz().distance(from.xz()) < WAYPOINT_RADIUS)
+        {
+            self.waypoints.pop_front();
+        }
+        self.waypoints.front().copied().unwrap_or(goal)
+    }
+}
@@ -0,0 +1,120 @@
+// How much room a villager takes up
+const AGENT_RADIUS: f32 = 0.15;
+// Fraction of an overlap between two villagers resolved per second
+const SEPARATION_RATE: f32 = 8.0;
+// Blocked cells closer than this to a walking villager steer it away
+const AVOIDANCE_RADIUS: f32 = 0.45;
+// Caps the avoidance against the path direction, so a villager always makes progress
+const AVOIDANCE_WEIGHT: f32 = 0.5;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+
+use crate::navigation::{grid::*, path::NavPath};
+use crate::world::Terrain;
+
+/// Something that moves around and keeps its distance from others of its kind and from
+/// obstacles.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct NavAgent {
+    pub radius: f32,
+}
+
+impl Default for NavAgent {
+    fn default() -> Self {
+        Self {
+            radius: AGENT_RADIUS,
+        }
+    }
+}
+
+/// A fixed direction per pair, so agents standing exactly on top of each other still part.
+fn tiebreak_direction(a: Entity, b: Entity) -> Vec2 {
+    let angle = (a.index() * 7 + b.index() * 13) as f32;
+    Vec2::from_angle(angle)
+}
+
+/// Steers away from blocked cells close to `position`, other than those of `target`.
+/// Added to the path direction while walking, so corners are rounded off instead of cut.
+pub fn obstacle_avoidance(nav_grid: &NavGrid, position: Vec3, target: Entity) -> Vec3 {
+    let cell = NavGrid::cell_of(position);
+    let mut away = Vec3::ZERO;
+    for x in -1..=1 {
+        for y in -1..=1 {
+            let neighbour = cell + IVec2::new(x, y);
+            if nav_grid.is_walkable(neighbour, &[target]) {
+                continue;
+            }
+            let offset = (position - NavGrid::center_of(neighbour)).with_y(0.0);
+            let distance = offset.length();
+            if distance < AVOIDANCE_RADIUS {
+                away += offset.normalize_or_zero() * (1.0 - distance / AVOIDANCE_RADIUS);
+            }
+        }
+    }
+    away.clamp_length_max(AVOIDANCE_WEIGHT)
+}
+
+/// Pushes overlapping agents apart, without pushing them into obstacles. Runs after all
+/// movement, so agents heading for the same spot queue up around it instead of stacking
+/// up.
+pub fn separate_agents(
+    mut agents: Query<(Entity, &mut Transform, &NavAgent, Option<&NavPath>)>,
+    obstacles: Query<(Entity, &Transform, &NavObstacle), Without<NavAgent>>,
+    terrain: Res<Terrain>,
+    time: Res<Time>,
+) {
+    let mut positions = agents
+        .iter()
+        .map(|(entity, transform, agent, _)| (entity, transform.translation.xz(), agent.radius))
+        .collect::<Vec<_>>();
+    positions.sort_by_key(|(entity, _, _)| *entity);
+
+    // A few villagers at most, so every pair is checked
+    let rate = (SEPARATION_RATE * time.delta_secs()).min(1.0);
+    let mut pushes = vec![Vec2::ZERO; positions.len()];
+    for a in 0..positions.len() {
+        for b in a + 1..positions.len() {
+            let (entity_a, position_a, radius_a) = positions[a];
+            let (entity_b, position_b, radius_b) = positions[b];
+            let offset = position_b - position_a;
+            let overlap = radius_a + radius_b - offset.length();
+            if overlap <= 0.0 {
+                continue;
+            }
+            let direction = offset
+                .try_normalize()
+                .unwrap_or_else(|| tiebreak_direction(entity_a, entity_b));
+            pushes[a] -= direction * overlap * 0.5 * rate;
+            pushes[b] += direction * overlap * 0.5 * rate;
+        }
+    }
+
+    for (index, (entity, ..)) in positions.iter().enumerate() {
+        if pushes[index] == Vec2::ZERO {
+            continue;
+        }
+        let Ok((_, mut transform, agent, path)) = agents.get_mut(*entity) else {
+            continue;
+        };
+        let position = transform.translation.xz();
+        let pushed = position + pushes[index];
+
+        // Never shove anyone deeper into an obstacle, besides the one they're headed for
+        let target = path.and_then(NavPath::target);
+        let into_obstacle =
+            obstacles
+                .iter()
+                .any(|(obstacle_entity, obstacle_transform, obstacle)| {
+                    let center = obstacle_transform.translation.xz();
+                    let distance = pushed.distance(center);
+                    Some(obstacle_entity) != target
+                        && distance < obstacle.radius + agent.radius
+                        && distance < position.distance(center)
+                });
+        if !into_obstacle {
+            transform.translation = terrain.on_surface(Vec3::new(pushed.x, 0.0, pushed.y));
+        }
+    }
+}
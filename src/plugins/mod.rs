@@ -0,0 +1,145 @@
+use bevy::app::PluginGroupBuilder;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::assets::SceneAssets;
+use crate::behavior::BehaviorPlugin;
+use crate::build_mode::BuildModePlugin;
+use crate::debug::DebugPlugin;
+use crate::fsm::FSMPlugin;
+use crate::goap::GoapPlugin;
+use crate::harvestable::{ForestPlugin, HarvestablePlugin};
+use crate::inventory::InventoryPlugin;
+use crate::item::ItemPlugin;
+use crate::jobs::JobsPlugin;
+use crate::loading::{GameState, LoadingPlugin};
+use crate::navigation::NavigationPlugin;
+use crate::random::{RandomPlugin, SimulationRng};
+use crate::rendering::RenderingPlugin;
+use crate::save::SavePlugin;
+use crate::structure::StructurePlugin;
+use crate::villager::VillagerPlugin;
+use crate::world::{Terrain, WorldPlugin};
+
+/// Stands in for the renderer's asset types, with every model left empty.
+struct PlaceholderAssetsPlugin;
+
+impl Plugin for PlaceholderAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Assets<Mesh>>();
+        app.init_resource::<Assets<StandardMaterial>>();
+        app.insert_resource(SceneAssets::placeholder());
+    }
+}
+
+/// What `SimulationPlugins` needs from Bevy when there's no window or GPU, in place of
+/// `DefaultPlugins`. Used by the headless runner and tests.
+pub struct HeadlessPlugins;
+
+impl PluginGroup for HeadlessPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add_group(MinimalPlugins)
+            .add(AssetPlugin::default())
+            .add(TransformPlugin)
+            .add(HierarchyPlugin)
+            .add(bevy::scene::ScenePlugin)
+            .add(PlaceholderAssetsPlugin)
+    }
+}
+
+/// Seeds the simulation randomness and the terrain.
+struct SeedPlugin(u64);
+
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SimulationRng::new(self.0));
+        app.insert_resource(Terrain::new(self.0 as u32));
+    }
+}
+
+/// Every system that moves the village forward. Held back while the game is still loading,
+/// apps without a `GameState` run it right away.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SimulationSet;
+
+fn simulation_running(state: Option<Res<State<GameState>>>) -> bool {
+    state.is_none_or(|state| *state.get() == GameState::InGame)
+}
+
+struct SimulationSetPlugin;
+
+impl Plugin for SimulationSetPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(First, SimulationSet.run_if(simulation_running));
+        app.configure_sets(PreUpdate, SimulationSet.run_if(simulation_running));
+        app.configure_sets(Update, SimulationSet.run_if(simulation_running));
+        app.configure_sets(PostUpdate, SimulationSet.run_if(simulation_running));
+        app.configure_sets(Last, SimulationSet.run_if(simulation_running));
+    }
+}
+
+/// Everything that makes the village run, with nothing to look at: physics, the world,
+/// items, structures and villagers with all their controllers.
+///
+/// Expects the app to already have time, assets, transforms, hierarchy and scenes, like
+/// `DefaultPlugins` or `MinimalPlugins` with those added.
+pub struct SimulationPlugins {
+    pub seed: u64,
+}
+
+impl Default for SimulationPlugins {
+    fn default() -> Self {
+        Self {
+            seed: crate::random::DEFAULT_SEED,
+        }
+    }
+}
+
+impl PluginGroup for SimulationPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(RapierPhysicsPlugin::<NoUserData>::default())
+            .add(SimulationSetPlugin)
+            .add(RandomPlugin)
+            .add(WorldPlugin)
+            .add(ItemPlugin)
+            .add(HarvestablePlugin)
+            .add(ForestPlugin)
+            .add(StructurePlugin)
+            .add(JobsPlugin)
+            .add(NavigationPlugin)
+            .add(InventoryPlugin)
+            .add(VillagerPlugin)
+            .add(FSMPlugin)
+            .add(BehaviorPlugin)
+            .add(GoapPlugin)
+            .add(SeedPlugin(self.seed))
+    }
+}
+
+/// The whole game: the simulation plus loading, saving, rendering, build mode and debug tools. Parts can be
+/// left out with `disable`, e.g. `VillagePlugins::default().build().disable::<DebugPlugin>()`.
+pub struct VillagePlugins {
+    pub seed: u64,
+}
+
+impl Default for VillagePlugins {
+    fn default() -> Self {
+        Self {
+            seed: crate::random::DEFAULT_SEED,
+        }
+    }
+}
+
+impl PluginGroup for VillagePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add_group(SimulationPlugins { seed: self.seed })
+            .add(LoadingPlugin)
+            .add(SavePlugin)
+            .add(RenderingPlugin)
+            .add(BuildModePlugin)
+            .add(DebugPlugin)
+    }
+}
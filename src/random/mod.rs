@@ -0,0 +1,13 @@
+use bevy::prelude::*;
+
+pub mod rng;
+
+pub use rng::*;
+
+pub struct RandomPlugin;
+
+impl Plugin for RandomPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimulationRng>();
+    }
+}
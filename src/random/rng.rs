@@ -0,0 +1,52 @@
+// Used when no seed is configured
+pub const DEFAULT_SEED: u64 = 1;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// The only source of randomness in the simulation. Every system draws from its own stream,
+/// so the order systems happen to run in doesn't change what each of them rolls.
+#[derive(Resource)]
+pub struct SimulationRng {
+    seed: u64,
+    streams: HashMap<&'static str, StdRng>,
+}
+
+impl Default for SimulationRng {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+impl SimulationRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::default(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The stream with this name, forked from the seed the first time it is asked for.
+    /// By convention the name is that of the system using it.
+    pub fn stream(&mut self, name: &'static str) -> &mut StdRng {
+        let seed = self.seed;
+        self.streams
+            .entry(name)
+            .or_insert_with(|| StdRng::seed_from_u64(seed ^ stream_hash(name)))
+    }
+}
+
+/// FNV-1a, which unlike the std hashers is the same on every run and platform.
+fn stream_hash(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
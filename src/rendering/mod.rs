@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+use smooth_bevy_cameras::{
+    controllers::orbit::{OrbitCameraBundle, OrbitCameraController, OrbitCameraPlugin},
+    LookTransformPlugin,
+};
+
+use crate::assets::*;
+use crate::world::ChunkAnchor;
+
+/// Models from the scene manifest, the light and the orbiting camera. Everything the simulation doesn't need.
+pub struct RenderingPlugin;
+
+impl Plugin for RenderingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((LookTransformPlugin, OrbitCameraPlugin::default()));
+        app.init_asset::<SceneManifest>();
+        app.init_asset_loader::<SceneManifestLoader>();
+        app.add_systems(PreStartup, load_scene_manifest);
+        app.add_systems(PreUpdate, apply_scene_manifest);
+        app.add_systems(Startup, setup);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    // Light
+    commands.spawn((
+        PointLight {
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_xyz(4.0, 8.0, 4.0),
+    ));
+    // camera
+    commands.spawn((
+        Camera3d::default(),
+        OrbitCameraBundle::new(
+            OrbitCameraController::default(),
+            Vec3::new(-2.5, 4.5, 9.0),
+            Vec3::new(0., 0., 0.),
+            Vec3::Y,
+        ),
+        ChunkAnchor,
+    ));
+}
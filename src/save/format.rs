@@ -0,0 +1,292 @@
+// Bumped whenever the format changes, see `migrate`
+pub const SAVE_VERSION: u32 = 4;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::path::Path;
+
+use crate::assets::SceneAssetId;
+use crate::behavior::BTNode;
+use crate::goap::GoapGoal;
+use crate::item::ItemType;
+use crate::save::{v1, v2, v3};
+use crate::structure::registry::StructureKind;
+use crate::villager::needs::Need;
+
+/// Stands in for an entity inside a save. Entities get new ids when they are restored,
+/// so every reference between saved entities goes through one of these.
+pub type SaveId = u64;
+
+/// Everything needed to bring a village back exactly as it was.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub seed: u64,
+    pub terrain_seed: u32,
+    pub terrain_height: f32,
+    pub chunks: Vec<(i32, i32)>,
+    pub villagers: Vec<SavedVillager>,
+    pub trees: Vec<SavedTree>,
+    pub item_drops: Vec<SavedItemDrop>,
+    pub structures: Vec<SavedStructure>,
+    /// Missing from saves made before anything could be built.
+    #[serde(default)]
+    pub construction_sites: Vec<SavedConstructionSite>,
+    /// Jobs that were reserved, as (target, villager).
+    pub claims: Vec<(SaveId, SaveId)>,
+    /// Trees of chunks that were loaded once and aren't anymore.
+    pub unloaded_trees: Vec<SavedUnloadedChunk>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedTransform {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+impl From<&Transform> for SavedTransform {
+    fn from(transform: &Transform) -> Self {
+        Self {
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+            scale: transform.scale.to_array(),
+        }
+    }
+}
+
+impl From<SavedTransform> for Transform {
+    fn from(saved: SavedTransform) -> Self {
+        Transform {
+            translation: Vec3::from_array(saved.translation),
+            rotation: Quat::from_array(saved.rotation),
+            scale: Vec3::from_array(saved.scale),
+        }
+    }
+}
+
+/// The FSM state a villager was in, with its targets.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SavedState {
+    Idle,
+    WalkingTo {
+        target: SaveId,
+        proximity: f32,
+    },
+    WalkingToHarvest {
+        target: SaveId,
+        proximity: f32,
+    },
+    Harvesting {
+        target: SaveId,
+    },
+    PickingUp {
+        target: SaveId,
+        proximity: f32,
+    },
+    BringingTo {
+        target: SaveId,
+        proximity: f32,
+    },
+    WalkingHome {
+        target: SaveId,
+        need: Need,
+        proximity: f32,
+    },
+    Eating {
+        house: SaveId,
+    },
+    Sleeping {
+        house: SaveId,
+    },
+    Fetching {
+        target: SaveId,
+        site: SaveId,
+        proximity: f32,
+    },
+    Supplying {
+        target: SaveId,
+        proximity: f32,
+    },
+    Building {
+        target: SaveId,
+        proximity: f32,
+    },
+}
+
+/// What decides a villager's next task.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SavedController {
+    /// The FSM with utility decisions when idle.
+    Utility,
+    /// A GOAP agent. The plan is made again after loading.
+    Goap { goal: GoapGoal },
+    /// A behavior tree. It starts over from the root after loading.
+    Behavior { root: BTNode },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedVillager {
+    pub id: SaveId,
+    pub name: String,
+    pub transform: SavedTransform,
+    pub movement_speed: f32,
+    pub harvesting_speed: f32,
+    pub homeliness: f32,
+    pub woodcutting: f32,
+    pub hauling: f32,
+    pub hunger: f32,
+    pub energy: f32,
+    pub inventory: Vec<(ItemType, u32)>,
+    pub max_count: u32,
+    pub max_weight: f32,
+    /// None for villagers without an FSM, like behavior tree villagers.
+    pub state: Option<SavedState>,
+    pub controller: SavedController,
+    /// Missing from saves made before villagers had homes, they get one assigned again.
+    #[serde(default)]
+    pub home: Option<SaveId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedTree {
+    pub id: SaveId,
+    pub kind: SceneAssetId,
+    pub transform: SavedTransform,
+    pub health: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedUnloadedChunk {
+    pub coord: (i32, i32),
+    pub trees: Vec<SavedUnloadedTree>,
+}
+
+/// A tree of an unloaded chunk. It has no entity, so nothing can refer to it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedUnloadedTree {
+    pub kind: SceneAssetId,
+    pub transform: SavedTransform,
+    pub health: f32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedItemDrop {
+    pub id: SaveId,
+    pub transform: SavedTransform,
+    pub item_type: ItemType,
+    pub count: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedStructure {
+    pub id: SaveId,
+    pub kind: StructureKind,
+    pub transform: SavedTransform,
+    /// What its stockpile holds, for structures with storage.
+    pub storage: Option<SavedStorage>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedStorage {
+    pub item_type: ItemType,
+    pub stored: u32,
+    pub capacity: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedConstructionSite {
+    pub id: SaveId,
+    pub transform: SavedTransform,
+    pub kind: StructureKind,
+    pub delivered: u32,
+    pub progress: f32,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    /// Saved by a newer version of the game, or by one too old to migrate.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "could not access save: {}", error),
+            SaveError::Parse(error) => write!(f, "could not parse save: {}", error),
+            SaveError::Serialize(error) => write!(f, "could not write save: {}", error),
+            SaveError::UnsupportedVersion(version) => {
+                write!(f, "save version {} is not supported", version)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<std::io::Error> for SaveError {
+    fn from(error: std::io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for SaveError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        SaveError::Parse(error)
+    }
+}
+
+impl From<ron::Error> for SaveError {
+    fn from(error: ron::Error) -> Self {
+        SaveError::Serialize(error)
+    }
+}
+
+/// Just enough of any save to tell which format the rest is in.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+/// Reads a save of any supported version and brings it up to `SAVE_VERSION`.
+///
+/// When the format changes: bump `SAVE_VERSION`, move the old structs into a module for
+/// the old version and add an arm here that parses them and converts them forward.
+pub fn migrate(text: &str) -> Result<SaveFile, SaveError> {
+    let header = ron::from_str::<SaveHeader>(text)?;
+    match header.version {
+        SAVE_VERSION => Ok(ron::from_str::<SaveFile>(text)?),
+        3 => Ok(ron::from_str::<v3::SaveFile>(text)?.into()),
+        2 => Ok(v3::SaveFile::from(ron::from_str::<v2::SaveFile>(text)?).into()),
+        1 => {
+            Ok(v3::SaveFile::from(v2::SaveFile::from(ron::from_str::<v1::SaveFile>(text)?)).into())
+        }
+        version => Err(SaveError::UnsupportedVersion(version)),
+    }
+}
+
+pub fn to_ron(save: &SaveFile) -> Result<String, SaveError> {
+    Ok(ron::ser::to_string_pretty(
+        save,
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
+
+pub fn write_save(path: &Path, save: &SaveFile) -> Result<(), SaveError> {
+    if let Some(directory) = path.parent() {
+        std::fs::create_dir_all(directory)?;
+    }
+    std::fs::write(path, to_ron(save)?)?;
+    Ok(())
+}
+
+pub fn read_save(path: &Path) -> Result<SaveFile, SaveError> {
+    migrate(&std::fs::read_to_string(path)?)
+}
@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+
+use crate::plugins::SimulationSet;
+
+pub mod format;
+pub mod requests;
+pub mod snapshot;
+mod v1;
+mod v2;
+mod v3;
+
+pub use format::*;
+pub use requests::*;
+pub use snapshot::*;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveGame>();
+        app.add_event::<LoadGame>();
+        app.init_resource::<Autosave>();
+        app.add_systems(Update, autosave.in_set(SimulationSet));
+        app.add_systems(Last, handle_save_requests.in_set(SimulationSet));
+    }
+}
@@ -0,0 +1,78 @@
+// Seconds between autosaves
+const AUTOSAVE_INTERVAL: f32 = 60.0;
+const AUTOSAVE_PATH: &str = "saves/autosave.ron";
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+
+use std::path::PathBuf;
+
+use crate::save::{format::*, snapshot::*};
+
+/// Saves the village at the end of the frame.
+#[derive(Event, Clone, Debug)]
+pub struct SaveGame {
+    pub path: PathBuf,
+}
+
+/// Replaces the village with a saved one at the end of the frame.
+#[derive(Event, Clone, Debug)]
+pub struct LoadGame {
+    pub path: PathBuf,
+}
+
+#[derive(Resource)]
+pub struct Autosave {
+    pub timer: Timer,
+    pub path: PathBuf,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(AUTOSAVE_INTERVAL, TimerMode::Repeating),
+            path: PathBuf::from(AUTOSAVE_PATH),
+        }
+    }
+}
+
+pub fn autosave(
+    mut autosave: ResMut<Autosave>,
+    mut save_events: EventWriter<SaveGame>,
+    time: Res<Time>,
+) {
+    if autosave.timer.tick(time.delta()).just_finished() {
+        save_events.send(SaveGame {
+            path: autosave.path.clone(),
+        });
+    }
+}
+
+pub fn handle_save_requests(world: &mut World) {
+    let saves = world
+        .resource_mut::<Events<SaveGame>>()
+        .drain()
+        .collect::<Vec<_>>();
+    for request in saves {
+        let save = capture(world);
+        match write_save(&request.path, &save) {
+            Ok(()) => info!("saved village to {}", request.path.display()),
+            Err(error) => error!("{}: {}", request.path.display(), error),
+        }
+    }
+
+    let loads = world
+        .resource_mut::<Events<LoadGame>>()
+        .drain()
+        .collect::<Vec<_>>();
+    for request in loads {
+        match read_save(&request.path) {
+            Ok(save) => {
+                restore(world, &save);
+                info!("loaded village from {}", request.path.display());
+            }
+            Err(error) => error!("{}: {}", request.path.display(), error),
+        }
+    }
+}
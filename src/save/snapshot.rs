@@ -0,0 +1,570 @@
+use bevy::ecs::world::CommandQueue;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::assets::*;
+use crate::behavior::*;
+use crate::fsm::{components::*, machine::*};
+use crate::goap::*;
+use crate::harvestable::{harvestable::*, tree::*};
+use crate::inventory::*;
+use crate::item::*;
+use crate::item_drop::*;
+use crate::jobs::*;
+use crate::random::SimulationRng;
+use crate::save::format::*;
+use crate::structure::{construction::*, registry::*, stockpile::*};
+use crate::villager::{needs::Needs, population::Home, utility::VillagerTraits, villager::*};
+use crate::world::*;
+
+fn save_id(entity: Entity) -> SaveId {
+    entity.to_bits()
+}
+
+fn saved_state(world: &World, entity: Entity, state: VillagerState) -> Option<SavedState> {
+    Some(match state {
+        VillagerState::Idle => SavedState::Idle,
+        VillagerState::WalkingTo => {
+            let state = world.get::<FSMWalkingTo>(entity)?;
+            SavedState::WalkingTo {
+                target: save_id(state.target),
+                proximity: state.proximity,
+            }
+        }
+        VillagerState::WalkingToHarvest => {
+            let state = world.get::<FSMWalkingToHarvest>(entity)?;
+            SavedState::WalkingToHarvest {
+                target: save_id(state.target),
+                proximity: state.proximity,
+            }
+        }
+        VillagerState::Harvesting => SavedState::Harvesting {
+            target: save_id(world.get::<FSMHarvesting>(entity)?.target),
+        },
+        VillagerState::PickingUp => {
+            let state = world.get::<FSMPickingUp>(entity)?;
+            SavedState::PickingUp {
+                target: save_id(state.target),
+                proximity: state.proximity,
+            }
+        }
+        VillagerState::BringingTo => {
+            let state = world.get::<FSMBringingTo>(entity)?;
+            SavedState::BringingTo {
+                target: save_id(state.target),
+                proximity: state.proximity,
+            }
+        }
+        VillagerState::WalkingHome => {
+            let state = world.get::<FSMWalkingHome>(entity)?;
+            SavedState::WalkingHome {
+                target: save_id(state.target),
+                need: state.need,
+                proximity: state.proximity,
+            }
+        }
+        VillagerState::Eating => SavedState::Eating {
+            house: save_id(world.get::<FSMEating>(entity)?.house),
+        },
+        VillagerState::Sleeping => SavedState::Sleeping {
+            house: save_id(world.get::<FSMSleeping>(entity)?.house),
+        },
+        VillagerState::Fetching => {
+            let state = world.get::<FSMFetching>(entity)?;
+            SavedState::Fetching {
+                target: save_id(state.target),
+                site: save_id(state.site),
+                proximity: state.proximity,
+            }
+        }
+        VillagerState::Supplying => {
+            let state = world.get::<FSMSupplying>(entity)?;
+            SavedState::Supplying {
+                target: save_id(state.target),
+                proximity: state.proximity,
+            }
+        }
+        VillagerState::Building => {
+            let state = world.get::<FSMBuilding>(entity)?;
+            SavedState::Building {
+                target: save_id(state.target),
+                proximity: state.proximity,
+            }
+        }
+    })
+}
+
+/// Takes a snapshot of the whole village.
+pub fn capture(world: &mut World) -> SaveFile {
+    let mut villagers = world.query::<(
+        Entity,
+        &Transform,
+        &Villager,
+        &VillagerTraits,
+        &Needs,
+        &Inventory,
+        Option<&FSM<VillagerState>>,
+        Option<&GoapAgent>,
+        Option<&BehaviorTree>,
+        Option<&Name>,
+        Option<&Home>,
+    )>();
+    let villagers = villagers
+        .iter(world)
+        .map(
+            |(
+                entity,
+                transform,
+                villager,
+                traits,
+                needs,
+                inventory,
+                fsm,
+                goap,
+                behavior,
+                name,
+                home,
+            )| {
+                let controller = match (goap, behavior) {
+                    (Some(agent), _) => SavedController::Goap { goal: agent.goal },
+                    (None, Some(behavior_tree)) => SavedController::Behavior {
+                        root: behavior_tree.root().clone(),
+                    },
+                    (None, None) => SavedController::Utility,
+                };
+                SavedVillager {
+                    id: save_id(entity),
+                    name: name.map_or("Villager".to_string(), |name| name.to_string()),
+                    transform: transform.into(),
+                    movement_speed: villager.movement_speed,
+                    harvesting_speed: villager.harvesting_speed,
+                    homeliness: traits.homeliness,
+                    woodcutting: traits.woodcutting,
+                    hauling: traits.hauling,
+                    hunger: needs.hunger,
+                    energy: needs.energy,
+                    inventory: inventory
+                        .stacks()
+                        .iter()
+                        .map(|stack| (stack.item_type, stack.count))
+                        .collect(),
+                    max_count: inventory.max_count,
+                    max_weight: inventory.max_weight,
+                    state: fsm.and_then(|fsm| saved_state(world, entity, fsm.current())),
+                    controller,
+                    home: home.map(|home| save_id(home.house)),
+                }
+            },
+        )
+        .collect();
+
+    let mut trees = world.query::<(Entity, &Transform, &Tree, &Harvestable)>();
+    let trees = trees
+        .iter(world)
+        .map(|(entity, transform, tree, harvestable)| SavedTree {
+            id: save_id(entity),
+            kind: tree.kind.clone(),
+            transform: transform.into(),
+            health: harvestable.health,
+        })
+        .collect();
+
+    let mut item_drops = world.query_filtered::<(Entity, &Transform, &ItemStack), With<ItemDrop>>();
+    let item_drops = item_drops
+        .iter(world)
+        .map(|(entity, transform, stack)| SavedItemDrop {
+            id: save_id(entity),
+            transform: transform.into(),
+            item_type: stack.item_type,
+            count: stack.count,
+        })
+        .collect();
+
+    let mut structures = world.query::<(Entity, &Transform, &Structure, Option<&Children>)>();
+    let structures = structures
+        .iter(world)
+        .map(|(entity, transform, structure, children)| {
+            let storage = children
+                .and_then(stockpile_of)
+                .and_then(|stockpile_entity| {
+                    let stored = world.get::<ItemStack>(stockpile_entity)?;
+                    let stockpile = world.get::<Stockpile>(stockpile_entity)?;
+                    Some(SavedStorage {
+                        item_type: stored.item_type,
+                        stored: stored.count,
+                        capacity: stockpile.capacity,
+                    })
+                });
+            SavedStructure {
+                id: save_id(entity),
+                kind: structure.kind,
+                transform: transform.into(),
+                storage,
+            }
+        })
+        .collect();
+
+    let mut construction_sites = world.query::<(Entity, &Transform, &ConstructionSite)>();
+    let construction_sites = construction_sites
+        .iter(world)
+        .map(|(entity, transform, site)| SavedConstructionSite {
+            id: save_id(entity),
+            transform: transform.into(),
+            kind: site.kind,
+            delivered: site.delivered,
+            progress: site.progress,
+        })
+        .collect();
+
+    let claims = world
+        .resource::<JobBoard>()
+        .jobs()
+        .filter_map(|job| Some((save_id(job.target), save_id(job.reserved_by?))))
+        .collect();
+
+    let mut chunks = world
+        .resource::<ChunkMap>()
+        .coords()
+        .map(|coord| (coord.0.x, coord.0.y))
+        .collect::<Vec<_>>();
+    chunks.sort();
+
+    // Worlds without a forest have none
+    let mut unloaded_trees = world
+        .get_resource::<UnloadedTrees>()
+        .into_iter()
+        .flat_map(UnloadedTrees::iter)
+        .map(|(coord, trees)| SavedUnloadedChunk {
+            coord: (coord.0.x, coord.0.y),
+            trees: trees
+                .iter()
+                .map(|(kind, transform, health)| SavedUnloadedTree {
+                    kind: kind.clone(),
+                    transform: transform.into(),
+                    health: *health,
+                })
+                .collect(),
+        })
+        .collect::<Vec<_>>();
+    unloaded_trees.sort_by_key(|chunk| chunk.coord);
+
+    let terrain = *world.resource::<Terrain>();
+    SaveFile {
+        version: SAVE_VERSION,
+        seed: world.resource::<SimulationRng>().seed(),
+        terrain_seed: terrain.seed,
+        terrain_height: terrain.height_scale,
+        chunks,
+        villagers,
+        trees,
+        item_drops,
+        structures,
+        construction_sites,
+        claims,
+        unloaded_trees,
+    }
+}
+
+/// Replaces the current village with the one in `save`.
+pub fn restore(world: &mut World, save: &SaveFile) {
+    let mut current = world.query_filtered::<Entity, Or<(
+        With<Villager>,
+        With<Tree>,
+        With<ItemDrop>,
+        With<Structure>,
+        With<ConstructionSite>,
+        With<Chunk>,
+    )>>();
+    for entity in current.iter(world).collect::<Vec<_>>() {
+        world.entity_mut(entity).despawn_recursive();
+    }
+    *world.resource_mut::<ChunkMap>() = ChunkMap::default();
+    let mut unloaded_trees = UnloadedTrees::default();
+    for chunk in save.unloaded_trees.iter() {
+        let trees = chunk
+            .trees
+            .iter()
+            .map(|tree| (tree.kind.clone(), tree.transform.into(), tree.health))
+            .collect();
+        unloaded_trees.remember(ChunkCoord::new(chunk.coord.0, chunk.coord.1), trees);
+    }
+    world.insert_resource(unloaded_trees);
+    let mut job_board = world.resource_mut::<JobBoard>();
+    let targets = job_board.jobs().map(|job| job.target).collect::<Vec<_>>();
+    for target in targets {
+        job_board.remove(target);
+    }
+
+    world.insert_resource(SimulationRng::new(save.seed));
+    world.insert_resource(Terrain {
+        seed: save.terrain_seed,
+        height_scale: save.terrain_height,
+    });
+
+    let mut entities = HashMap::<SaveId, Entity>::new();
+    let mut chunks = Vec::new();
+    world.resource_scope(|world, mut meshes: Mut<Assets<Mesh>>| {
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        let scene_assets = world.resource::<SceneAssets>();
+        let registry = world.resource::<ItemRegistry>();
+        let structures = world.resource::<StructureRegistry>();
+        let terrain = world.resource::<Terrain>();
+
+        if let Some(chunk_assets) = world.get_resource::<ChunkAssets>() {
+            for (x, z) in save.chunks.iter() {
+                let coord = ChunkCoord::new(*x, *z);
+                let chunk = spawn_chunk(&mut commands, &mut meshes, chunk_assets, terrain, coord);
+                commands.entity(chunk).insert(RestoredChunk);
+                chunks.push((coord, chunk));
+            }
+        }
+
+        for tree in save.trees.iter() {
+            let transform = Transform::from(tree.transform);
+            let entity = spawn_tree_of_kind(
+                &mut commands,
+                scene_assets,
+                tree.kind.clone(),
+                transform.translation,
+            );
+            commands.entity(entity).insert((
+                transform,
+                Harvestable {
+                    health: tree.health,
+                },
+            ));
+            entities.insert(tree.id, entity);
+        }
+
+        for item_drop in save.item_drops.iter() {
+            let entity = place_item_drop(
+                &mut commands,
+                registry,
+                scene_assets,
+                ItemStack::new(item_drop.item_type, item_drop.count),
+                item_drop.transform.into(),
+            );
+            entities.insert(item_drop.id, entity);
+        }
+
+        for structure in save.structures.iter() {
+            let transform = Transform::from(structure.transform);
+            let entity = spawn_structure(
+                &mut commands,
+                structures,
+                registry,
+                scene_assets,
+                structure.kind,
+                transform.translation,
+            );
+            commands.entity(entity).insert(transform);
+            entities.insert(structure.id, entity);
+        }
+
+        for site in save.construction_sites.iter() {
+            let transform = Transform::from(site.transform);
+            let entity = spawn_construction_site(
+                &mut commands,
+                structures,
+                site.kind,
+                transform.translation,
+            );
+            commands.entity(entity).insert((
+                transform,
+                ConstructionSite {
+                    delivered: site.delivered,
+                    progress: site.progress,
+                    ..ConstructionSite::new(site.kind, structures.get(site.kind))
+                },
+            ));
+            entities.insert(site.id, entity);
+        }
+
+        // Traits are overwritten right after, this only keeps the spawner happy
+        let mut rng = StdRng::seed_from_u64(save.seed);
+        let villager_entities = save
+            .villagers
+            .iter()
+            .map(|villager| {
+                let transform = Transform::from(villager.transform);
+                let entity =
+                    spawn_villager(&mut commands, scene_assets, transform.translation, &mut rng);
+                entities.insert(villager.id, entity);
+                entity
+            })
+            .collect::<Vec<_>>();
+
+        // Now that everything has its new entity, references between them can be remapped.
+        // Anything that wasn't saved becomes a placeholder, which fails the task gracefully
+        let remap = |id: SaveId| entities.get(&id).copied().unwrap_or(Entity::PLACEHOLDER);
+
+        for (villager, entity) in save.villagers.iter().zip(villager_entities) {
+            let mut inventory = Inventory::new(villager.max_count, villager.max_weight);
+            for (item_type, count) in villager.inventory.iter() {
+                inventory.add(*item_type, *count);
+            }
+
+            let mut entity_commands = commands.entity(entity);
+            entity_commands.insert((
+                Transform::from(villager.transform),
+                Villager {
+                    movement_speed: villager.movement_speed,
+                    harvesting_speed: villager.harvesting_speed,
+                },
+                VillagerTraits {
+                    homeliness: villager.homeliness,
+                    woodcutting: villager.woodcutting,
+                    hauling: villager.hauling,
+                },
+                Needs {
+                    hunger: villager.hunger,
+                    energy: villager.energy,
+                },
+                inventory,
+                Name::new(villager.name.clone()),
+            ));
+            if let Some(house) = villager.home {
+                entity_commands.insert(Home {
+                    house: remap(house),
+                });
+            }
+
+            match villager.state {
+                None => {
+                    entity_commands
+                        .remove::<(FSM<VillagerState>, FSMIdle, FSMHistory<VillagerState>)>();
+                }
+                Some(SavedState::Idle) => {}
+                Some(state) => {
+                    entity_commands.remove::<FSMIdle>();
+                    match state {
+                        SavedState::Idle => unreachable!(),
+                        SavedState::WalkingTo { target, proximity } => {
+                            entity_commands.insert(FSM::start(FSMWalkingTo {
+                                target: remap(target),
+                                proximity,
+                            }))
+                        }
+                        SavedState::WalkingToHarvest { target, proximity } => entity_commands
+                            .insert(FSM::start(FSMWalkingToHarvest {
+                                target: remap(target),
+                                proximity,
+                            })),
+                        SavedState::Harvesting { target } => {
+                            entity_commands.insert(FSM::start(FSMHarvesting {
+                                target: remap(target),
+                            }))
+                        }
+                        SavedState::PickingUp { target, proximity } => {
+                            entity_commands.insert(FSM::start(FSMPickingUp {
+                                target: remap(target),
+                                proximity,
+                            }))
+                        }
+                        SavedState::BringingTo { target, proximity } => {
+                            entity_commands.insert(FSM::start(FSMBringingTo {
+                                target: remap(target),
+                                proximity,
+                            }))
+                        }
+                        SavedState::WalkingHome {
+                            target,
+                            need,
+                            proximity,
+                        } => entity_commands.insert(FSM::start(FSMWalkingHome {
+                            target: remap(target),
+                            need,
+                            proximity,
+                        })),
+                        SavedState::Eating { house } => {
+                            entity_commands.insert(FSM::start(FSMEating {
+                                house: remap(house),
+                            }))
+                        }
+                        SavedState::Sleeping { house } => {
+                            entity_commands.insert(FSM::start(FSMSleeping {
+                                house: remap(house),
+                            }))
+                        }
+                        SavedState::Fetching {
+                            target,
+                            site,
+                            proximity,
+                        } => entity_commands.insert(FSM::start(FSMFetching {
+                            target: remap(target),
+                            site: remap(site),
+                            proximity,
+                        })),
+                        SavedState::Supplying { target, proximity } => {
+                            entity_commands.insert(FSM::start(FSMSupplying {
+                                target: remap(target),
+                                proximity,
+                            }))
+                        }
+                        SavedState::Building { target, proximity } => {
+                            entity_commands.insert(FSM::start(FSMBuilding {
+                                target: remap(target),
+                                proximity,
+                            }))
+                        }
+                    };
+                }
+            }
+
+            match &villager.controller {
+                SavedController::Utility => {}
+                SavedController::Goap { goal } => {
+                    entity_commands.insert(GoapAgent::new(*goal));
+                }
+                SavedController::Behavior { root } => {
+                    entity_commands.insert(BehaviorTree::new(root.clone()));
+                }
+            }
+        }
+
+        queue.apply(world);
+    });
+
+    let mut chunk_map = world.resource_mut::<ChunkMap>();
+    for (coord, chunk) in chunks {
+        chunk_map.insert(coord, chunk);
+    }
+
+    for structure in save.structures.iter() {
+        let Some(storage) = &structure.storage else {
+            continue;
+        };
+        let Some(stockpile_entity) = world
+            .get::<Children>(entities[&structure.id])
+            .and_then(stockpile_of)
+        else {
+            continue;
+        };
+        if let Some(mut stored) = world.get_mut::<ItemStack>(stockpile_entity) {
+            *stored = ItemStack::new(storage.item_type, storage.stored);
+        }
+        if let Some(mut stockpile) = world.get_mut::<Stockpile>(stockpile_entity) {
+            stockpile.capacity = storage.capacity;
+        }
+    }
+
+    // Jobs are posted again as soon as their targets show up, but claims would be lost
+    let mut job_board = world.resource_mut::<JobBoard>();
+    for tree in save.trees.iter() {
+        job_board.post(JobKind::Harvest, entities[&tree.id]);
+    }
+    for item_drop in save.item_drops.iter() {
+        job_board.post(JobKind::Haul, entities[&item_drop.id]);
+    }
+    for site in save.construction_sites.iter() {
+        job_board.post(JobKind::Build, entities[&site.id]);
+    }
+    for (target, villager) in save.claims.iter() {
+        if let (Some(target), Some(villager)) = (entities.get(target), entities.get(villager)) {
+            job_board.claim(*target, *villager);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Saves from before models were listed in the scene manifest. Trees named their model
+//! with a fixed enum instead of a manifest id, everything else is the same as in version 2.
+
+use serde::Deserialize;
+
+use crate::assets::SceneAssetId;
+use crate::save::format::{self, *};
+use crate::save::v2::{self, SavedHouse, SavedWoodHut};
+
+#[derive(Deserialize)]
+enum SceneAssetType {
+    StructureHouse,
+    StructureWoodHut,
+    TreePine,
+    TreeRound,
+    TreeDead,
+    Villager,
+    ResourceWood,
+}
+
+impl SceneAssetType {
+    fn id(&self) -> SceneAssetId {
+        SceneAssetId::new(match self {
+            SceneAssetType::StructureHouse => "house",
+            SceneAssetType::StructureWoodHut => "wood_hut",
+            SceneAssetType::TreePine => "tree_pine",
+            SceneAssetType::TreeRound => "tree_round",
+            SceneAssetType::TreeDead => "tree_dead",
+            SceneAssetType::Villager => "villager",
+            SceneAssetType::ResourceWood => "wood",
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct SavedTree {
+    id: SaveId,
+    kind: SceneAssetType,
+    transform: SavedTransform,
+    health: f32,
+}
+
+#[derive(Deserialize)]
+pub struct SaveFile {
+    seed: u64,
+    terrain_seed: u32,
+    terrain_height: f32,
+    chunks: Vec<(i32, i32)>,
+    villagers: Vec<SavedVillager>,
+    trees: Vec<SavedTree>,
+    item_drops: Vec<SavedItemDrop>,
+    houses: Vec<SavedHouse>,
+    wood_huts: Vec<SavedWoodHut>,
+    #[serde(default)]
+    construction_sites: Vec<SavedConstructionSite>,
+    claims: Vec<(SaveId, SaveId)>,
+}
+
+impl From<SaveFile> for v2::SaveFile {
+    fn from(save: SaveFile) -> Self {
+        v2::SaveFile {
+            seed: save.seed,
+            terrain_seed: save.terrain_seed,
+            terrain_height: save.terrain_height,
+            chunks: save.chunks,
+            villagers: save.villagers,
+            trees: save
+                .trees
+                .into_iter()
+                .map(|tree| format::SavedTree {
+                    id: tree.id,
+                    kind: tree.kind.id(),
+                    transform: tree.transform,
+                    health: tree.health,
+                })
+                .collect(),
+            item_drops: save.item_drops,
+            houses: save.houses,
+            wood_huts: save.wood_huts,
+            construction_sites: save.construction_sites,
+            claims: save.claims,
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Saves from before structures were saved by kind. Houses and wood huts had a list each,
+//! everything else is the same as in version 3.
+
+use serde::Deserialize;
+
+use crate::item::ItemType;
+use crate::save::format::*;
+use crate::save::v3;
+use crate::structure::registry::StructureKind;
+
+#[derive(Deserialize)]
+pub(super) struct SavedHouse {
+    pub(super) id: SaveId,
+    pub(super) transform: SavedTransform,
+}
+
+#[derive(Deserialize)]
+pub(super) struct SavedWoodHut {
+    pub(super) id: SaveId,
+    pub(super) transform: SavedTransform,
+    pub(super) item_type: ItemType,
+    pub(super) stored: u32,
+    pub(super) capacity: u32,
+}
+
+#[derive(Deserialize)]
+pub struct SaveFile {
+    pub(super) seed: u64,
+    pub(super) terrain_seed: u32,
+    pub(super) terrain_height: f32,
+    pub(super) chunks: Vec<(i32, i32)>,
+    pub(super) villagers: Vec<SavedVillager>,
+    pub(super) trees: Vec<SavedTree>,
+    pub(super) item_drops: Vec<SavedItemDrop>,
+    pub(super) houses: Vec<SavedHouse>,
+    pub(super) wood_huts: Vec<SavedWoodHut>,
+    #[serde(default)]
+    pub(super) construction_sites: Vec<SavedConstructionSite>,
+    pub(super) claims: Vec<(SaveId, SaveId)>,
+}
+
+impl From<SaveFile> for v3::SaveFile {
+    fn from(save: SaveFile) -> Self {
+        let houses = save.houses.into_iter().map(|house| SavedStructure {
+            id: house.id,
+            kind: StructureKind::House,
+            transform: house.transform,
+            storage: None,
+        });
+        let wood_huts = save.wood_huts.into_iter().map(|wood_hut| SavedStructure {
+            id: wood_hut.id,
+            kind: StructureKind::WoodHut,
+            transform: wood_hut.transform,
+            storage: Some(SavedStorage {
+                item_type: wood_hut.item_type,
+                stored: wood_hut.stored,
+                capacity: wood_hut.capacity,
+            }),
+        });
+
+        v3::SaveFile {
+            seed: save.seed,
+            terrain_seed: save.terrain_seed,
+            terrain_height: save.terrain_height,
+            chunks: save.chunks,
+            villagers: save.villagers,
+            trees: save.trees,
+            item_drops: save.item_drops,
+            structures: houses.chain(wood_huts).collect(),
+            construction_sites: save.construction_sites,
+            claims: save.claims,
+        }
+    }
+}
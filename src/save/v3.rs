@@ -0,0 +1,40 @@
+//! Saves from before the trees of unloaded chunks were kept. Those chunks grow a new forest
+//! when they are loaded again, everything else is the same as now.
+
+use serde::Deserialize;
+
+use crate::save::format::{self, *};
+
+#[derive(Deserialize)]
+pub struct SaveFile {
+    pub(super) seed: u64,
+    pub(super) terrain_seed: u32,
+    pub(super) terrain_height: f32,
+    pub(super) chunks: Vec<(i32, i32)>,
+    pub(super) villagers: Vec<SavedVillager>,
+    pub(super) trees: Vec<SavedTree>,
+    pub(super) item_drops: Vec<SavedItemDrop>,
+    pub(super) structures: Vec<SavedStructure>,
+    #[serde(default)]
+    pub(super) construction_sites: Vec<SavedConstructionSite>,
+    pub(super) claims: Vec<(SaveId, SaveId)>,
+}
+
+impl From<SaveFile> for format::SaveFile {
+    fn from(save: SaveFile) -> Self {
+        format::SaveFile {
+            version: SAVE_VERSION,
+            seed: save.seed,
+            terrain_seed: save.terrain_seed,
+            terrain_height: save.terrain_height,
+            chunks: save.chunks,
+            villagers: save.villagers,
+            trees: save.trees,
+            item_drops: save.item_drops,
+            structures: save.structures,
+            construction_sites: save.construction_sites,
+            claims: save.claims,
+            unloaded_trees: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+use crate::assets::*;
+use crate::goap::{GoapAgent, GoapGoal};
+use crate::item::ItemRegistry;
+use crate::random::SimulationRng;
+use crate::structure::registry::*;
+use crate::villager::villager::*;
+use crate::world::Terrain;
+
+/// The village every new game starts with: a house, a wood hut and one villager of each
+/// kind. Shared by the game and the headless runner, so balance numbers match.
+pub fn spawn_starting_village(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+    item_registry: Res<ItemRegistry>,
+    structure_registry: Res<StructureRegistry>,
+    terrain: Res<Terrain>,
+    mut rng: ResMut<SimulationRng>,
+    asset_server: Res<AssetServer>,
+) {
+    let rng = rng.stream("spawn_starting_village");
+
+    // Houses
+    spawn_structure(
+        &mut commands,
+        &structure_registry,
+        &item_registry,
+        &scene_assets,
+        StructureKind::House,
+        terrain.on_surface(Vec3::new(3.0, 0.0, 1.0)),
+    );
+    spawn_structure(
+        &mut commands,
+        &structure_registry,
+        &item_registry,
+        &scene_assets,
+        StructureKind::WoodHut,
+        terrain.on_surface(Vec3::new(0.633975, 0.0, 3.09808)),
+    );
+
+    // Villagers
+    spawn_villager(
+        &mut commands,
+        &scene_assets,
+        terrain.on_surface(Vec3::new(0.0, 0.0, 0.0)),
+        rng,
+    );
+    spawn_villager_with_behavior(
+        &mut commands,
+        &scene_assets,
+        terrain.on_surface(Vec3::new(0.5, 0.0, 0.5)),
+        asset_server.load("behaviors/hauler.bt.ron"),
+        rng,
+    );
+    let planner = spawn_villager(
+        &mut commands,
+        &scene_assets,
+        terrain.on_surface(Vec3::new(-0.5, 0.0, 0.5)),
+        rng,
+    );
+    commands.entity(planner).insert((
+        GoapAgent::new(GoapGoal::HutWoodAtLeast(20)),
+        Name::new("Villager (Planner)"),
+    ));
+}
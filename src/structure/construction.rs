@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+
+use crate::assets::*;
+use crate::item::*;
+use crate::navigation::NavObstacle;
+use crate::structure::registry::*;
+use crate::world::Terrain;
+
+/// Where a structure is going up. Villagers bring it wood until it has all it costs, then
+/// work on it until it's done and turns into the real thing.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct ConstructionSite {
+    pub kind: StructureKind,
+    /// Copied from the structure's definition, so villagers can tell what's left to do
+    /// without the registry.
+    pub wood_cost: u32,
+    pub build_time: f32,
+    pub delivered: u32,
+    pub progress: f32,
+}
+
+impl ConstructionSite {
+    pub fn new(kind: StructureKind, definition: &StructureDefinition) -> Self {
+        Self {
+            kind,
+            wood_cost: definition.wood_cost,
+            build_time: definition.build_time,
+            delivered: 0,
+            progress: 0.0,
+        }
+    }
+
+    pub fn wood_needed(&self) -> u32 {
+        self.wood_cost.saturating_sub(self.delivered)
+    }
+
+    pub fn is_supplied(&self) -> bool {
+        self.wood_needed() == 0
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.is_supplied() && self.progress >= self.build_time
+    }
+}
+
+pub fn spawn_construction_site(
+    commands: &mut Commands,
+    structures: &StructureRegistry,
+    kind: StructureKind,
+    position: Vec3,
+) -> Entity {
+    let definition = structures.get(kind);
+    commands
+        .spawn((
+            Transform::from_translation(position),
+            Visibility::default(),
+            ConstructionSite::new(kind, definition),
+            NavObstacle {
+                radius: definition.footprint,
+            },
+            Name::new("Construction Site"),
+        ))
+        .id()
+}
+
+/// Whether a footprint of `radius` at `position` stays clear of every obstacle, given as
+/// positions and radii.
+pub fn is_site_clear(
+    position: Vec3,
+    radius: f32,
+    obstacles: impl IntoIterator<Item = (Vec3, f32)>,
+) -> bool {
+    obstacles.into_iter().all(|(obstacle, obstacle_radius)| {
+        obstacle.xz().distance(position.xz()) >= radius + obstacle_radius
+    })
+}
+
+/// Asks for a construction site. Ignored if the footprint isn't clear.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlaceStructure {
+    pub kind: StructureKind,
+    pub position: Vec3,
+}
+
+/// Sent when a construction site turns into its structure.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct StructureCompleted {
+    pub site: Entity,
+    pub structure: Entity,
+    pub kind: StructureKind,
+}
+
+pub fn place_construction_sites(
+    mut commands: Commands,
+    mut place_structure_events: EventReader<PlaceStructure>,
+    obstacles: Query<(&Transform, &NavObstacle)>,
+    structures: Res<StructureRegistry>,
+    terrain: Res<Terrain>,
+) {
+    // Sites placed this frame aren't in the query yet
+    let mut placed = Vec::new();
+    for event in place_structure_events.read() {
+        let radius = structures.get(event.kind).footprint;
+        let existing = obstacles
+            .iter()
+            .map(|(transform, obstacle)| (transform.translation, obstacle.radius));
+        if !is_site_clear(
+            event.position,
+            radius,
+            existing.chain(placed.iter().copied()),
+        ) {
+            continue;
+        }
+
+        let position = terrain.on_surface(event.position);
+        spawn_construction_site(&mut commands, &structures, event.kind, position);
+        placed.push((position, radius));
+    }
+}
+
+pub fn complete_construction_sites(
+    mut commands: Commands,
+    sites: Query<(Entity, &Transform, &ConstructionSite)>,
+    structures: Res<StructureRegistry>,
+    items: Res<ItemRegistry>,
+    scene_assets: Res<SceneAssets>,
+    mut structure_completed_events: EventWriter<StructureCompleted>,
+) {
+    for (site, transform, construction_site) in sites.iter() {
+        if !construction_site.is_complete() {
+            continue;
+        }
+        commands.entity(site).despawn_recursive();
+        let structure = spawn_structure(
+            &mut commands,
+            &structures,
+            &items,
+            &scene_assets,
+            construction_site.kind,
+            transform.translation,
+        );
+        structure_completed_events.send(StructureCompleted {
+            site,
+            structure,
+            kind: construction_site.kind,
+        });
+    }
+}
@@ -0,0 +1,8 @@
+use bevy::prelude::*;
+
+/// Room for villagers to live in, eat and sleep. Any structure with housing in its
+/// definition has it.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Housing {
+    pub capacity: u32,
+}
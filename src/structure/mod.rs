@@ -1,2 +1,27 @@
-pub mod house;
-pub mod wood_hut;
\ No newline at end of file
+use bevy::prelude::*;
+
+use crate::plugins::SimulationSet;
+
+pub mod construction;
+pub mod housing;
+pub mod registry;
+pub mod stockpile;
+
+pub struct StructurePlugin;
+
+impl Plugin for StructurePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(registry::StructureRegistry::new());
+        app.add_event::<stockpile::StorageFull>();
+        app.add_event::<construction::PlaceStructure>();
+        app.add_event::<construction::StructureCompleted>();
+        app.add_systems(
+            Update,
+            (
+                construction::place_construction_sites,
+                construction::complete_construction_sites,
+            )
+                .in_set(SimulationSet),
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::*;
+use crate::item::*;
+use crate::navigation::NavObstacle;
+use crate::structure::{housing::Housing, stockpile::Stockpile};
+
+/// Everything that can be built.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum StructureKind {
+    House,
+    WoodHut,
+}
+
+impl StructureKind {
+    /// In the order build mode offers them.
+    pub const ALL: [StructureKind; 2] = [StructureKind::House, StructureKind::WoodHut];
+}
+
+/// Storage a structure comes with, kept on its first child.
+pub struct StructureStorage {
+    pub item_type: ItemType,
+    pub capacity: u32,
+    /// Where the stored stack sits, relative to the structure.
+    pub offset: Vec3,
+}
+
+pub struct StructureDefinition {
+    pub name: &'static str,
+    /// Model id in the scene manifest.
+    pub scene: &'static str,
+    /// Radius villagers walk around and other structures have to stay clear of.
+    pub footprint: f32,
+    /// Wood that has to be brought to a construction site before work can start.
+    pub wood_cost: u32,
+    /// Seconds of work to put it together, for one villager at full speed.
+    pub build_time: f32,
+    pub storage: Option<StructureStorage>,
+    /// How many villagers can live here.
+    pub housing: u32,
+}
+
+#[derive(Resource, Default)]
+pub struct StructureRegistry {
+    definitions: HashMap<StructureKind, StructureDefinition>,
+}
+
+impl StructureRegistry {
+    pub fn register(&mut self, kind: StructureKind, definition: StructureDefinition) -> &mut Self {
+        self.definitions.insert(kind, definition);
+        self
+    }
+
+    /// Panics for unregistered structures, every `StructureKind` is expected to be registered.
+    pub fn get(&self, kind: StructureKind) -> &StructureDefinition {
+        self.definitions
+            .get(&kind)
+            .unwrap_or_else(|| panic!("structure {:?} is not registered", kind))
+    }
+}
+
+fn house() -> StructureDefinition {
+    StructureDefinition {
+        name: "House",
+        scene: "house",
+        footprint: 0.6,
+        wood_cost: 12,
+        build_time: 8.0,
+        storage: None,
+        housing: 4,
+    }
+}
+
+fn wood_hut() -> StructureDefinition {
+    StructureDefinition {
+        name: "Wood Hut",
+        scene: "wood_hut",
+        footprint: 0.5,
+        wood_cost: 8,
+        build_time: 5.0,
+        storage: Some(StructureStorage {
+            item_type: ItemType::Wood,
+            capacity: 32,
+            offset: Vec3::new(0.0, 0.075, 0.0),
+        }),
+        housing: 0,
+    }
+}
+
+impl StructureRegistry {
+    /// Every structure the game knows about. New structures get a definition here.
+    pub fn new() -> Self {
+        let mut registry = Self::default();
+        registry
+            .register(StructureKind::House, house())
+            .register(StructureKind::WoodHut, wood_hut());
+        registry
+    }
+}
+
+/// Which kind of structure an entity is. What a structure can be used for comes from its
+/// definition, as `Housing` or a stockpile, not from its kind.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Structure {
+    pub kind: StructureKind,
+}
+
+/// Spawns a finished structure of any kind, with its model, footprint and storage.
+pub fn spawn_structure(
+    commands: &mut Commands,
+    structures: &StructureRegistry,
+    items: &ItemRegistry,
+    scene_assets: &SceneAssets,
+    kind: StructureKind,
+    position: Vec3,
+) -> Entity {
+    let definition = structures.get(kind);
+    let mut structure = commands.spawn((
+        SceneRoot(scene_assets.scene(definition.scene)),
+        scene_assets.transform(definition.scene, position),
+        Structure { kind },
+        NavObstacle {
+            radius: definition.footprint,
+        },
+        Name::new(definition.name),
+    ));
+    if definition.housing > 0 {
+        structure.insert(Housing {
+            capacity: definition.housing,
+        });
+    }
+
+    if let Some(storage) = &definition.storage {
+        structure.with_children(|this| {
+            let stored = ItemStack::new(storage.item_type, 0);
+            let mut stockpile = this.spawn((
+                stored,
+                Stockpile {
+                    capacity: storage.capacity,
+                },
+                Transform::from_translation(storage.offset),
+            ));
+            insert_item_model(&mut stockpile, items, scene_assets, stored);
+        });
+    }
+    structure.id()
+}
@@ -0,0 +1,47 @@
+// Where overflow lands relative to the building, just outside its door
+const OVERFLOW_OFFSET: Vec3 = Vec3::new(0.0, 0.5, 0.9);
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+
+use crate::item::*;
+
+/// Storage inside a building. Sits on the building's first child, next to the `ItemStack`
+/// it holds.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Stockpile {
+    pub capacity: u32,
+}
+
+impl Stockpile {
+    pub fn room(&self, stored: &ItemStack) -> u32 {
+        self.capacity.saturating_sub(stored.count)
+    }
+
+    /// Stores as much as fits and returns how many didn't.
+    pub fn deposit(&self, stored: &mut ItemStack, count: u32) -> u32 {
+        let deposited = count.min(self.room(stored));
+        stored.count += deposited;
+        count - deposited
+    }
+}
+
+/// Sent when a deposit fills a stockpile up. Anything that didn't fit is dropped next to
+/// the building and counted in `overflow`.
+#[derive(Event, Clone, Debug)]
+pub struct StorageFull {
+    pub building: Entity,
+    pub item_type: ItemType,
+    pub overflow: u32,
+}
+
+/// The stockpile of a storage building, if it has one.
+pub fn stockpile_of(building_children: &Children) -> Option<Entity> {
+    building_children.first().copied()
+}
+
+/// Where items that don't fit into a building are dropped.
+pub fn overflow_position(building: &Transform) -> Vec3 {
+    building.translation + building.rotation * OVERFLOW_OFFSET
+}
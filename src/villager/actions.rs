@@ -1,15 +1,80 @@
+// Villagers start slowing down this close to where they're going
+const ARRIVAL_RADIUS: f32 = 0.5;
+// and never get slower than this fraction of their speed
+const MIN_ARRIVAL_SPEED: f32 = 0.3;
+
+////////////////////////////////////////////////////////////////
+
 use bevy::prelude::*;
+use rand::Rng;
 
-const EPSILON: f32 = 0.0001;
+use crate::assets::*;
+use crate::harvestable::harvestable::*;
+use crate::inventory::*;
+use crate::item::*;
+use crate::item_drop::*;
+use crate::navigation::*;
+use crate::world::Terrain;
 
+/// Takes one step along the path to `target`, going around obstacles.
+#[allow(clippy::too_many_arguments)]
 pub fn walk_to(
     transform: &mut Transform,
+    path: &mut NavPath,
+    target: Entity,
     target_transform: &Transform,
     movement_speed: f32,
-    time: &Res<Time>,
+    nav_grid: &NavGrid,
+    terrain: &Terrain,
+    time: &Time,
 ) {
+    let goal = target_transform.translation;
+    let waypoint = path.next_waypoint(nav_grid, transform.translation, target, goal);
     // Maybe later we can use Rapier RigidBodies and set the velocity
-    let direction = (target_transform.translation - transform.translation).with_y(0.0);
-    transform.translation += direction.normalize() * movement_speed * time.delta_secs();
-    transform.look_at(transform.translation + direction, Vec3::Y);
+    let direction = (waypoint - transform.translation).with_y(0.0);
+    let mut step = movement_speed * time.delta_secs();
+    if waypoint != goal {
+        // Don't overshoot the corner
+        step = step.min(direction.length());
+    } else {
+        step *= (direction.length() / ARRIVAL_RADIUS).clamp(MIN_ARRIVAL_SPEED, 1.0);
+    }
+    // Round off corners instead of brushing past obstacles, but only while on the way
+    let heading = if direction == Vec3::ZERO {
+        Vec3::ZERO
+    } else {
+        (direction.normalize() + obstacle_avoidance(nav_grid, transform.translation, target))
+            .normalize_or_zero()
+    };
+    transform.translation += heading * step;
+    transform.translation = terrain.on_surface(transform.translation);
+    if heading != Vec3::ZERO {
+        transform.look_at(transform.translation + heading, Vec3::Y);
+    }
+}
+
+pub fn harvest(harvestable: &mut Harvestable, harvesting_speed: f32, time: &Time) {
+    harvestable.health -= time.delta_secs() * harvesting_speed;
+}
+
+/// Puts everything in the inventory down on the ground.
+pub fn drop_inventory(
+    commands: &mut Commands,
+    registry: &ItemRegistry,
+    scene_assets: &SceneAssets,
+    inventory: &mut Inventory,
+    position: Vec3,
+    rng: &mut impl Rng,
+) {
+    for stack in inventory.clear() {
+        spawn_item_drop(
+            commands,
+            registry,
+            scene_assets,
+            stack.item_type,
+            position,
+            stack.count,
+            rng,
+        );
+    }
 }
@@ -1,2 +1,32 @@
-pub mod villager;
+use bevy::prelude::*;
+
+use crate::plugins::SimulationSet;
+
 pub mod actions;
+pub mod needs;
+pub mod population;
+pub mod utility;
+#[allow(clippy::module_inception)]
+pub mod villager;
+
+pub struct VillagerPlugin;
+
+impl Plugin for VillagerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<population::Population>();
+        app.add_event::<population::PopulationChanged>();
+        app.add_systems(
+            Update,
+            (
+                needs::decay_needs,
+                population::assign_homes,
+                population::grow_population,
+            )
+                .in_set(SimulationSet),
+        );
+        app.add_systems(
+            PostUpdate,
+            population::track_population.in_set(SimulationSet),
+        );
+    }
+}
@@ -0,0 +1,105 @@
+// Per second, needs go from 1.0 (satisfied) down to 0.0
+const HUNGER_DECAY: f32 = 0.01;
+const ENERGY_DECAY: f32 = 0.007;
+// Per second, while eating or sleeping in a house
+const EATING_RATE: f32 = 0.25;
+const SLEEPING_RATE: f32 = 0.1;
+
+// Below this a villager drops what it's doing to take care of the need
+const CRITICAL_THRESHOLD: f32 = 0.2;
+// Below this a villager starts slowing down
+const LOW_THRESHOLD: f32 = 0.4;
+// Speed multiplier when a need is completely empty
+const MIN_PENALTY: f32 = 0.4;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::fsm::components::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Need {
+    Hunger,
+    Energy,
+}
+
+/// How satisfied a villager's needs are, from 1.0 (fully) down to 0.0.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Needs {
+    pub hunger: f32,
+    pub energy: f32,
+}
+
+impl Default for Needs {
+    fn default() -> Self {
+        Self {
+            hunger: 1.0,
+            energy: 1.0,
+        }
+    }
+}
+
+impl Needs {
+    pub fn get(&self, need: Need) -> f32 {
+        match need {
+            Need::Hunger => self.hunger,
+            Need::Energy => self.energy,
+        }
+    }
+
+    pub fn satisfy(&mut self, need: Need, amount: f32) {
+        let value = match need {
+            Need::Hunger => &mut self.hunger,
+            Need::Energy => &mut self.energy,
+        };
+        *value = (*value + amount).clamp(0.0, 1.0);
+    }
+
+    /// The lowest need, if it is low enough to drop everything for.
+    pub fn critical(&self) -> Option<Need> {
+        [Need::Hunger, Need::Energy]
+            .into_iter()
+            .filter(|need| self.get(*need) < CRITICAL_THRESHOLD)
+            .min_by(|a, b| self.get(*a).total_cmp(&self.get(*b)))
+    }
+
+    fn penalty(value: f32) -> f32 {
+        if value >= LOW_THRESHOLD {
+            1.0
+        } else {
+            MIN_PENALTY + (1.0 - MIN_PENALTY) * value / LOW_THRESHOLD
+        }
+    }
+
+    /// Multiplier on movement speed. Tired villagers walk slower.
+    pub fn movement_penalty(&self) -> f32 {
+        Self::penalty(self.energy)
+    }
+
+    /// Multiplier on harvesting speed. Hungry or tired villagers chop slower.
+    pub fn harvesting_penalty(&self) -> f32 {
+        Self::penalty(self.hunger) * Self::penalty(self.energy)
+    }
+}
+
+pub fn decay_needs(
+    mut villagers: Query<(&mut Needs, Has<FSMEating>, Has<FSMSleeping>)>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_secs();
+
+    for (mut needs, eating, sleeping) in &mut villagers {
+        if eating {
+            needs.satisfy(Need::Hunger, EATING_RATE * delta);
+        } else {
+            needs.satisfy(Need::Hunger, -HUNGER_DECAY * delta);
+        }
+        if sleeping {
+            needs.satisfy(Need::Energy, SLEEPING_RATE * delta);
+        } else {
+            needs.satisfy(Need::Energy, -ENERGY_DECAY * delta);
+        }
+    }
+}
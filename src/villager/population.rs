@@ -0,0 +1,195 @@
+// Most villagers the village grows to, no matter how much housing there is
+const POPULATION_CAP: u32 = 20;
+// Seconds between births, at most
+const BIRTH_INTERVAL: f32 = 60.0;
+// Every resident of a house has to be at least this fed for a child to be born there
+const BIRTH_MIN_HUNGER: f32 = 0.5;
+// Newborns appear this far from the middle of the house, outside its footprint
+const BIRTH_DISTANCE: f32 = 0.8;
+// Food taken out of storage to feed every newborn
+const BIRTH_FOOD_COST: u32 = 4;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::Rng;
+
+use crate::assets::SceneAssets;
+use crate::item::*;
+use crate::random::SimulationRng;
+use crate::structure::{housing::Housing, stockpile::Stockpile};
+use crate::villager::{needs::Needs, villager::*};
+use crate::world::Terrain;
+
+/// The house a villager lives in, where it goes to eat and sleep.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Home {
+    pub house: Entity,
+}
+
+#[derive(Resource, Debug)]
+pub struct Population {
+    /// Villagers right now, updated at the end of every frame.
+    pub count: u32,
+    pub cap: u32,
+    pub until_next_birth: f32,
+}
+
+impl Default for Population {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            cap: POPULATION_CAP,
+            until_next_birth: BIRTH_INTERVAL,
+        }
+    }
+}
+
+/// Sent whenever the number of villagers changes, for any reason.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct PopulationChanged {
+    pub previous: u32,
+    pub population: u32,
+}
+
+/// How many villagers call each house home.
+fn residents(homes: impl Iterator<Item = Entity>) -> HashMap<Entity, u32> {
+    let mut residents = HashMap::new();
+    for house in homes {
+        *residents.entry(house).or_insert(0) += 1;
+    }
+    residents
+}
+
+/// Gives every homeless villager the nearest house with room, and takes away homes that
+/// are gone.
+pub fn assign_homes(
+    mut commands: Commands,
+    villagers: Query<(Entity, &Transform, Option<&Home>), With<Villager>>,
+    houses: Query<(Entity, &Transform, &Housing)>,
+) {
+    let mut residents = residents(
+        villagers
+            .iter()
+            .filter_map(|(_, _, home)| home.map(|home| home.house))
+            .filter(|house| houses.contains(*house)),
+    );
+
+    for (villager, transform, home) in villagers.iter() {
+        if home.is_some_and(|home| houses.contains(home.house)) {
+            continue;
+        }
+
+        let house = houses
+            .iter()
+            .filter(|(house, _, housing)| {
+                residents.get(house).copied().unwrap_or(0) < housing.capacity
+            })
+            .min_by(|a, b| {
+                let a = a.1.translation.distance(transform.translation);
+                let b = b.1.translation.distance(transform.translation);
+                a.total_cmp(&b)
+            })
+            .map(|(house, _, _)| house);
+
+        match house {
+            Some(house) => {
+                *residents.entry(house).or_insert(0) += 1;
+                commands.entity(villager).insert(Home { house });
+            }
+            None if home.is_some() => {
+                commands.entity(villager).remove::<Home>();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Every so often, the house with the most room whose residents are all well fed gets a
+/// new villager, as long as there is enough food in storage to feed it.
+#[allow(clippy::too_many_arguments)]
+pub fn grow_population(
+    mut commands: Commands,
+    mut population: ResMut<Population>,
+    villagers: Query<(&Needs, &Home), With<Villager>>,
+    houses: Query<(Entity, &Transform, &Housing)>,
+    mut stockpiles: Query<&mut ItemStack, With<Stockpile>>,
+    scene_assets: Res<SceneAssets>,
+    terrain: Res<Terrain>,
+    mut rng: ResMut<SimulationRng>,
+    time: Res<Time>,
+) {
+    // Stays at zero while nobody is eligible, so the next eligible house gets a birth
+    // straight away rather than after however long the wait ran over
+    population.until_next_birth = (population.until_next_birth - time.delta_secs()).max(0.0);
+    if population.until_next_birth > 0.0 {
+        return;
+    }
+    if population.count >= population.cap {
+        return;
+    }
+    let food = stockpiles
+        .iter()
+        .filter(|item_stack| item_stack.item_type == ItemType::Food)
+        .map(|item_stack| item_stack.count)
+        .sum::<u32>();
+    if food < BIRTH_FOOD_COST {
+        return;
+    }
+
+    let residents = residents(villagers.iter().map(|(_, home)| home.house));
+    let hungry = villagers
+        .iter()
+        .filter(|(needs, _)| needs.hunger < BIRTH_MIN_HUNGER)
+        .map(|(_, home)| home.house)
+        .collect::<Vec<_>>();
+    let Some((house, house_transform, _)) = houses
+        .iter()
+        .filter_map(|(house, transform, housing)| {
+            let count = residents.get(&house).copied().unwrap_or(0);
+            let eligible = count > 0 && count < housing.capacity && !hungry.contains(&house);
+            eligible.then_some((house, transform.translation, housing.capacity - count))
+        })
+        // Ties go to the lowest entity, so the choice doesn't hang on query order
+        .max_by_key(|(house, _, room)| (*room, std::cmp::Reverse(*house)))
+    else {
+        return;
+    };
+
+    let mut owed = BIRTH_FOOD_COST;
+    for mut item_stack in stockpiles.iter_mut() {
+        if item_stack.item_type != ItemType::Food {
+            continue;
+        }
+        let taken = item_stack.count.min(owed);
+        item_stack.count -= taken;
+        owed -= taken;
+        if owed == 0 {
+            break;
+        }
+    }
+
+    let rng = rng.stream("grow_population");
+    let angle = rng.random_range(0.0..std::f32::consts::TAU);
+    let position = terrain
+        .on_surface(house_transform + Vec3::new(angle.cos(), 0.0, angle.sin()) * BIRTH_DISTANCE);
+    let villager = spawn_villager(&mut commands, &scene_assets, position, rng);
+    commands.entity(villager).insert(Home { house });
+    population.until_next_birth = BIRTH_INTERVAL;
+}
+
+pub fn track_population(
+    mut population: ResMut<Population>,
+    villagers: Query<(), With<Villager>>,
+    mut population_changed_events: EventWriter<PopulationChanged>,
+) {
+    let count = villagers.iter().count() as u32;
+    if count != population.count {
+        population_changed_events.send(PopulationChanged {
+            previous: population.count,
+            population: count,
+        });
+        population.count = count;
+    }
+}
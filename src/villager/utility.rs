@@ -0,0 +1,341 @@
+// Distance at which a target is worth half as much as one right next to the villager
+const DISTANCE_FALLOFF: f32 = 3.0;
+// Closer than this to a house counts as already being home
+const AT_HOME_DISTANCE: f32 = 0.5;
+
+const WALK_HOME_BASE_SCORE: f32 = 0.15;
+const CHOP_TREE_BASE_SCORE: f32 = 0.6;
+const HAUL_ITEMS_BASE_SCORE: f32 = 0.8;
+// Above hauling, items already picked up should get where they're going first
+const DELIVER_ITEMS_BASE_SCORE: f32 = 0.9;
+// Scales the squared urgency of a need, high enough that a critical need beats any work
+const NEED_WEIGHT: f32 = 2.0;
+// Below delivering, carried wood goes to huts before sites come fetching it
+const SUPPLY_SITE_BASE_SCORE: f32 = 0.7;
+// Once the wood is in, finishing the structure is worth more than more wood
+const BUILD_SITE_BASE_SCORE: f32 = 0.75;
+// Needs above this aren't worth a trip home
+const NEED_SEEK_THRESHOLD: f32 = 0.6;
+
+// Traits are rolled within this much of 1.0
+const TRAIT_SPREAD: f32 = 0.2;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::Rng;
+
+use crate::item::ItemType;
+use crate::jobs::*;
+use crate::villager::needs::*;
+
+/// Personal preferences that scale how much a villager likes each kind of action.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct VillagerTraits {
+    pub homeliness: f32,
+    pub woodcutting: f32,
+    pub hauling: f32,
+}
+
+impl Default for VillagerTraits {
+    fn default() -> Self {
+        Self {
+            homeliness: 1.0,
+            woodcutting: 1.0,
+            hauling: 1.0,
+        }
+    }
+}
+
+impl VillagerTraits {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self {
+            homeliness: rng.random_range(1.0 - TRAIT_SPREAD..=1.0 + TRAIT_SPREAD),
+            woodcutting: rng.random_range(1.0 - TRAIT_SPREAD..=1.0 + TRAIT_SPREAD),
+            hauling: rng.random_range(1.0 - TRAIT_SPREAD..=1.0 + TRAIT_SPREAD),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IdleAction {
+    WalkHome { house: Entity },
+    ChopTree { tree: Entity },
+    HaulItems { item_drop: Entity },
+    DeliverItems { storage: Entity },
+    Eat { house: Entity },
+    Sleep { house: Entity },
+    SupplySite { site: Entity, wood_hut: Entity },
+    BuildSite { site: Entity },
+}
+
+impl IdleAction {
+    /// The target whose job has to be claimed before the action can start.
+    pub fn job_target(&self) -> Option<Entity> {
+        match self {
+            IdleAction::ChopTree { tree } => Some(*tree),
+            IdleAction::HaulItems { item_drop } => Some(*item_drop),
+            IdleAction::SupplySite { site, .. } | IdleAction::BuildSite { site } => Some(*site),
+            IdleAction::WalkHome { .. }
+            | IdleAction::DeliverItems { .. }
+            | IdleAction::Eat { .. }
+            | IdleAction::Sleep { .. } => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScoredAction {
+    pub action: IdleAction,
+    pub score: f32,
+}
+
+/// The last decision an idle villager made, kept around for inspection.
+#[derive(Component, Clone, Debug, Default)]
+pub struct UtilityDecision {
+    pub chosen: Option<ScoredAction>,
+    pub candidates: Vec<ScoredAction>,
+}
+
+impl UtilityDecision {
+    /// Candidates with a positive score, best first. The next one is the fallback when
+    /// the chosen action's job can't be claimed after all.
+    pub fn ranked(&self) -> Vec<ScoredAction> {
+        let mut ranked = self
+            .candidates
+            .iter()
+            .filter(|candidate| candidate.score > 0.0)
+            .copied()
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        ranked
+    }
+}
+
+/// Everything a scorer may look at when a villager decides what to do next.
+pub struct DecisionContext<'a> {
+    pub villager: Entity,
+    pub position: Vec3,
+    pub traits: VillagerTraits,
+    pub needs: Needs,
+    pub houses: &'a [(Entity, Vec3)],
+    /// The villager's own house, if it has one.
+    pub home: Option<(Entity, Vec3)>,
+    pub trees: &'a [(Entity, Vec3)],
+    /// Item drops the villager has room for and some storage has room for too.
+    pub item_drops: &'a [(Entity, Vec3, ItemType)],
+    /// Storage with room for something the villager is carrying.
+    pub deliverable_storage: &'a [(Entity, Vec3)],
+    /// Storage with any wood stored.
+    pub stocked_wood_huts: &'a [(Entity, Vec3)],
+    /// Construction sites, with how much wood each still needs.
+    pub construction_sites: &'a [(Entity, Vec3, u32)],
+    /// How many items the villager is already carrying.
+    pub carried: u32,
+    /// Fraction of storage in use, per item type that has any storage at all.
+    pub storage_fill: &'a HashMap<ItemType, f32>,
+    pub job_board: &'a JobBoard,
+}
+
+pub type Scorer = fn(&DecisionContext) -> Option<ScoredAction>;
+
+/// New idle actions get a scorer here and a match arm in `fsm_update_idle`.
+pub const SCORERS: [Scorer; 8] = [
+    score_walk_home,
+    score_chop_tree,
+    score_haul_items,
+    score_deliver_items,
+    score_supply_site,
+    score_build_site,
+    score_eat,
+    score_sleep,
+];
+
+pub fn distance_consideration(distance: f32) -> f32 {
+    1.0 / (1.0 + distance / DISTANCE_FALLOFF)
+}
+
+fn nearest(position: Vec3, targets: &[(Entity, Vec3)]) -> Option<(Entity, f32)> {
+    targets
+        .iter()
+        .map(|(entity, target)| (*entity, position.distance(*target)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// The villager's home, or the nearest house for the homeless.
+fn home_of(context: &DecisionContext) -> Option<(Entity, f32)> {
+    match context.home {
+        Some((house, position)) => Some((house, context.position.distance(position))),
+        None => nearest(context.position, context.houses),
+    }
+}
+
+/// Nearest target whose job the villager is allowed to claim.
+fn nearest_available(
+    context: &DecisionContext,
+    targets: &[(Entity, Vec3)],
+) -> Option<(Entity, f32)> {
+    targets
+        .iter()
+        .filter(|(entity, _)| context.job_board.is_available(*entity, context.villager))
+        .map(|(entity, target)| (*entity, context.position.distance(*target)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+pub fn score_walk_home(context: &DecisionContext) -> Option<ScoredAction> {
+    let (house, distance) = home_of(context)?;
+    if distance < AT_HOME_DISTANCE {
+        return None;
+    }
+
+    Some(ScoredAction {
+        action: IdleAction::WalkHome { house },
+        score: WALK_HOME_BASE_SCORE * context.traits.homeliness,
+    })
+}
+
+pub fn score_chop_tree(context: &DecisionContext) -> Option<ScoredAction> {
+    let (tree, distance) = nearest_available(context, context.trees)?;
+    // Chopping is pointless once all wood storage is full
+    let room = 1.0
+        - context
+            .storage_fill
+            .get(&ItemType::Wood)
+            .copied()
+            .unwrap_or(0.0);
+
+    Some(ScoredAction {
+        action: IdleAction::ChopTree { tree },
+        score: CHOP_TREE_BASE_SCORE
+            * context.job_board.priority(JobKind::Harvest)
+            * context.traits.woodcutting
+            * distance_consideration(distance)
+            * room,
+    })
+}
+
+pub fn score_haul_items(context: &DecisionContext) -> Option<ScoredAction> {
+    // The fuller the storage for an item, the less it's worth bringing more of it
+    context
+        .item_drops
+        .iter()
+        .filter(|(entity, _, _)| context.job_board.is_available(*entity, context.villager))
+        .map(|(item_drop, position, item_type)| {
+            let fill = context.storage_fill.get(item_type).copied().unwrap_or(1.0);
+            ScoredAction {
+                action: IdleAction::HaulItems {
+                    item_drop: *item_drop,
+                },
+                score: HAUL_ITEMS_BASE_SCORE
+                    * context.job_board.priority(JobKind::Haul)
+                    * context.traits.hauling
+                    * distance_consideration(context.position.distance(*position))
+                    * (1.0 - fill),
+            }
+        })
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+}
+
+pub fn score_deliver_items(context: &DecisionContext) -> Option<ScoredAction> {
+    if context.carried == 0 {
+        return None;
+    }
+    let (storage, _) = nearest(context.position, context.deliverable_storage)?;
+
+    Some(ScoredAction {
+        action: IdleAction::DeliverItems { storage },
+        score: DELIVER_ITEMS_BASE_SCORE * context.traits.hauling,
+    })
+}
+
+/// Nearest construction site the villager may claim, among those that do or don't still
+/// need wood.
+fn nearest_site(context: &DecisionContext, needs_wood: bool) -> Option<(Entity, Vec3, f32)> {
+    context
+        .construction_sites
+        .iter()
+        .filter(|(_, _, wood_needed)| (*wood_needed > 0) == needs_wood)
+        .filter(|(entity, _, _)| context.job_board.is_available(*entity, context.villager))
+        .map(|(entity, target, _)| (*entity, *target, context.position.distance(*target)))
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+}
+
+pub fn score_supply_site(context: &DecisionContext) -> Option<ScoredAction> {
+    let (site, site_position, _) = nearest_site(context, true)?;
+    // The whole trip, from here to the hut and on to the site
+    let (wood_hut, distance) = context
+        .stocked_wood_huts
+        .iter()
+        .map(|(entity, hut)| {
+            (
+                *entity,
+                context.position.distance(*hut) + hut.distance(site_position),
+            )
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    Some(ScoredAction {
+        action: IdleAction::SupplySite { site, wood_hut },
+        score: SUPPLY_SITE_BASE_SCORE
+            * context.job_board.priority(JobKind::Build)
+            * context.traits.hauling
+            * distance_consideration(distance),
+    })
+}
+
+pub fn score_build_site(context: &DecisionContext) -> Option<ScoredAction> {
+    let (site, _, distance) = nearest_site(context, false)?;
+
+    Some(ScoredAction {
+        action: IdleAction::BuildSite { site },
+        score: BUILD_SITE_BASE_SCORE
+            * context.job_board.priority(JobKind::Build)
+            * distance_consideration(distance),
+    })
+}
+
+fn score_need(context: &DecisionContext, need: Need) -> Option<(Entity, f32)> {
+    let value = context.needs.get(need);
+    if value >= NEED_SEEK_THRESHOLD {
+        return None;
+    }
+    let (house, _) = home_of(context)?;
+    let urgency = 1.0 - value;
+
+    Some((house, NEED_WEIGHT * urgency * urgency))
+}
+
+pub fn score_eat(context: &DecisionContext) -> Option<ScoredAction> {
+    let (house, score) = score_need(context, Need::Hunger)?;
+
+    Some(ScoredAction {
+        action: IdleAction::Eat { house },
+        score,
+    })
+}
+
+pub fn score_sleep(context: &DecisionContext) -> Option<ScoredAction> {
+    let (house, score) = score_need(context, Need::Energy)?;
+
+    Some(ScoredAction {
+        action: IdleAction::Sleep { house },
+        score,
+    })
+}
+
+/// Scores every candidate action and picks the best one with a positive score.
+pub fn decide(context: &DecisionContext) -> UtilityDecision {
+    let candidates = SCORERS
+        .iter()
+        .filter_map(|scorer| scorer(context))
+        .collect::<Vec<_>>();
+
+    let chosen = candidates
+        .iter()
+        .filter(|candidate| candidate.score > 0.0)
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+        .copied();
+
+    UtilityDecision { chosen, candidates }
+}
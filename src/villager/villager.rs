@@ -1,34 +1,74 @@
 const MOVEMENT_SPEED: f32 = 3.0;
 const HARVESTING_SPEED: f32 = 1.0;
+const FSM_HISTORY_LENGTH: usize = 16;
+pub const VILLAGER_SCENE: &str = "villager";
 
 ////////////////////////////////////////////////////////////////
 
 use bevy::prelude::*;
+use rand::Rng;
 
 use crate::assets::*;
-use crate::fsm::*;
+use crate::behavior::*;
+use crate::fsm::{components::*, machine::*};
+use crate::inventory::Inventory;
+use crate::navigation::{NavAgent, NavPath};
+use crate::villager::{needs::*, utility::*};
+use crate::world::ChunkAnchor;
 
 #[derive(Component)]
+#[require(
+    VillagerTraits,
+    UtilityDecision,
+    Needs,
+    Inventory,
+    ChunkAnchor,
+    NavPath,
+    NavAgent
+)]
 pub struct Villager {
     pub movement_speed: f32,
     pub harvesting_speed: f32,
 }
 
-pub fn spawn_villager(commands: &mut Commands, scene_assets: &SceneAssets, position: Vec3) {
+pub fn spawn_villager(
+    commands: &mut Commands,
+    scene_assets: &SceneAssets,
+    position: Vec3,
+    rng: &mut impl Rng,
+) -> Entity {
+    commands
+        .spawn((
+            SceneRoot(scene_assets.scene(VILLAGER_SCENE)),
+            scene_assets.transform(VILLAGER_SCENE, position),
+            Villager {
+                movement_speed: MOVEMENT_SPEED,
+                harvesting_speed: HARVESTING_SPEED,
+            },
+            VillagerTraits::random(rng),
+            FSM::start(FSMIdle),
+            FSMHistory::<VillagerState>::new(FSM_HISTORY_LENGTH),
+            Name::new("Villager"),
+        ))
+        .id()
+}
+/// Spawns a villager that follows a behavior tree instead of the FSM.
+pub fn spawn_villager_with_behavior(
+    commands: &mut Commands,
+    scene_assets: &SceneAssets,
+    position: Vec3,
+    behavior: Handle<BehaviorTreeAsset>,
+    rng: &mut impl Rng,
+) {
     commands.spawn((
-        SceneRoot(
-            scene_assets
-                .handles
-                .get(&SceneAssetType::Villager)
-                .unwrap()
-                .clone(),
-        ),
-        Transform::from_translation(position).with_scale(GLOBAL_SCALE_VEC),
+        SceneRoot(scene_assets.scene(VILLAGER_SCENE)),
+        scene_assets.transform(VILLAGER_SCENE, position),
         Villager {
             movement_speed: MOVEMENT_SPEED,
             harvesting_speed: HARVESTING_SPEED,
         },
-        FSMIdle,
-        Name::new("Villager"),
+        VillagerTraits::random(rng),
+        BehaviorTreeHandle(behavior),
+        Name::new("Villager (Hauler)"),
     ));
-}
\ No newline at end of file
+}
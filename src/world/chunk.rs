@@ -0,0 +1,210 @@
+// Side length of a square chunk, in world units
+pub const CHUNK_SIZE: f32 = 8.0;
+// Chunks this many chunks away from an anchor (or closer) are loaded
+const LOAD_RADIUS: i32 = 2;
+// Loaded chunks stay until they are this far from every anchor, so they don't flicker at the edge
+const UNLOAD_RADIUS: i32 = 3;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+
+use crate::world::{terrain::*, underworld::*};
+
+/// Position of a chunk on the chunk grid. Chunk (0, 0) spans x and z from 0 to `CHUNK_SIZE`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ChunkCoord(pub IVec2);
+
+impl ChunkCoord {
+    pub fn new(x: i32, z: i32) -> Self {
+        Self(IVec2::new(x, z))
+    }
+
+    /// The chunk a world position falls in. Height is ignored.
+    pub fn from_position(position: Vec3) -> Self {
+        Self::new(
+            (position.x / CHUNK_SIZE).floor() as i32,
+            (position.z / CHUNK_SIZE).floor() as i32,
+        )
+    }
+
+    /// Corner of the chunk with the lowest x and z.
+    pub fn origin(&self) -> Vec3 {
+        Vec3::new(self.0.x as f32, 0.0, self.0.y as f32) * CHUNK_SIZE
+    }
+
+    pub fn center(&self) -> Vec3 {
+        self.origin() + Vec3::new(CHUNK_SIZE, 0.0, CHUNK_SIZE) / 2.0
+    }
+
+    /// Distance in chunks, counting diagonal steps as one.
+    pub fn distance(&self, other: ChunkCoord) -> i32 {
+        let difference = (self.0 - other.0).abs();
+        difference.x.max(difference.y)
+    }
+
+    /// Uniformly random position within this chunk, at y = 0.0.
+    pub fn random_position(&self, rng: &mut impl Rng) -> Vec3 {
+        self.origin()
+            + Vec3::new(
+                rng.random::<f32>() * CHUNK_SIZE,
+                0.0,
+                rng.random::<f32>() * CHUNK_SIZE,
+            )
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Chunk {
+    pub coord: ChunkCoord,
+}
+
+/// Chunks are loaded around everything with this, e.g. the camera and villagers.
+#[derive(Component, Default)]
+pub struct ChunkAnchor;
+
+/// A chunk whose contents came from a save, so it must not be populated again.
+#[derive(Component, Default)]
+pub struct RestoredChunk;
+
+/// Despawned together with the chunk it stands in.
+#[derive(Component, Default)]
+pub struct ChunkLocal;
+
+/// Sent for every chunk that is unloaded, while what stands in it still exists.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ChunkUnloaded {
+    pub coord: ChunkCoord,
+}
+
+/// The chunk entity for every loaded chunk.
+#[derive(Resource, Default)]
+pub struct ChunkMap {
+    chunks: HashMap<ChunkCoord, Entity>,
+}
+
+impl ChunkMap {
+    pub fn get(&self, coord: ChunkCoord) -> Option<Entity> {
+        self.chunks.get(&coord).copied()
+    }
+
+    pub fn insert(&mut self, coord: ChunkCoord, chunk: Entity) {
+        self.chunks.insert(coord, chunk);
+    }
+
+    pub fn remove(&mut self, coord: ChunkCoord) -> Option<Entity> {
+        self.chunks.remove(&coord)
+    }
+
+    pub fn is_loaded(&self, coord: ChunkCoord) -> bool {
+        self.chunks.contains_key(&coord)
+    }
+
+    pub fn coords(&self) -> impl Iterator<Item = ChunkCoord> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+/// Shared by the ground of every chunk.
+#[derive(Resource)]
+pub struct ChunkAssets {
+    pub ground_material: Handle<StandardMaterial>,
+}
+
+pub fn load_chunk_assets(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(ChunkAssets {
+        ground_material: materials.add(Color::srgb_u8(50, 200, 50)),
+    });
+}
+
+pub fn spawn_chunk(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    chunk_assets: &ChunkAssets,
+    terrain: &Terrain,
+    coord: ChunkCoord,
+) -> Entity {
+    let half_size = CHUNK_SIZE / 2.0;
+    commands
+        .spawn((
+            Mesh3d(meshes.add(terrain.chunk_mesh(coord))),
+            MeshMaterial3d(chunk_assets.ground_material.clone()),
+            Transform::from_translation(coord.center()),
+            RigidBody::Fixed,
+            Chunk { coord },
+            Name::new(format!("Chunk ({}, {})", coord.0.x, coord.0.y)),
+        ))
+        .with_children(|this| {
+            this.spawn((terrain.chunk_collider(coord), Transform::IDENTITY));
+            this.spawn((
+                Collider::cuboid(half_size, 1.0, half_size),
+                Transform::from_xyz(0.0, -3.1, 0.0),
+                UnderworldDeleter,
+            ));
+        })
+        .id()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn stream_chunks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    chunk_assets: Res<ChunkAssets>,
+    terrain: Res<Terrain>,
+    mut chunk_map: ResMut<ChunkMap>,
+    anchors: Query<&Transform, With<ChunkAnchor>>,
+    chunk_locals: Query<(Entity, &Transform), With<ChunkLocal>>,
+    mut chunk_unloaded_events: EventWriter<ChunkUnloaded>,
+) {
+    let anchor_coords = anchors
+        .iter()
+        .map(|transform| ChunkCoord::from_position(transform.translation))
+        .collect::<Vec<_>>();
+
+    for anchor_coord in anchor_coords.iter() {
+        for x in -LOAD_RADIUS..=LOAD_RADIUS {
+            for z in -LOAD_RADIUS..=LOAD_RADIUS {
+                let coord = ChunkCoord(anchor_coord.0 + IVec2::new(x, z));
+                if !chunk_map.is_loaded(coord) {
+                    let chunk =
+                        spawn_chunk(&mut commands, &mut meshes, &chunk_assets, &terrain, coord);
+                    chunk_map.chunks.insert(coord, chunk);
+                }
+            }
+        }
+    }
+
+    let unloaded = chunk_map
+        .coords()
+        .filter(|coord| {
+            anchor_coords
+                .iter()
+                .all(|anchor_coord| coord.distance(*anchor_coord) > UNLOAD_RADIUS)
+        })
+        .collect::<Vec<_>>();
+    if unloaded.is_empty() {
+        return;
+    }
+
+    for coord in unloaded.iter() {
+        let chunk = chunk_map.chunks.remove(coord).unwrap();
+        commands.entity(chunk).despawn_recursive();
+        chunk_unloaded_events.send(ChunkUnloaded { coord: *coord });
+    }
+    for (entity, transform) in chunk_locals.iter() {
+        if unloaded.contains(&ChunkCoord::from_position(transform.translation)) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
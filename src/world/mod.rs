@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+
+use crate::plugins::SimulationSet;
+
+pub mod chunk;
+pub mod terrain;
+pub mod underworld;
+
+pub use chunk::*;
+pub use terrain::*;
+pub use underworld::*;
+
+pub struct WorldPlugin;
+
+impl Plugin for WorldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ChunkUnloaded>();
+        app.init_resource::<ChunkMap>();
+        app.init_resource::<Terrain>();
+        app.add_systems(PreStartup, load_chunk_assets);
+        app.add_systems(
+            Update,
+            (stream_chunks, delete_underworld).in_set(SimulationSet),
+        );
+    }
+}
@@ -0,0 +1,212 @@
+// Used when no seed is configured
+const DEFAULT_SEED: u32 = 1;
+// Highest point of the terrain, lowest is 0.0
+const HEIGHT_SCALE: f32 = 1.5;
+// Rough size of hills and valleys, in world units
+const HILL_SIZE: f32 = 14.0;
+// Rough size of forests and clearings, in world units
+const BIOME_SIZE: f32 = 24.0;
+// Layers of detail added on top of the hills
+const OCTAVES: u32 = 3;
+// Quads along each side of a chunk's ground mesh and collider
+pub const CHUNK_RESOLUTION: usize = 16;
+// Distance between the points a ray is checked at, small enough not to step over a hilltop
+const RAY_STEP: f32 = 0.1;
+// Halvings that narrow down where a ray crosses the ground, once it has
+const RAY_REFINEMENTS: u32 = 12;
+
+////////////////////////////////////////////////////////////////
+
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy_rapier3d::prelude::*;
+
+use crate::world::chunk::*;
+
+/// Noise-based terrain. The same seed always gives the same world.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Terrain {
+    pub seed: u32,
+    pub height_scale: f32,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+impl Terrain {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            height_scale: HEIGHT_SCALE,
+        }
+    }
+
+    /// Completely flat ground at y = 0.0.
+    pub fn flat() -> Self {
+        Self {
+            seed: DEFAULT_SEED,
+            height_scale: 0.0,
+        }
+    }
+
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        if self.height_scale == 0.0 {
+            return 0.0;
+        }
+        fractal_noise(self.seed, x / HILL_SIZE, z / HILL_SIZE) * self.height_scale
+    }
+
+    /// The same position, moved up or down onto the ground.
+    pub fn on_surface(&self, position: Vec3) -> Vec3 {
+        position.with_y(self.height_at(position.x, position.z))
+    }
+
+    /// Where a ray first hits the ground within `max_distance`, if it does.
+    pub fn cast_ray(&self, ray: Ray3d, max_distance: f32) -> Option<Vec3> {
+        let above_ground = |distance: f32| {
+            let point = ray.get_point(distance);
+            point.y > self.height_at(point.x, point.z)
+        };
+
+        let steps = (max_distance / RAY_STEP).ceil() as u32;
+        let mut before = 0.0;
+        for step in 1..=steps {
+            let distance = (step as f32 * RAY_STEP).min(max_distance);
+            if above_ground(distance) {
+                before = distance;
+                continue;
+            }
+
+            let mut after = distance;
+            for _ in 0..RAY_REFINEMENTS {
+                let middle = (before + after) / 2.0;
+                if above_ground(middle) {
+                    before = middle;
+                } else {
+                    after = middle;
+                }
+            }
+            return Some(self.on_surface(ray.get_point(after)));
+        }
+        None
+    }
+
+    /// How much the ground wants to be forest, from 0.0 (clearing) to 1.0 (dense forest).
+    pub fn forest_density(&self, x: f32, z: f32) -> f32 {
+        let noise = fractal_noise(
+            self.seed.wrapping_add(0x9e37_79b9),
+            x / BIOME_SIZE,
+            z / BIOME_SIZE,
+        );
+        // Stretch the noise so there are proper clearings and proper forests
+        ((noise - 0.35) * 2.5).clamp(0.0, 1.0)
+    }
+
+    fn normal_at(&self, x: f32, z: f32) -> Vec3 {
+        let step = CHUNK_SIZE / CHUNK_RESOLUTION as f32;
+        let dx = self.height_at(x + step, z) - self.height_at(x - step, z);
+        let dz = self.height_at(x, z + step) - self.height_at(x, z - step);
+        Vec3::new(-dx, 2.0 * step, -dz).normalize()
+    }
+
+    /// Ground mesh of a chunk, relative to the chunk's center.
+    pub fn chunk_mesh(&self, coord: ChunkCoord) -> Mesh {
+        let center = coord.center();
+        let step = CHUNK_SIZE / CHUNK_RESOLUTION as f32;
+        let side = CHUNK_RESOLUTION + 1;
+
+        let mut positions = Vec::with_capacity(side * side);
+        let mut normals = Vec::with_capacity(side * side);
+        let mut uvs = Vec::with_capacity(side * side);
+        for i in 0..side {
+            for j in 0..side {
+                let local_x = j as f32 * step - CHUNK_SIZE / 2.0;
+                let local_z = i as f32 * step - CHUNK_SIZE / 2.0;
+                let (x, z) = (center.x + local_x, center.z + local_z);
+                positions.push([local_x, self.height_at(x, z), local_z]);
+                normals.push(self.normal_at(x, z).to_array());
+                uvs.push([
+                    j as f32 / CHUNK_RESOLUTION as f32,
+                    i as f32 / CHUNK_RESOLUTION as f32,
+                ]);
+            }
+        }
+
+        let mut indices = Vec::with_capacity(CHUNK_RESOLUTION * CHUNK_RESOLUTION * 6);
+        for i in 0..CHUNK_RESOLUTION {
+            for j in 0..CHUNK_RESOLUTION {
+                let top_left = (i * side + j) as u32;
+                let top_right = top_left + 1;
+                let bottom_left = top_left + side as u32;
+                let bottom_right = bottom_left + 1;
+                indices.extend([top_left, bottom_left, top_right]);
+                indices.extend([top_right, bottom_left, bottom_right]);
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+
+    /// Heightfield collider matching `chunk_mesh`, relative to the chunk's center.
+    pub fn chunk_collider(&self, coord: ChunkCoord) -> Collider {
+        let origin = coord.origin();
+        let step = CHUNK_SIZE / CHUNK_RESOLUTION as f32;
+        let side = CHUNK_RESOLUTION + 1;
+
+        // Column-major, rows go along z and columns along x
+        let mut heights = Vec::with_capacity(side * side);
+        for j in 0..side {
+            for i in 0..side {
+                heights
+                    .push(self.height_at(origin.x + j as f32 * step, origin.z + i as f32 * step));
+            }
+        }
+        Collider::heightfield(heights, side, side, Vec3::new(CHUNK_SIZE, 1.0, CHUNK_SIZE))
+    }
+}
+
+fn hash(seed: u32, x: i32, z: i32) -> f32 {
+    let mut h = seed ^ (x as u32).wrapping_mul(0x27d4_eb2d) ^ (z as u32).wrapping_mul(0x1656_67b1);
+    h = (h ^ (h >> 15)).wrapping_mul(0x85eb_ca6b);
+    h = (h ^ (h >> 13)).wrapping_mul(0xc2b2_ae35);
+    h ^= h >> 16;
+    h as f32 / u32::MAX as f32
+}
+
+/// Smoothly interpolated random values on a grid, from 0.0 to 1.0.
+fn value_noise(seed: u32, x: f32, z: f32) -> f32 {
+    let (x0, z0) = (x.floor(), z.floor());
+    let (ix, iz) = (x0 as i32, z0 as i32);
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, tz) = (smooth(x - x0), smooth(z - z0));
+
+    let top = hash(seed, ix, iz).lerp(hash(seed, ix + 1, iz), tx);
+    let bottom = hash(seed, ix, iz + 1).lerp(hash(seed, ix + 1, iz + 1), tx);
+    top.lerp(bottom, tz)
+}
+
+/// Value noise with finer and fainter layers on top, still from 0.0 to 1.0.
+fn fractal_noise(seed: u32, x: f32, z: f32) -> f32 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max = 0.0;
+    for octave in 0..OCTAVES {
+        total += value_noise(seed.wrapping_add(octave), x * frequency, z * frequency) * amplitude;
+        max += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    total / max
+}
@@ -0,0 +1,37 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+/// A sensor under the ground. Anything that falls through the world and touches it is despawned.
+#[derive(Component)]
+#[require(
+    Sensor,
+    ActiveEvents(|| ActiveEvents::COLLISION_EVENTS),
+    ActiveCollisionTypes(|| ActiveCollisionTypes::DYNAMIC_STATIC)
+)]
+pub struct UnderworldDeleter;
+
+pub fn delete_underworld(
+    mut collision_events: EventReader<CollisionEvent>,
+    underworld_deleters: Query<Entity, With<UnderworldDeleter>>,
+    parent_query: Query<&Parent, With<Collider>>,
+    mut commands: Commands,
+) {
+    for event in collision_events.read() {
+        let CollisionEvent::Started(entity1, entity2, _) = *event else {
+            continue;
+        };
+        let to_despawn = if underworld_deleters.contains(entity1) {
+            entity2
+        } else if underworld_deleters.contains(entity2) {
+            entity1
+        } else {
+            continue;
+        };
+        if let Ok(parent) = parent_query.get(to_despawn) {
+            // Lesson: The entity is what has the collider. Sometimes this is a child of something else
+            commands.entity(parent.get()).despawn_recursive();
+        } else {
+            commands.entity(to_despawn).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+use village::assets::*;
+use village::plugins::HeadlessPlugins;
+use village::structure::registry::*;
+
+#[test]
+fn bundled_manifest_has_every_model_the_game_spawns() {
+    let scene_assets = SceneAssets::placeholder();
+
+    let structures = StructureRegistry::new();
+    for kind in StructureKind::ALL {
+        assert!(scene_assets.get(structures.get(kind).scene).is_some());
+    }
+    assert!(scene_assets.get("villager").is_some());
+    assert!(scene_assets.get("wood").is_some());
+    assert_eq!(
+        scene_assets.tagged("tree").collect::<Vec<_>>(),
+        ["tree_pine", "tree_round", "tree_dead"]
+            .map(SceneAssetId::new)
+            .iter()
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn manifest_entries_fall_back_to_defaults() {
+    let entries = parse_manifest(
+        br#"[
+            (id: "rock", file: "rock.glb"),
+            (
+                id: "birch",
+                file: "tree.glb",
+                scene: 3,
+                scale: 0.5,
+                tags: ["tree"],
+                collider: Some(Cuboid(half_extents: (0.3, 1.0, 0.4))),
+            ),
+        ]"#,
+    )
+    .unwrap();
+
+    assert_eq!(entries[0].scene, 0);
+    assert_eq!(entries[0].scale, GLOBAL_SCALE);
+    assert!(entries[0].tags.is_empty());
+    assert_eq!(entries[0].collider, None);
+    assert_eq!(entries[1].scene, 3);
+    assert_eq!(entries[1].collider.as_ref().unwrap().footprint(), 0.5);
+
+    let scene_assets = SceneAssets::from_manifest(&entries, [Handle::default(), Handle::default()]);
+    assert_eq!(
+        scene_assets.transform("birch", Vec3::X),
+        Transform::from_xyz(1.0, 0.0, 0.0).with_scale(Vec3::splat(0.5))
+    );
+    assert_eq!(
+        scene_assets.tagged("tree").collect::<Vec<_>>(),
+        [&SceneAssetId::new("birch")]
+    );
+}
+
+#[test]
+fn manifest_loads_through_the_asset_server() {
+    let mut app = App::new();
+    app.add_plugins(HeadlessPlugins)
+        .init_asset::<SceneManifest>()
+        .init_asset_loader::<SceneManifestLoader>();
+    let handle = app
+        .world()
+        .resource::<AssetServer>()
+        .load::<SceneManifest>(SCENE_MANIFEST_PATH);
+
+    let mut loaded = false;
+    for _ in 0..1000 {
+        app.update();
+        if app
+            .world()
+            .resource::<Assets<SceneManifest>>()
+            .contains(&handle)
+        {
+            loaded = true;
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    assert!(loaded, "scene manifest never loaded");
+
+    let manifests = app.world().resource::<Assets<SceneManifest>>();
+    let manifest = manifests.get(&handle).unwrap();
+    assert_eq!(manifest.entries, bundled_manifest());
+    assert_eq!(manifest.scenes.len(), manifest.entries.len());
+}
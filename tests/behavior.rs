@@ -0,0 +1,243 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Harness;
+use village::behavior::*;
+use village::inventory::Inventory;
+use village::item::*;
+use village::structure::registry::StructureKind;
+use village::villager::villager::Villager;
+
+use BTAction::*;
+use BTCondition::*;
+
+fn spawn_with_tree(harness: &mut Harness, position: Vec3, root: BTNode) -> Entity {
+    harness
+        .world_mut()
+        .spawn((
+            Transform::from_translation(position),
+            Villager {
+                movement_speed: 3.0,
+                harvesting_speed: 1.0,
+            },
+            BehaviorTree::new(root),
+        ))
+        .id()
+}
+
+fn tick(harness: &mut Harness, villager: Entity) -> BTStatus {
+    harness.app.update();
+    behavior_tree(harness, villager).last_status.unwrap()
+}
+
+fn behavior_tree(harness: &Harness, villager: Entity) -> &BehaviorTree {
+    harness.world().get::<BehaviorTree>(villager).unwrap()
+}
+
+fn give_wood(harness: &mut Harness, villager: Entity) {
+    harness
+        .world_mut()
+        .get_mut::<Inventory>(villager)
+        .unwrap()
+        .add(ItemType::Wood, 1);
+}
+
+fn position(harness: &Harness, entity: Entity) -> Vec3 {
+    harness
+        .world()
+        .get::<Transform>(entity)
+        .unwrap()
+        .translation
+}
+
+fn walk_to_house() -> BTNode {
+    BTNode::sequence([
+        BTNode::Action(FindHouse),
+        BTNode::Action(WalkToTarget { proximity: 0.2 }),
+    ])
+}
+
+#[test]
+fn sequence_runs_children_in_order_until_one_is_running() {
+    let mut harness = Harness::new();
+    let house = harness.spawn_structure(StructureKind::House, Vec3::new(4.0, 0.0, 0.0));
+    let villager = spawn_with_tree(&mut harness, Vec3::ZERO, walk_to_house());
+
+    assert_eq!(tick(&mut harness, villager), BTStatus::Running);
+    assert_eq!(
+        behavior_tree(&harness, villager).blackboard.target,
+        Some(house)
+    );
+    assert!(position(&harness, villager).x > 0.0);
+}
+
+#[test]
+fn sequence_stops_at_the_first_failure() {
+    let mut harness = Harness::new();
+    harness.spawn_structure(StructureKind::House, Vec3::new(4.0, 0.0, 0.0));
+    let villager = spawn_with_tree(
+        &mut harness,
+        Vec3::ZERO,
+        BTNode::sequence([BTNode::Condition(HoldingWood), walk_to_house()]),
+    );
+
+    assert_eq!(tick(&mut harness, villager), BTStatus::Failure);
+    assert_eq!(position(&harness, villager), Vec3::ZERO);
+}
+
+#[test]
+fn selector_stops_at_the_first_success() {
+    let mut harness = Harness::new();
+    harness.spawn_structure(StructureKind::House, Vec3::new(4.0, 0.0, 0.0));
+    let villager = spawn_with_tree(
+        &mut harness,
+        Vec3::ZERO,
+        BTNode::selector([BTNode::Condition(HoldingWood), walk_to_house()]),
+    );
+    give_wood(&mut harness, villager);
+
+    assert_eq!(tick(&mut harness, villager), BTStatus::Success);
+    assert_eq!(position(&harness, villager), Vec3::ZERO);
+}
+
+#[test]
+fn selector_falls_through_failures() {
+    let mut harness = Harness::new();
+    let house = harness.spawn_structure(StructureKind::House, Vec3::new(4.0, 0.0, 0.0));
+    let villager = spawn_with_tree(
+        &mut harness,
+        Vec3::ZERO,
+        BTNode::selector([BTNode::Condition(HoldingWood), walk_to_house()]),
+    );
+
+    assert_eq!(tick(&mut harness, villager), BTStatus::Running);
+    assert_eq!(
+        behavior_tree(&harness, villager).blackboard.target,
+        Some(house)
+    );
+}
+
+#[test]
+fn running_child_is_resumed_instead_of_restarted() {
+    let mut harness = Harness::new();
+    let house = harness.spawn_structure(StructureKind::House, Vec3::new(4.0, 0.0, 0.0));
+    let villager = spawn_with_tree(
+        &mut harness,
+        Vec3::ZERO,
+        BTNode::sequence([
+            BTNode::decorate(BTDecorator::Invert, BTNode::Condition(HoldingWood)),
+            walk_to_house(),
+        ]),
+    );
+    assert_eq!(tick(&mut harness, villager), BTStatus::Running);
+
+    // Neither the condition nor the search run again while the walk is running
+    give_wood(&mut harness, villager);
+    harness.spawn_structure(StructureKind::House, Vec3::new(0.0, 0.0, 1.5));
+    assert_eq!(tick(&mut harness, villager), BTStatus::Running);
+    assert_eq!(
+        behavior_tree(&harness, villager).blackboard.target,
+        Some(house)
+    );
+
+    let arrived = harness.run_until(5.0, |world| {
+        world.get::<BehaviorTree>(villager).unwrap().last_status == Some(BTStatus::Success)
+    });
+    assert!(arrived.is_some(), "walk never finished");
+    assert!(position(&harness, villager).distance(position(&harness, house)) < 0.2);
+}
+
+#[test]
+fn repeat_runs_until_enough_successes() {
+    let mut harness = Harness::new();
+    harness.spawn_structure(StructureKind::House, Vec3::new(4.0, 0.0, 0.0));
+    let villager = spawn_with_tree(
+        &mut harness,
+        Vec3::ZERO,
+        BTNode::decorate(BTDecorator::Repeat(3), BTNode::Action(FindHouse)),
+    );
+
+    assert_eq!(tick(&mut harness, villager), BTStatus::Running);
+    assert_eq!(tick(&mut harness, villager), BTStatus::Running);
+    assert_eq!(tick(&mut harness, villager), BTStatus::Success);
+    assert_eq!(tick(&mut harness, villager), BTStatus::Running);
+}
+
+fn load_hauler(harness: &mut Harness) -> Handle<BehaviorTreeAsset> {
+    let handle = harness
+        .world()
+        .resource::<AssetServer>()
+        .load::<BehaviorTreeAsset>("behaviors/hauler.bt.ron");
+
+    for _ in 0..1000 {
+        harness.app.update();
+        if harness
+            .world()
+            .resource::<Assets<BehaviorTreeAsset>>()
+            .contains(&handle)
+        {
+            return handle;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    panic!("hauler behavior tree never loaded");
+}
+
+#[test]
+fn hauler_tree_loads_from_ron() {
+    let mut harness = Harness::new();
+    let handle = load_hauler(&mut harness);
+
+    let behavior_trees = harness.world().resource::<Assets<BehaviorTreeAsset>>();
+    assert_eq!(
+        behavior_trees.get(&handle).unwrap().root,
+        BTNode::selector([
+            BTNode::sequence([
+                BTNode::Condition(HoldingWood),
+                BTNode::Action(FindWoodHut),
+                BTNode::Action(WalkToTarget { proximity: 0.2 }),
+                BTNode::Action(DepositWood),
+            ]),
+            BTNode::sequence([
+                BTNode::Action(FindWood),
+                BTNode::Action(WalkToTarget { proximity: 0.2 }),
+                BTNode::Action(PickUpWood),
+            ]),
+        ])
+    );
+}
+
+#[test]
+fn hauler_tree_hauls_wood_to_the_hut() {
+    let mut harness = Harness::new();
+    let handle = load_hauler(&mut harness);
+    let wood_hut = harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 0.0));
+    harness.spawn_wood_drop(Vec3::new(2.0, 0.0, 1.0), 3);
+    let villager = harness
+        .world_mut()
+        .spawn((
+            Transform::default(),
+            Villager {
+                movement_speed: 3.0,
+                harvesting_speed: 1.0,
+            },
+            BehaviorTreeHandle(handle),
+        ))
+        .id();
+
+    harness.app.update();
+    assert!(
+        harness.world().get::<BehaviorTree>(villager).is_some(),
+        "loaded tree was never attached"
+    );
+
+    let hauled = harness.run_until(20.0, |world| {
+        world
+            .get::<BehaviorTree>(villager)
+            .is_some_and(|tree| tree.last_status == Some(BTStatus::Success))
+            && world.get::<Inventory>(villager).unwrap().is_empty()
+    });
+    assert!(hauled.is_some(), "wood was never delivered");
+    assert_eq!(harness.stored(wood_hut), 3);
+}
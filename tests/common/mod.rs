@@ -0,0 +1,279 @@
+//! A minimal simulation app for integration tests: the real simulation plugins on flat
+//! terrain, with no trees growing on their own unless asked for and time advancing a fixed
+//! step per frame.
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use bevy::app::PluginGroupBuilder;
+use bevy::ecs::world::CommandQueue;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+
+use village::assets::*;
+use village::fsm::components::*;
+use village::fsm::machine::*;
+use village::harvestable::{tree::*, ForestPlugin};
+use village::inventory::Inventory;
+use village::item::*;
+use village::item_drop::*;
+use village::plugins::{HeadlessPlugins, SimulationPlugins};
+use village::random::SimulationRng;
+use village::structure::{construction::*, registry::*, stockpile::*};
+use village::villager::villager::*;
+use village::world::Terrain;
+
+pub const TIMESTEP: f32 = 0.05;
+
+pub struct Harness {
+    pub app: App,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Self::build(
+            SimulationPlugins { seed: 1 }
+                .build()
+                .disable::<ForestPlugin>(),
+        )
+    }
+
+    /// Like `new`, but with the forest growing trees on every chunk that loads.
+    pub fn with_forest() -> Self {
+        Self::build(SimulationPlugins { seed: 1 }.build())
+    }
+
+    fn build(simulation: PluginGroupBuilder) -> Self {
+        let mut app = App::new();
+        app.add_plugins((HeadlessPlugins, simulation))
+            .insert_resource(Terrain::flat())
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                TIMESTEP,
+            )));
+        // Runs startup, so the item registry exists before anything is spawned
+        app.update();
+        Self { app }
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    /// Spawns through the game's own spawn functions, with their usual resources.
+    pub fn spawn(
+        &mut self,
+        spawn: impl FnOnce(&mut Commands, &SceneAssets, &ItemRegistry, &mut SimulationRng) -> Entity,
+    ) -> Entity {
+        let world = self.app.world_mut();
+        let mut queue = CommandQueue::default();
+        let entity = world.resource_scope(|world, mut rng: Mut<SimulationRng>| {
+            let mut commands = Commands::new(&mut queue, world);
+            spawn(
+                &mut commands,
+                world.resource::<SceneAssets>(),
+                world.resource::<ItemRegistry>(),
+                &mut rng,
+            )
+        });
+        queue.apply(world);
+        entity
+    }
+
+    pub fn spawn_villager(&mut self, position: Vec3) -> Entity {
+        self.spawn(|commands, scene_assets, _, rng| {
+            spawn_villager(commands, scene_assets, position, rng.stream("harness"))
+        })
+    }
+
+    /// Spawns a villager that starts out in `state` instead of idle.
+    pub fn spawn_villager_in<T: FSMState<Id = VillagerState>>(
+        &mut self,
+        position: Vec3,
+        state: T,
+    ) -> Entity {
+        let villager = self.spawn_villager(position);
+        self.world_mut()
+            .entity_mut(villager)
+            .remove::<FSMIdle>()
+            .insert(FSM::start(state));
+        villager
+    }
+
+    /// Puts items straight into the villager's inventory.
+    pub fn give(&mut self, villager: Entity, item_type: ItemType, count: u32) {
+        self.world_mut()
+            .get_mut::<Inventory>(villager)
+            .unwrap()
+            .add(item_type, count);
+    }
+
+    pub fn spawn_tree(&mut self, position: Vec3) -> Entity {
+        self.spawn(|commands, scene_assets, _, _| {
+            spawn_tree_of_kind(
+                commands,
+                scene_assets,
+                SceneAssetId::new("tree_pine"),
+                position,
+            )
+        })
+    }
+
+    pub fn spawn_structure(&mut self, kind: StructureKind, position: Vec3) -> Entity {
+        let world = self.app.world_mut();
+        world.resource_scope(|world, structures: Mut<StructureRegistry>| {
+            let mut queue = CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, world);
+            let entity = spawn_structure(
+                &mut commands,
+                &structures,
+                world.resource::<ItemRegistry>(),
+                world.resource::<SceneAssets>(),
+                kind,
+                position,
+            );
+            queue.apply(world);
+            entity
+        })
+    }
+
+    pub fn spawn_construction_site(&mut self, kind: StructureKind, position: Vec3) -> Entity {
+        let world = self.app.world_mut();
+        world.resource_scope(|world, structures: Mut<StructureRegistry>| {
+            let mut queue = CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, world);
+            let entity = spawn_construction_site(&mut commands, &structures, kind, position);
+            queue.apply(world);
+            entity
+        })
+    }
+
+    pub fn spawn_wood_hut(&mut self, position: Vec3) -> Entity {
+        self.spawn_structure(StructureKind::WoodHut, position)
+    }
+
+    /// A wood hut made over to hold `item_type`, for storage no structure comes with yet.
+    pub fn spawn_storage(&mut self, position: Vec3, item_type: ItemType, capacity: u32) -> Entity {
+        let storage = self.spawn_wood_hut(position);
+        let stockpile = self.stockpile(storage);
+        *self.world_mut().get_mut::<ItemStack>(stockpile).unwrap() = ItemStack::new(item_type, 0);
+        self.world_mut()
+            .get_mut::<Stockpile>(stockpile)
+            .unwrap()
+            .capacity = capacity;
+        storage
+    }
+
+    pub fn spawn_item_drop(&mut self, position: Vec3, item_stack: ItemStack) -> Entity {
+        self.spawn(|commands, scene_assets, registry, _| {
+            place_item_drop(
+                commands,
+                registry,
+                scene_assets,
+                item_stack,
+                Transform::from_translation(position),
+            )
+        })
+    }
+
+    pub fn spawn_wood_drop(&mut self, position: Vec3, count: u32) -> Entity {
+        self.spawn_item_drop(position, ItemStack::new(ItemType::Wood, count))
+    }
+
+    pub fn advance(&mut self, seconds: f32) {
+        for _ in 0..(seconds / TIMESTEP).ceil() as u32 {
+            self.app.update();
+        }
+    }
+
+    /// Steps until `condition` holds, returning how long that took, or `None` if it
+    /// didn't happen within `seconds`.
+    pub fn run_until(
+        &mut self,
+        seconds: f32,
+        mut condition: impl FnMut(&mut World) -> bool,
+    ) -> Option<f32> {
+        let frames = (seconds / TIMESTEP).ceil() as u32;
+        for frame in 1..=frames {
+            self.app.update();
+            if condition(self.app.world_mut()) {
+                return Some(frame as f32 * TIMESTEP);
+            }
+        }
+        None
+    }
+
+    pub fn state(&self, villager: Entity) -> VillagerState {
+        self.world()
+            .get::<FSM<VillagerState>>(villager)
+            .unwrap()
+            .current()
+    }
+
+    /// Panics unless the villager is in `state` at some frame within `seconds`.
+    pub fn assert_reaches_state(&mut self, villager: Entity, state: VillagerState, seconds: f32) {
+        let reached = self.run_until(seconds, |world| {
+            world
+                .get::<FSM<VillagerState>>(villager)
+                .is_some_and(|fsm| fsm.current() == state)
+        });
+        assert!(
+            reached.is_some(),
+            "villager didn't reach {:?} within {}s, it is {:?}",
+            state,
+            seconds,
+            self.state(villager)
+        );
+    }
+
+    pub fn stockpile(&self, building: Entity) -> Entity {
+        self.world()
+            .get::<Children>(building)
+            .and_then(stockpile_of)
+            .expect("building has no stockpile")
+    }
+
+    /// How much the hut holds right now.
+    pub fn stored(&self, wood_hut: Entity) -> u32 {
+        let stockpile = self.stockpile(wood_hut);
+        self.world().get::<ItemStack>(stockpile).unwrap().count
+    }
+
+    /// Fills the hut with `count` wood, as if it had been hauled there.
+    pub fn stock(&mut self, wood_hut: Entity, count: u32) {
+        let stockpile = self.stockpile(wood_hut);
+        self.world_mut()
+            .get_mut::<ItemStack>(stockpile)
+            .unwrap()
+            .count = count;
+    }
+
+    /// Events sent during the last frame.
+    pub fn events<E: Event + Clone>(&self) -> Vec<E> {
+        self.world()
+            .resource::<Events<E>>()
+            .iter_current_update_events()
+            .cloned()
+            .collect()
+    }
+
+    pub fn count<F: bevy::ecs::query::QueryFilter>(&mut self) -> usize {
+        let world = self.app.world_mut();
+        world.query_filtered::<(), F>().iter(world).count()
+    }
+
+    pub fn count_structures(&mut self, kind: StructureKind) -> usize {
+        count_structures(self.app.world_mut(), kind)
+    }
+}
+
+pub fn count_structures(world: &mut World, kind: StructureKind) -> usize {
+    world
+        .query::<&Structure>()
+        .iter(world)
+        .filter(|structure| structure.kind == kind)
+        .count()
+}
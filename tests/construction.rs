@@ -0,0 +1,121 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::{count_structures, Harness};
+use village::fsm::components::*;
+use village::navigation::NavObstacle;
+use village::structure::construction::*;
+use village::structure::housing::Housing;
+use village::structure::registry::*;
+use village::structure::stockpile::*;
+
+fn place(harness: &mut Harness, kind: StructureKind, position: Vec3) {
+    harness
+        .world_mut()
+        .send_event(PlaceStructure { kind, position });
+}
+
+#[test]
+fn sites_are_not_placed_on_top_of_structures_or_each_other() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::ZERO);
+    harness.spawn_tree(Vec3::new(0.0, 0.0, 4.0));
+
+    place(&mut harness, StructureKind::House, Vec3::new(0.5, 0.0, 0.0));
+    place(&mut harness, StructureKind::House, Vec3::new(0.0, 0.0, 4.3));
+    place(&mut harness, StructureKind::House, Vec3::new(4.0, 0.0, 0.0));
+    place(
+        &mut harness,
+        StructureKind::WoodHut,
+        Vec3::new(4.5, 0.0, 0.0),
+    );
+    harness.advance(0.1);
+
+    assert_eq!(harness.count::<With<ConstructionSite>>(), 1);
+    let world = harness.world_mut();
+    let (transform, site) = world
+        .query::<(&Transform, &ConstructionSite)>()
+        .single(world);
+    assert_eq!(site.kind, StructureKind::House);
+    assert_eq!(transform.translation, Vec3::new(4.0, 0.0, 0.0));
+}
+
+#[test]
+fn villager_supplies_and_builds_a_wood_hut() {
+    let mut harness = Harness::new();
+    let wood_hut = harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 0.0));
+    harness.stock(wood_hut, 20);
+    let villager = harness.spawn_villager(Vec3::ZERO);
+    place(
+        &mut harness,
+        StructureKind::WoodHut,
+        Vec3::new(3.0, 0.0, 0.0),
+    );
+
+    harness.assert_reaches_state(villager, VillagerState::Fetching, 1.0);
+    harness.assert_reaches_state(villager, VillagerState::Supplying, 10.0);
+    harness.assert_reaches_state(villager, VillagerState::Building, 10.0);
+
+    let built = harness.run_until(30.0, |world| {
+        count_structures(world, StructureKind::WoodHut) == 2
+    });
+    assert!(built.is_some(), "the wood hut was never finished");
+    harness.advance(0.1);
+    assert_eq!(harness.count::<With<ConstructionSite>>(), 0);
+    let cost = harness
+        .world()
+        .resource::<StructureRegistry>()
+        .get(StructureKind::WoodHut)
+        .wood_cost;
+    assert_eq!(harness.stored(wood_hut), 20 - cost);
+    assert_eq!(harness.state(villager), VillagerState::Idle);
+}
+
+#[test]
+fn site_waits_for_wood_before_building_starts() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 0.0));
+    let villager = harness.spawn_villager(Vec3::ZERO);
+    place(&mut harness, StructureKind::House, Vec3::new(3.0, 0.0, 0.0));
+
+    harness.advance(5.0);
+    assert_eq!(harness.state(villager), VillagerState::Idle);
+    assert_eq!(harness.count_structures(StructureKind::House), 0);
+    let world = harness.world_mut();
+    let site = world.query::<&ConstructionSite>().single(world);
+    assert_eq!(site.delivered, 0);
+    assert_eq!(site.progress, 0.0);
+}
+
+#[test]
+fn structures_are_spawned_from_their_definitions() {
+    let mut harness = Harness::new();
+    for (index, kind) in StructureKind::ALL.into_iter().enumerate() {
+        let structure = harness.spawn_structure(kind, Vec3::new(index as f32 * 3.0, 0.0, 0.0));
+
+        let world = harness.world();
+        let definition = world.resource::<StructureRegistry>().get(kind);
+        assert_eq!(world.get::<Structure>(structure).unwrap().kind, kind);
+        assert_eq!(
+            world.get::<NavObstacle>(structure).unwrap().radius,
+            definition.footprint
+        );
+        let stockpile = world
+            .get::<Children>(structure)
+            .and_then(stockpile_of)
+            .and_then(|stockpile| world.get::<Stockpile>(stockpile));
+        assert_eq!(
+            stockpile.map(|stockpile| stockpile.capacity),
+            definition.storage.as_ref().map(|storage| storage.capacity)
+        );
+        assert_eq!(
+            world
+                .get::<Housing>(structure)
+                .map_or(0, |housing| housing.capacity),
+            definition.housing
+        );
+    }
+    assert_eq!(harness.count_structures(StructureKind::House), 1);
+    assert_eq!(harness.count_structures(StructureKind::WoodHut), 1);
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::CollisionEvent;
+use rand::Rng;
+
+use village::assets::*;
+use village::harvestable::tree::*;
+use village::random::*;
+use village::world::*;
+
+fn forest_app(seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<Assets<Mesh>>()
+        .init_resource::<Assets<StandardMaterial>>()
+        .add_event::<CollisionEvent>()
+        .add_plugins((WorldPlugin, RandomPlugin))
+        .insert_resource(SimulationRng::new(seed))
+        .insert_resource(Terrain::new(seed as u32))
+        .init_resource::<UnloadedTrees>()
+        .add_systems(Update, populate_new_chunks.after(stream_chunks));
+
+    app.insert_resource(SceneAssets::placeholder());
+    app.world_mut().spawn((Transform::default(), ChunkAnchor));
+    app
+}
+
+fn tree_positions(app: &mut App) -> Vec<Vec3> {
+    app.update();
+    app.update();
+    app.world_mut()
+        .query_filtered::<&Transform, With<Tree>>()
+        .iter(app.world())
+        .map(|transform| transform.translation)
+        .collect()
+}
+
+#[test]
+fn streams_are_reproducible_and_independent() {
+    let mut rng = SimulationRng::new(42);
+    let mut same = SimulationRng::new(42);
+
+    let rolls = (0..4)
+        .map(|_| rng.stream("a").random::<u32>())
+        .collect::<Vec<_>>();
+    // Drawing from another stream first doesn't change what "a" rolls
+    same.stream("b").random::<u32>();
+    let same_rolls = (0..4)
+        .map(|_| same.stream("a").random::<u32>())
+        .collect::<Vec<_>>();
+    assert_eq!(rolls, same_rolls);
+
+    let other_rolls = (0..4)
+        .map(|_| rng.stream("b").random::<u32>())
+        .collect::<Vec<_>>();
+    assert_ne!(rolls, other_rolls);
+}
+
+#[test]
+fn same_seed_grows_the_same_forest() {
+    let forest = tree_positions(&mut forest_app(9));
+    assert!(!forest.is_empty());
+    assert_eq!(forest, tree_positions(&mut forest_app(9)));
+    assert_ne!(forest, tree_positions(&mut forest_app(10)));
+}
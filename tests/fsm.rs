@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+
+use village::fsm::components::*;
+use village::fsm::machine::*;
+use village::fsm::transitions::*;
+use village::fsm::villager_fsm_definition;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum DoorState {
+    Open,
+    Closed,
+    Locked,
+}
+
+impl FSMStateId for DoorState {}
+
+#[derive(Component, Debug)]
+struct Open;
+
+#[derive(Component, Debug)]
+struct Closed;
+
+#[derive(Component, Debug)]
+struct Locked;
+
+impl FSMState for Open {
+    type Id = DoorState;
+    const ID: DoorState = DoorState::Open;
+}
+
+impl FSMState for Closed {
+    type Id = DoorState;
+    const ID: DoorState = DoorState::Closed;
+}
+
+impl FSMState for Locked {
+    type Id = DoorState;
+    const ID: DoorState = DoorState::Locked;
+}
+
+fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_fsm(
+        FSMDefinition::new()
+            .state::<Open>()
+            .state::<Closed>()
+            .state::<Locked>()
+            .transition(DoorState::Open, DoorState::Closed)
+            .transitions(DoorState::Closed, &[DoorState::Open, DoorState::Locked])
+            .transition(DoorState::Locked, DoorState::Closed),
+    );
+    app.update();
+    app
+}
+
+fn spawn_door(app: &mut App) -> Entity {
+    app.world_mut().spawn(FSM::start(Open)).id()
+}
+
+fn transition<FSMFrom: FSMState<Id = DoorState>>(
+    app: &mut App,
+    door: Entity,
+    to: impl FSMState<Id = DoorState>,
+) {
+    let world = app.world_mut();
+    fsm_transition_to::<FSMFrom>(&mut world.commands(), door, to, "test");
+    world.flush();
+}
+
+fn rejections(app: &App) -> Vec<(Entity, FSMTransitionError<DoorState>)> {
+    app.world()
+        .resource::<Events<FSMTransitionRejected<DoorState>>>()
+        .iter_current_update_events()
+        .map(|event| (event.entity, event.error.clone()))
+        .collect()
+}
+
+fn states_present(app: &App, door: Entity) -> usize {
+    let world = app.world();
+    [
+        world.get::<Open>(door).is_some(),
+        world.get::<Closed>(door).is_some(),
+        world.get::<Locked>(door).is_some(),
+    ]
+    .into_iter()
+    .filter(|present| *present)
+    .count()
+}
+
+fn current(app: &App, door: Entity) -> DoorState {
+    app.world().get::<FSM<DoorState>>(door).unwrap().current()
+}
+
+#[test]
+fn allowed_transition_swaps_the_state_component() {
+    let mut app = test_app();
+    let door = spawn_door(&mut app);
+
+    transition::<Open>(&mut app, door, Closed);
+
+    assert_eq!(current(&app, door), DoorState::Closed);
+    assert!(app.world().get::<Closed>(door).is_some());
+    assert!(app.world().get::<Open>(door).is_none());
+    assert!(rejections(&app).is_empty());
+}
+
+#[test]
+fn undeclared_transition_is_rejected() {
+    let mut app = test_app();
+    let door = spawn_door(&mut app);
+
+    transition::<Open>(&mut app, door, Locked);
+
+    assert_eq!(current(&app, door), DoorState::Open);
+    assert!(app.world().get::<Open>(door).is_some());
+    assert!(app.world().get::<Locked>(door).is_none());
+    assert_eq!(
+        rejections(&app),
+        vec![(
+            door,
+            FSMTransitionError::NotAllowed {
+                from: DoorState::Open,
+                to: DoorState::Locked,
+            }
+        )]
+    );
+}
+
+#[test]
+fn transition_from_the_wrong_state_is_rejected() {
+    let mut app = test_app();
+    let door = spawn_door(&mut app);
+
+    transition::<Closed>(&mut app, door, Locked);
+
+    assert_eq!(current(&app, door), DoorState::Open);
+    assert_eq!(
+        rejections(&app),
+        vec![(
+            door,
+            FSMTransitionError::NotInState {
+                expected: DoorState::Closed,
+                actual: DoorState::Open,
+            }
+        )]
+    );
+}
+
+#[test]
+fn transition_without_an_fsm_is_rejected() {
+    let mut app = test_app();
+    let door = app.world_mut().spawn(Open).id();
+
+    transition::<Open>(&mut app, door, Closed);
+
+    assert!(app.world().get::<Closed>(door).is_none());
+    assert_eq!(
+        rejections(&app),
+        vec![(door, FSMTransitionError::MissingFSM)]
+    );
+}
+
+#[test]
+fn agent_never_holds_two_states() {
+    let mut app = test_app();
+    let door = spawn_door(&mut app);
+
+    transition::<Open>(&mut app, door, Closed);
+    assert_eq!(states_present(&app, door), 1);
+    transition::<Closed>(&mut app, door, Locked);
+    assert_eq!(states_present(&app, door), 1);
+    // Rejected, so nothing may be left behind either
+    transition::<Locked>(&mut app, door, Open);
+    assert_eq!(states_present(&app, door), 1);
+    transition::<Locked>(&mut app, door, Closed);
+    assert_eq!(states_present(&app, door), 1);
+
+    assert_eq!(current(&app, door), DoorState::Closed);
+}
+
+#[test]
+fn stray_state_component_is_removed() {
+    let mut app = test_app();
+    let door = spawn_door(&mut app);
+
+    app.world_mut().entity_mut(door).insert(Locked);
+    app.update();
+
+    assert_eq!(states_present(&app, door), 1);
+    assert!(app.world().get::<Open>(door).is_some());
+}
+
+#[test]
+fn villager_cannot_skip_from_idle_to_harvesting() {
+    let definition = villager_fsm_definition();
+
+    assert!(definition.is_allowed(VillagerState::Idle, VillagerState::WalkingToHarvest));
+    assert!(definition.is_allowed(VillagerState::WalkingToHarvest, VillagerState::Harvesting));
+    assert!(!definition.is_allowed(VillagerState::Idle, VillagerState::Harvesting));
+    assert!(!definition.is_allowed(VillagerState::BringingTo, VillagerState::PickingUp));
+}
@@ -0,0 +1,182 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use bevy::utils::HashMap;
+
+use village::goap::*;
+
+use GoapAction::*;
+
+fn cost(plan: &[GoapAction]) -> u32 {
+    plan.iter().map(GoapAction::cost).sum()
+}
+
+/// Cheapest cost to reach the goal, by plain uniform-cost search without any heuristic.
+fn cheapest(start: GoapState, goal: GoapGoal) -> Option<u32> {
+    let mut open = BinaryHeap::from([Reverse((0, 0))]);
+    let mut states = vec![start];
+    let mut best: HashMap<GoapState, u32> = HashMap::from_iter([(start, 0)]);
+
+    while let Some(Reverse((cost, index))) = open.pop() {
+        let state = states[index];
+        if goal.is_satisfied(&state) {
+            return Some(cost);
+        }
+        if best.get(&state).is_some_and(|best| *best < cost) {
+            continue;
+        }
+        for action in GOAP_ACTIONS
+            .iter()
+            .filter(|action| action.is_possible(&state))
+        {
+            let next = action.apply(&state);
+            let next_cost = cost + action.cost();
+            if best.get(&next).is_none_or(|best| next_cost < *best) {
+                best.insert(next, next_cost);
+                states.push(next);
+                open.push(Reverse((next_cost, states.len() - 1)));
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn satisfied_goal_needs_no_plan() {
+    let state = GoapState {
+        hut_wood: 5,
+        ..Default::default()
+    };
+
+    assert_eq!(plan(state, GoapGoal::HutWoodAtLeast(5)), Some(vec![]));
+}
+
+#[test]
+fn carried_wood_is_brought_straight_to_the_hut() {
+    let state = GoapState {
+        has_wood: true,
+        wood_drops: 2,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        plan(state, GoapGoal::HutWoodAtLeast(3)),
+        Some(vec![BringWoodToHut])
+    );
+}
+
+#[test]
+fn wood_on_the_ground_is_hauled_before_trees_are_chopped() {
+    let state = GoapState {
+        wood_drops: 1,
+        trees_available: 3,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        plan(state, GoapGoal::HutWoodAtLeast(3)),
+        Some(vec![PickUpWood, BringWoodToHut])
+    );
+}
+
+#[test]
+fn trees_are_chopped_when_nothing_is_on_the_ground() {
+    let state = GoapState {
+        trees_available: 1,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        plan(state, GoapGoal::HutWoodAtLeast(3)),
+        Some(vec![WalkToTree, ChopTree, PickUpWood, BringWoodToHut])
+    );
+}
+
+#[test]
+fn unreachable_goal_gets_as_close_as_it_can() {
+    let state = GoapState {
+        wood_drops: 1,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        plan(state, GoapGoal::HutWoodAtLeast(9)),
+        Some(vec![PickUpWood, BringWoodToHut])
+    );
+    assert_eq!(
+        plan(GoapState::default(), GoapGoal::HutWoodAtLeast(9)),
+        None
+    );
+}
+
+#[test]
+fn walking_home_only_when_away() {
+    let away = GoapState::default();
+    let home = GoapState {
+        near_house: true,
+        ..Default::default()
+    };
+
+    assert_eq!(plan(away, GoapGoal::AtHome), Some(vec![WalkHome]));
+    assert_eq!(plan(home, GoapGoal::AtHome), Some(vec![]));
+}
+
+#[test]
+fn plans_are_as_cheap_as_possible() {
+    for has_wood in [false, true] {
+        for near_tree in [false, true] {
+            for trees_available in 0..=3 {
+                for wood_drops in 0..=3 {
+                    for target in [3, 6, 9] {
+                        let state = GoapState {
+                            has_wood,
+                            near_tree,
+                            trees_available,
+                            wood_drops,
+                            ..Default::default()
+                        };
+                        let goal = GoapGoal::HutWoodAtLeast(target);
+                        let Some(cheapest) = cheapest(state, goal) else {
+                            continue;
+                        };
+
+                        let plan = plan(state, goal).unwrap();
+                        let reached = plan.iter().fold(state, |state, action| {
+                            assert!(action.is_possible(&state), "{:?} in {:?}", action, plan);
+                            action.apply(&state)
+                        });
+                        assert!(goal.is_satisfied(&reached), "{:?} from {:?}", plan, state);
+                        assert_eq!(cost(&plan), cheapest, "{:?} from {:?}", plan, state);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn heuristic_never_overestimates() {
+    for has_wood in [false, true] {
+        for hut_wood in 0..=9 {
+            for wood_drops in 0..=3 {
+                let state = GoapState {
+                    has_wood,
+                    hut_wood,
+                    wood_drops,
+                    trees_available: 2,
+                    ..Default::default()
+                };
+                for goal in [GoapGoal::HutWoodAtLeast(9), GoapGoal::AtHome] {
+                    if let Some(cheapest) = cheapest(state, goal) {
+                        assert!(
+                            goal.heuristic(&state) <= cheapest,
+                            "{:?} for {:?}",
+                            state,
+                            goal
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
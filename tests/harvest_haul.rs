@@ -0,0 +1,106 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Harness;
+use village::fsm::components::*;
+use village::harvestable::tree::Tree;
+use village::item::*;
+use village::item_drop::ItemDrop;
+use village::jobs::JobBoard;
+use village::structure::stockpile::Stockpile;
+
+fn wood_on_the_ground(harness: &mut Harness) -> u32 {
+    let world = harness.world_mut();
+    world
+        .query_filtered::<&ItemStack, With<ItemDrop>>()
+        .iter(world)
+        .filter(|stack| stack.item_type == ItemType::Wood)
+        .map(|stack| stack.count)
+        .sum()
+}
+
+#[test]
+fn villager_walks_to_a_nearby_tree_and_starts_harvesting() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 0.0));
+    harness.spawn_tree(Vec3::new(2.0, 0.0, 0.0));
+    let villager = harness.spawn_villager(Vec3::ZERO);
+
+    harness.assert_reaches_state(villager, VillagerState::WalkingToHarvest, 0.5);
+    harness.assert_reaches_state(villager, VillagerState::Harvesting, 2.0);
+}
+
+#[test]
+fn felled_tree_ends_up_in_the_hut() {
+    let mut harness = Harness::new();
+    let wood_hut = harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 0.0));
+    let tree = harness.spawn_tree(Vec3::new(2.0, 0.0, 0.0));
+    harness.spawn_villager(Vec3::ZERO);
+
+    let felled = harness.run_until(30.0, |world| world.get_entity(tree).is_err());
+    assert!(felled.is_some(), "tree was never felled");
+    assert_eq!(harness.count::<With<Tree>>(), 0);
+
+    let dropped = wood_on_the_ground(&mut harness);
+    assert!(dropped > 0);
+    let stored = harness.stored(wood_hut);
+
+    let hauled = harness.run_until(30.0, |world| {
+        world
+            .query_filtered::<&ItemStack, With<Stockpile>>()
+            .iter(world)
+            .any(|stack| stack.count == stored + dropped)
+    });
+    assert!(hauled.is_some(), "wood never reached the hut");
+    assert_eq!(harness.stored(wood_hut), stored + dropped);
+    assert_eq!(wood_on_the_ground(&mut harness), 0);
+}
+
+#[test]
+fn dropped_wood_is_hauled_to_the_hut() {
+    let mut harness = Harness::new();
+    let wood_hut = harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 0.0));
+    harness.spawn_wood_drop(Vec3::new(2.0, 0.0, 1.0), 5);
+    let villager = harness.spawn_villager(Vec3::ZERO);
+
+    harness.assert_reaches_state(villager, VillagerState::BringingTo, 5.0);
+    harness.assert_reaches_state(villager, VillagerState::Idle, 5.0);
+    assert_eq!(harness.stored(wood_hut), 5);
+    assert_eq!(wood_on_the_ground(&mut harness), 0);
+}
+
+#[test]
+fn two_villagers_never_work_the_same_tree() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 0.0));
+    let tree = harness.spawn_tree(Vec3::new(2.0, 0.0, 0.0));
+    let villagers = [
+        harness.spawn_villager(Vec3::ZERO),
+        harness.spawn_villager(Vec3::new(0.0, 0.0, 0.5)),
+    ];
+
+    let mut chopping = None;
+    harness.run_until(10.0, |world| {
+        let at_the_tree = villagers
+            .into_iter()
+            .filter(|villager| {
+                world
+                    .get::<FSMWalkingToHarvest>(*villager)
+                    .is_some_and(|walking| walking.target == tree)
+                    || world
+                        .get::<FSMHarvesting>(*villager)
+                        .is_some_and(|harvesting| harvesting.target == tree)
+            })
+            .collect::<Vec<_>>();
+        assert!(at_the_tree.len() <= 1, "both villagers went for the tree");
+        if let Some(villager) = at_the_tree.first() {
+            chopping.get_or_insert(*villager);
+            assert_eq!(chopping, Some(*villager), "the tree changed hands");
+            assert!(world.resource::<JobBoard>().is_reserved_by(tree, *villager));
+        }
+        world.get_entity(tree).is_err()
+    });
+
+    assert!(chopping.is_some(), "nobody went for the tree");
+}
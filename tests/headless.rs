@@ -0,0 +1,66 @@
+use village::headless::*;
+
+fn short_run(seed: u64) -> HeadlessConfig {
+    HeadlessConfig {
+        seconds: 20.0,
+        timestep: 1.0 / 30.0,
+        seed,
+        output: None,
+    }
+}
+
+#[test]
+fn flags_are_parsed() {
+    let args = [
+        "--headless",
+        "--seconds",
+        "30",
+        "--seed",
+        "7",
+        "--output",
+        "stats.ron",
+    ]
+    .map(String::from);
+    let config = HeadlessConfig::from_args(args).unwrap();
+    assert_eq!(config.seconds, 30.0);
+    assert_eq!(config.seed, 7);
+    assert_eq!(config.output, Some("stats.ron".into()));
+    assert_eq!(config.timestep, HeadlessConfig::default().timestep);
+
+    assert!(HeadlessConfig::from_args(["--seconds".to_string()]).is_err());
+    assert!(HeadlessConfig::from_args(["--timestep", "0"].map(String::from)).is_err());
+    assert!(HeadlessConfig::from_args(["--fast".to_string()]).is_err());
+}
+
+#[test]
+fn a_run_accounts_for_all_villager_time() {
+    let config = short_run(1);
+    let stats = run_headless(&config);
+    assert!((stats.seconds - config.seconds).abs() < config.timestep);
+
+    // Every FSM villager spends each second in exactly one state
+    let villagers = stats.state_seconds.values().sum::<f32>() / stats.seconds;
+    assert!(villagers >= 1.0);
+    assert!((villagers - villagers.round()).abs() < 0.01);
+    assert!(stats.state_seconds.len() > 1);
+}
+
+#[test]
+fn same_seed_gives_the_same_stats() {
+    let first = run_headless(&short_run(3));
+    let second = run_headless(&short_run(3));
+    assert_eq!(first.wood_gathered, second.wood_gathered);
+    assert_eq!(first.trees_felled, second.trees_felled);
+    assert_eq!(first.task_failures, second.task_failures);
+    assert_eq!(first.state_seconds, second.state_seconds);
+}
+
+#[test]
+fn a_run_fells_trees_and_gathers_wood() {
+    let stats = run_headless(&HeadlessConfig {
+        seconds: 60.0,
+        ..short_run(1)
+    });
+    assert!(stats.trees_felled > 0, "no trees felled:\n{}", stats);
+    assert!(stats.wood_gathered > 0, "no wood gathered:\n{}", stats);
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use common::Harness;
+use village::fsm::components::*;
+use village::fsm::machine::*;
+
+use VillagerState::*;
+
+fn history(harness: &Harness, villager: Entity) -> Vec<VillagerState> {
+    harness
+        .world()
+        .get::<FSMHistory<VillagerState>>(villager)
+        .unwrap()
+        .states()
+        .collect()
+}
+
+#[test]
+fn history_records_a_harvest() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 0.0));
+    let tree = harness.spawn_tree(Vec3::new(2.0, 0.0, 0.0));
+    let villager = harness.spawn_villager(Vec3::ZERO);
+
+    let felled = harness.run_until(30.0, |world| {
+        world.get_entity(tree).is_err()
+            && world
+                .get::<FSMHistory<VillagerState>>(villager)
+                .is_some_and(|history| history.entries().count() >= 4)
+    });
+    assert!(felled.is_some(), "tree was never felled");
+
+    assert_eq!(
+        history(&harness, villager)[..4],
+        [Idle, WalkingToHarvest, Harvesting, Idle]
+    );
+}
+
+#[test]
+fn every_transition_is_sent_as_an_event() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 0.0));
+    harness.spawn_tree(Vec3::new(2.0, 0.0, 0.0));
+    let villager = harness.spawn_villager(Vec3::ZERO);
+
+    let mut transitions = Vec::new();
+    harness.run_until(30.0, |world| {
+        transitions.extend(
+            world
+                .resource::<Events<StateTransitioned<VillagerState>>>()
+                .iter_current_update_events()
+                .filter(|event| event.entity == villager)
+                .map(|event| (event.from, event.to)),
+        );
+        transitions.len() >= 3
+    });
+
+    assert_eq!(
+        transitions[..3],
+        [
+            (Idle, WalkingToHarvest),
+            (WalkingToHarvest, Harvesting),
+            (Harvesting, Idle),
+        ]
+    );
+    // The history and the events tell the same story
+    let recorded = history(&harness, villager);
+    for (i, (from, to)) in transitions.iter().enumerate() {
+        assert_eq!((recorded[i], recorded[i + 1]), (*from, *to));
+    }
+}
+
+#[test]
+fn history_keeps_only_the_latest_entries() {
+    let mut history = FSMHistory::new(3);
+    for (i, state) in [Idle, WalkingTo, Idle, PickingUp, BringingTo]
+        .into_iter()
+        .enumerate()
+    {
+        history.push(FSMHistoryEntry {
+            state,
+            at: Duration::from_secs(i as u64),
+            reason: "test",
+        });
+    }
+
+    assert_eq!(
+        history.states().collect::<Vec<_>>(),
+        [Idle, PickingUp, BringingTo]
+    );
+    assert_eq!(history.entries().next().unwrap().at, Duration::from_secs(2));
+    assert_eq!(history.latest().unwrap().state, BringingTo);
+}
+
+#[test]
+fn villager_history_stays_bounded() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 0.0));
+    for x in 0..6 {
+        harness.spawn_tree(Vec3::new(2.0 + x as f32 * 0.5, 0.0, 1.0));
+    }
+    let villager = harness.spawn_villager(Vec3::ZERO);
+
+    let mut transitions = 0;
+    harness.run_until(120.0, |world| {
+        transitions += world
+            .resource::<Events<StateTransitioned<VillagerState>>>()
+            .iter_current_update_events()
+            .filter(|event| event.entity == villager)
+            .count();
+        false
+    });
+
+    let history = harness
+        .world()
+        .get::<FSMHistory<VillagerState>>(villager)
+        .unwrap();
+    assert!(
+        transitions > history.capacity(),
+        "only {} transitions",
+        transitions
+    );
+    assert_eq!(history.entries().count(), history.capacity());
+}
@@ -0,0 +1,313 @@
+mod common;
+
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+
+use common::Harness;
+use village::assets::*;
+use village::fsm::components::*;
+use village::inventory::*;
+use village::item::*;
+use village::item_drop::*;
+use village::jobs::*;
+use village::random::SimulationRng;
+use village::structure::stockpile::*;
+
+fn spawn_wood_hut_storing(
+    harness: &mut Harness,
+    position: Vec3,
+    stored: u32,
+    capacity: u32,
+) -> Entity {
+    let wood_hut = harness.spawn_storage(position, ItemType::Wood, capacity);
+    harness.stock(wood_hut, stored);
+    wood_hut
+}
+
+fn spawn_picking_up(harness: &mut Harness, item_drop: Entity, inventory: Inventory) -> Entity {
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMPickingUp {
+            target: item_drop,
+            proximity: 0.2,
+        },
+    );
+    harness.world_mut().entity_mut(villager).insert(inventory);
+    let mut job_board = harness.world_mut().resource_mut::<JobBoard>();
+    job_board.post(JobKind::Haul, item_drop);
+    job_board.claim(item_drop, villager);
+    villager
+}
+
+fn carried(harness: &Harness, villager: Entity) -> &Inventory {
+    harness.world().get::<Inventory>(villager).unwrap()
+}
+
+#[test]
+fn inventory_respects_count_and_weight() {
+    let mut inventory = Inventory::new(8, 5.0);
+
+    assert_eq!(inventory.add(ItemType::Wood, 3), 3);
+    assert_eq!(inventory.room_for(ItemType::Wood), 2);
+    assert_eq!(inventory.add(ItemType::Wood, 4), 2);
+    assert_eq!(inventory.count(ItemType::Wood), 5);
+
+    assert_eq!(inventory.take(ItemType::Wood, 2), 2);
+    assert_eq!(inventory.take_all(ItemType::Wood), 3);
+    assert!(inventory.is_empty());
+}
+
+#[test]
+fn partial_pickup_leaves_the_rest_on_the_ground() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(3.0, 0.0, 0.0));
+    let wood = harness.spawn_wood_drop(Vec3::new(0.1, 0.0, 0.0), 5);
+    let mut inventory = Inventory::new(8, 100.0);
+    inventory.add(ItemType::Wood, 6);
+    let villager = spawn_picking_up(&mut harness, wood, inventory);
+
+    harness.app.update();
+
+    assert_eq!(carried(&harness, villager).count(ItemType::Wood), 8);
+    assert_eq!(harness.world().get::<ItemStack>(wood).unwrap().count, 3);
+    assert!(harness
+        .world()
+        .resource::<JobBoard>()
+        .is_available(wood, Entity::PLACEHOLDER));
+    assert_eq!(harness.state(villager), VillagerState::BringingTo);
+}
+
+#[test]
+fn nothing_picked_up_means_nothing_to_haul() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(3.0, 0.0, 0.0));
+    let wood = harness.spawn_wood_drop(Vec3::new(0.1, 0.0, 0.0), 5);
+    // Not even one piece of wood is light enough
+    let villager = spawn_picking_up(&mut harness, wood, Inventory::new(8, 0.5));
+
+    harness.app.update();
+
+    assert!(carried(&harness, villager).is_empty());
+    assert_eq!(harness.world().get::<ItemStack>(wood).unwrap().count, 5);
+    assert!(harness
+        .world()
+        .resource::<JobBoard>()
+        .is_available(wood, Entity::PLACEHOLDER));
+    assert_eq!(harness.state(villager), VillagerState::Idle);
+}
+
+#[test]
+fn nearby_wood_is_gathered_before_hauling() {
+    let mut harness = Harness::new();
+    let wood_hut = harness.spawn_wood_hut(Vec3::new(3.0, 0.0, 0.0));
+    let first = harness.spawn_wood_drop(Vec3::new(0.1, 0.0, 0.0), 2);
+    let second = harness.spawn_wood_drop(Vec3::new(-1.0, 0.0, 0.0), 3);
+    let villager = spawn_picking_up(&mut harness, first, Inventory::default());
+
+    harness.app.update();
+
+    assert!(harness.world().get_entity(first).is_err());
+    assert_eq!(
+        harness
+            .world()
+            .get::<FSMPickingUp>(villager)
+            .unwrap()
+            .target,
+        second
+    );
+    assert!(harness
+        .world()
+        .resource::<JobBoard>()
+        .is_reserved_by(second, villager));
+
+    // Walk over to the second drop, pick it up and head to the hut
+    for _ in 0..100 {
+        harness.app.update();
+        if harness.state(villager) != VillagerState::PickingUp {
+            break;
+        }
+    }
+
+    assert_eq!(
+        harness
+            .world()
+            .get::<FSMBringingTo>(villager)
+            .unwrap()
+            .target,
+        wood_hut
+    );
+    assert_eq!(carried(&harness, villager).count(ItemType::Wood), 5);
+}
+
+#[test]
+fn oversized_drops_are_split_into_stacks() {
+    let mut harness = Harness::new();
+
+    harness
+        .world_mut()
+        .run_system_once(
+            |mut commands: Commands,
+             registry: Res<ItemRegistry>,
+             scene_assets: Res<SceneAssets>,
+             mut rng: ResMut<SimulationRng>| {
+                spawn_item_drop(
+                    &mut commands,
+                    &registry,
+                    &scene_assets,
+                    ItemType::Stone,
+                    Vec3::ZERO,
+                    40,
+                    rng.stream("test"),
+                );
+            },
+        )
+        .unwrap();
+
+    let mut counts = harness
+        .world_mut()
+        .query_filtered::<&ItemStack, With<ItemDrop>>()
+        .iter(harness.world())
+        .map(|item_stack| item_stack.count)
+        .collect::<Vec<_>>();
+    counts.sort();
+    assert_eq!(counts, vec![8, 16, 16]);
+}
+
+#[test]
+fn items_without_matching_storage_are_not_hauled() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(3.0, 0.0, 0.0));
+    let stone =
+        harness.spawn_item_drop(Vec3::new(0.1, 0.0, 0.0), ItemStack::new(ItemType::Stone, 2));
+    let villager = spawn_picking_up(&mut harness, stone, Inventory::default());
+
+    harness.app.update();
+
+    assert_eq!(harness.state(villager), VillagerState::Idle);
+    assert!(carried(&harness, villager).is_empty());
+}
+
+#[test]
+fn items_are_hauled_to_storage_for_their_type() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(1.0, 0.0, 0.0));
+    let stone_storage = harness.spawn_storage(Vec3::new(4.0, 0.0, 0.0), ItemType::Stone, 32);
+    let stone =
+        harness.spawn_item_drop(Vec3::new(0.1, 0.0, 0.0), ItemStack::new(ItemType::Stone, 2));
+    let villager = spawn_picking_up(&mut harness, stone, Inventory::default());
+
+    harness.app.update();
+
+    assert_eq!(
+        harness
+            .world()
+            .get::<FSMBringingTo>(villager)
+            .unwrap()
+            .target,
+        stone_storage
+    );
+}
+
+#[test]
+fn every_carried_stack_is_delivered() {
+    let mut harness = Harness::new();
+    let wood_hut = harness.spawn_wood_hut(Vec3::new(0.1, 0.0, 0.0));
+    let stone_storage = harness.spawn_storage(Vec3::new(2.0, 0.0, 0.0), ItemType::Stone, 32);
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMBringingTo {
+            target: wood_hut,
+            proximity: 0.2,
+        },
+    );
+    harness.give(villager, ItemType::Wood, 3);
+    harness.give(villager, ItemType::Stone, 2);
+
+    harness.app.update();
+    assert_eq!(
+        harness
+            .world()
+            .get::<FSMBringingTo>(villager)
+            .unwrap()
+            .target,
+        stone_storage
+    );
+
+    for _ in 0..40 {
+        harness.app.update();
+    }
+
+    let mut stored = harness
+        .world_mut()
+        .query_filtered::<&ItemStack, With<Stockpile>>()
+        .iter(harness.world())
+        .map(|stored| (stored.item_type, stored.count))
+        .collect::<Vec<_>>();
+    stored.sort_by_key(|(_, count)| *count);
+    assert_eq!(stored, vec![(ItemType::Stone, 2), (ItemType::Wood, 3)]);
+    assert!(carried(&harness, villager).is_empty());
+    assert_eq!(harness.state(villager), VillagerState::Idle);
+}
+
+#[test]
+fn full_wood_huts_are_skipped() {
+    let mut harness = Harness::new();
+    spawn_wood_hut_storing(&mut harness, Vec3::new(1.0, 0.0, 0.0), 32, 32);
+    let roomy_hut = spawn_wood_hut_storing(&mut harness, Vec3::new(4.0, 0.0, 0.0), 0, 32);
+    let wood = harness.spawn_wood_drop(Vec3::new(0.1, 0.0, 0.0), 2);
+    let villager = spawn_picking_up(&mut harness, wood, Inventory::default());
+
+    harness.app.update();
+
+    assert_eq!(
+        harness
+            .world()
+            .get::<FSMBringingTo>(villager)
+            .unwrap()
+            .target,
+        roomy_hut
+    );
+}
+
+#[test]
+fn overflow_is_dropped_next_to_the_hut() {
+    let mut harness = Harness::new();
+    let wood_hut = spawn_wood_hut_storing(&mut harness, Vec3::new(1.0, 0.0, 0.0), 30, 32);
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        // Close enough to deposit from where the villager stands
+        FSMBringingTo {
+            target: wood_hut,
+            proximity: 1.5,
+        },
+    );
+    harness.give(villager, ItemType::Wood, 5);
+
+    harness.app.update();
+
+    let stored = harness
+        .world_mut()
+        .query_filtered::<&ItemStack, With<Stockpile>>()
+        .single(harness.world())
+        .count;
+    assert_eq!(stored, 32);
+    let dropped = harness
+        .world_mut()
+        .query_filtered::<(&ItemStack, &Transform), With<ItemDrop>>()
+        .iter(harness.world())
+        .map(|(item_stack, transform)| (item_stack.count, transform.translation))
+        .collect::<Vec<_>>();
+    assert_eq!(dropped.len(), 1);
+    let (count, position) = dropped[0];
+    assert_eq!(count, 3);
+    // At the hut, not where the villager is standing
+    let hut_position = Vec3::new(1.0, 0.0, 0.0);
+    assert!(position.distance(hut_position) < position.distance(Vec3::ZERO));
+    assert!(position.xz().distance(hut_position.xz()) < 1.0);
+
+    let storage_full = harness.events::<StorageFull>();
+    assert_eq!(storage_full.len(), 1);
+    assert_eq!(storage_full[0].building, wood_hut);
+    assert_eq!(storage_full[0].overflow, 3);
+    assert_eq!(harness.state(villager), VillagerState::Idle);
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+
+use common::*;
+use village::assets::*;
+use village::item::*;
+use village::loading::*;
+use village::structure::registry::*;
+use village::villager::needs::Needs;
+
+fn hunger(harness: &Harness, villager: Entity) -> f32 {
+    harness.world().get::<Needs>(villager).unwrap().hunger
+}
+
+fn manifest_without(ids: &[&str]) -> SceneAssets {
+    let entries = bundled_manifest()
+        .into_iter()
+        .filter(|entry| !ids.contains(&entry.id.0.as_str()))
+        .collect::<Vec<_>>();
+    let handles = entries
+        .iter()
+        .map(|_| Handle::default())
+        .collect::<Vec<_>>();
+    SceneAssets::from_manifest(&entries, handles)
+}
+
+fn item_registry(harness: &Harness) -> &ItemRegistry {
+    harness.world().resource::<ItemRegistry>()
+}
+
+#[test]
+fn bundled_manifest_is_missing_nothing() {
+    let harness = Harness::new();
+    let missing = missing_scenes(
+        &SceneAssets::placeholder(),
+        &StructureRegistry::new(),
+        item_registry(&harness),
+    );
+    assert!(missing.is_empty(), "missing {:?}", missing);
+}
+
+#[test]
+fn missing_scenes_names_each_model_the_game_needs() {
+    let harness = Harness::new();
+    let scene_assets = manifest_without(&[
+        "house",
+        "wood",
+        "villager",
+        "tree_pine",
+        "tree_round",
+        "tree_dead",
+    ]);
+    let missing = missing_scenes(
+        &scene_assets,
+        &StructureRegistry::new(),
+        item_registry(&harness),
+    );
+
+    assert_eq!(
+        missing,
+        [
+            "\"house\" for House",
+            "\"wood\" for Wood",
+            "\"villager\" for villagers",
+            "any model tagged \"tree\" for trees",
+        ]
+    );
+}
+
+#[test]
+fn unknown_scene_ids_get_an_empty_scene() {
+    let scene_assets = SceneAssets::placeholder();
+    assert_eq!(scene_assets.scene("windmill"), Handle::default());
+}
+
+#[test]
+fn simulation_waits_until_loading_is_done() {
+    let mut harness = Harness::new();
+    harness
+        .app
+        .add_plugins(StatesPlugin)
+        .init_state::<GameState>();
+    let villager = harness.spawn_villager(Vec3::ZERO);
+    let start = hunger(&harness, villager);
+
+    harness.advance(2.0);
+    assert_eq!(hunger(&harness, villager), start);
+
+    harness
+        .world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::InGame);
+    harness.advance(2.0);
+    assert!(hunger(&harness, villager) < start);
+}
+
+#[test]
+fn loading_moves_on_once_every_model_is_there() {
+    let mut harness = Harness::new();
+    harness.app.add_plugins((StatesPlugin, LoadingPlugin));
+    harness.advance(0.2);
+
+    assert_eq!(
+        *harness.world().resource::<State<GameState>>().get(),
+        GameState::InGame
+    );
+}
+
+#[test]
+fn loading_quits_when_a_model_is_missing() {
+    let mut harness = Harness::new();
+    harness
+        .app
+        .add_plugins((StatesPlugin, LoadingPlugin))
+        .insert_resource(manifest_without(&["wood_hut"]));
+    harness.app.update();
+
+    assert_eq!(
+        *harness.world().resource::<State<GameState>>().get(),
+        GameState::Loading
+    );
+    assert!(harness
+        .app
+        .should_exit()
+        .is_some_and(|exit| exit.is_error()));
+}
@@ -0,0 +1,262 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Harness;
+use village::fsm::components::*;
+use village::fsm::machine::FSM;
+use village::navigation::*;
+use village::villager::villager::Villager;
+
+fn spawn_obstacle(harness: &mut Harness, position: Vec3, radius: f32) -> Entity {
+    harness
+        .world_mut()
+        .spawn((
+            Transform::from_translation(position),
+            NavObstacle { radius },
+        ))
+        .id()
+}
+
+/// Steps until the villager is in `state`, panicking if it ever stands in a blocked cell.
+fn walk_clear_of_obstacles(harness: &mut Harness, villager: Entity, state: VillagerState) {
+    let reached = harness.run_until(10.0, |world| {
+        let position = world.get::<Transform>(villager).unwrap().translation;
+        let nav_grid = world.resource::<NavGrid>();
+        assert!(
+            nav_grid.is_walkable(NavGrid::cell_of(position), &[]),
+            "villager walked into an obstacle at {}",
+            position
+        );
+        world.get::<FSM<VillagerState>>(villager).unwrap().current() == state
+    });
+    assert!(reached.is_some(), "villager never reached {:?}", state);
+}
+
+#[test]
+fn paths_go_around_walls() {
+    let mut nav_grid = NavGrid::default();
+    for (index, z) in (-6..=6).enumerate() {
+        let post = Entity::from_raw(index as u32);
+        nav_grid.add_obstacle(post, Vec3::new(0.0, 0.0, z as f32 * 0.5), 0.1);
+    }
+    let from = Vec3::new(-2.0, 0.0, 0.0);
+    let to = Vec3::new(2.0, 0.0, 0.0);
+    assert!(!nav_grid.is_line_walkable(from, to, &[]));
+
+    let (cells, complete) = find_path(&nav_grid, from, to, &[]);
+    assert!(complete);
+    assert_eq!(*cells.last().unwrap(), NavGrid::cell_of(to));
+    assert!(cells.iter().all(|cell| nav_grid.is_walkable(*cell, &[])));
+    assert!(cells
+        .windows(2)
+        .all(|pair| (pair[1] - pair[0]).abs().max_element() == 1));
+}
+
+#[test]
+fn unreachable_goals_are_only_searched_for_again_once_the_grid_changes() {
+    let mut nav_grid = NavGrid::default();
+    let to = Vec3::new(2.0, 0.0, 0.0);
+    let goal = NavGrid::cell_of(to);
+    // A ring of posts all the way around the goal
+    let mut posts = 0;
+    for x in -2..=2 {
+        for y in -2..=2 {
+            if i32::max(i32::abs(x), i32::abs(y)) == 2 {
+                let cell = goal + IVec2::new(x, y);
+                nav_grid.add_obstacle(Entity::from_raw(posts), NavGrid::center_of(cell), 0.1);
+                posts += 1;
+            }
+        }
+    }
+    let target = Entity::from_raw(posts);
+    let from = Vec3::new(-2.0, 0.0, 0.0);
+
+    // Walk the partial path to the closest the search got
+    let mut path = NavPath::default();
+    let mut position = from;
+    for _ in 0..100 {
+        position = path.next_waypoint(&nav_grid, position, target, to);
+        if path.waypoints().count() == 0 {
+            break;
+        }
+    }
+    assert_eq!(path.waypoints().count(), 0);
+
+    // Pushed somewhere else, the villager doesn't search the same grid again
+    path.next_waypoint(&nav_grid, from, target, to);
+    assert_eq!(path.waypoints().count(), 0);
+
+    nav_grid.add_obstacle(Entity::from_raw(posts + 1), Vec3::new(-20.0, 0.0, 0.0), 0.1);
+    path.next_waypoint(&nav_grid, from, target, to);
+    assert!(path.waypoints().count() > 0);
+}
+
+#[test]
+fn removed_obstacles_free_their_cells() {
+    let mut nav_grid = NavGrid::default();
+    let rock = Entity::from_raw(1);
+    let position = Vec3::new(1.0, 0.0, 1.0);
+    nav_grid.add_obstacle(rock, position, 0.4);
+    let version = nav_grid.version();
+    assert!(!nav_grid.is_walkable(NavGrid::cell_of(position), &[]));
+    assert!(nav_grid.is_walkable(NavGrid::cell_of(position), &[rock]));
+
+    nav_grid.remove_obstacle(rock);
+    assert!(nav_grid.is_walkable(NavGrid::cell_of(position), &[]));
+    assert!(nav_grid.version() > version);
+}
+
+#[test]
+fn walkers_steer_away_from_obstacles_they_pass_close_to() {
+    let mut nav_grid = NavGrid::default();
+    let rock = Entity::from_raw(1);
+    let target = Entity::from_raw(2);
+    nav_grid.add_obstacle(rock, Vec3::new(1.1, 0.0, 0.2), 0.1);
+
+    let avoidance = obstacle_avoidance(&nav_grid, Vec3::new(0.9, 0.0, 0.25), target);
+    assert!(avoidance.x < 0.0);
+    assert!(avoidance.length() <= 0.5);
+    // Nothing to avoid far away, or when the obstacle is where the villager is going
+    assert_eq!(
+        obstacle_avoidance(&nav_grid, Vec3::new(-2.0, 0.0, 0.25), target),
+        Vec3::ZERO
+    );
+    assert_eq!(
+        obstacle_avoidance(&nav_grid, Vec3::new(0.9, 0.0, 0.25), rock),
+        Vec3::ZERO
+    );
+}
+
+#[test]
+fn villager_walks_around_a_structure_in_the_way() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 3.0));
+    spawn_obstacle(&mut harness, Vec3::new(2.0, 0.0, 0.0), 0.8);
+    harness.spawn_wood_drop(Vec3::new(4.0, 0.0, 0.0), 3);
+    let villager = harness.spawn_villager(Vec3::ZERO);
+
+    walk_clear_of_obstacles(&mut harness, villager, VillagerState::BringingTo);
+}
+
+#[test]
+fn villager_plans_again_when_something_is_placed_in_its_way() {
+    let mut harness = Harness::new();
+    harness.spawn_wood_hut(Vec3::new(-3.0, 0.0, 3.0));
+    harness.spawn_wood_drop(Vec3::new(4.0, 0.0, 0.0), 3);
+    let villager = harness.spawn_villager(Vec3::ZERO);
+    harness.assert_reaches_state(villager, VillagerState::PickingUp, 1.0);
+    assert_eq!(
+        harness
+            .world()
+            .get::<NavPath>(villager)
+            .unwrap()
+            .waypoints()
+            .count(),
+        0
+    );
+
+    let position = harness
+        .world()
+        .get::<Transform>(villager)
+        .unwrap()
+        .translation;
+    harness.spawn_tree(position + Vec3::new(1.0, 0.0, 0.0));
+    harness.advance(0.1);
+    assert!(
+        harness
+            .world()
+            .get::<NavPath>(villager)
+            .unwrap()
+            .waypoints()
+            .count()
+            > 0
+    );
+    walk_clear_of_obstacles(&mut harness, villager, VillagerState::BringingTo);
+}
+
+fn spawn_walking_to(harness: &mut Harness, position: Vec3, target: Entity) -> Entity {
+    harness
+        .world_mut()
+        .spawn((
+            Transform::from_translation(position),
+            Villager {
+                movement_speed: 3.0,
+                harvesting_speed: 1.0,
+            },
+            FSM::start(FSMWalkingTo {
+                target,
+                proximity: 0.3,
+            }),
+        ))
+        .id()
+}
+
+fn distance_between(harness: &Harness, a: Entity, b: Entity) -> f32 {
+    let position = |entity| {
+        harness
+            .world()
+            .get::<Transform>(entity)
+            .unwrap()
+            .translation
+    };
+    position(a).xz().distance(position(b).xz())
+}
+
+#[test]
+fn villagers_on_the_same_spot_move_apart() {
+    let mut harness = Harness::new();
+    let first = harness.spawn_villager(Vec3::ZERO);
+    let second = harness.spawn_villager(Vec3::ZERO);
+    harness.advance(1.0);
+
+    let radius = harness.world().get::<NavAgent>(first).unwrap().radius;
+    assert!(distance_between(&harness, first, second) > radius * 1.9);
+}
+
+#[test]
+fn villagers_sharing_a_target_arrive_side_by_side() {
+    let mut harness = Harness::new();
+    let spot = harness
+        .world_mut()
+        .spawn(Transform::from_xyz(3.0, 0.0, 0.0))
+        .id();
+    let first = spawn_walking_to(&mut harness, Vec3::new(0.0, 0.0, 0.1), spot);
+    let second = spawn_walking_to(&mut harness, Vec3::new(0.0, 0.0, -0.1), spot);
+
+    harness.assert_reaches_state(first, VillagerState::Idle, 3.0);
+    harness.assert_reaches_state(second, VillagerState::Idle, 3.0);
+    harness.advance(0.5);
+    let radius = harness.world().get::<NavAgent>(first).unwrap().radius;
+    assert!(distance_between(&harness, first, second) > radius * 1.9);
+}
+
+#[test]
+fn villagers_slow_down_as_they_arrive() {
+    let mut harness = Harness::new();
+    let spot = harness
+        .world_mut()
+        .spawn(Transform::from_xyz(2.0, 0.0, 0.0))
+        .id();
+    let villager = spawn_walking_to(&mut harness, Vec3::ZERO, spot);
+    let x = |harness: &Harness| {
+        harness
+            .world()
+            .get::<Transform>(villager)
+            .unwrap()
+            .translation
+            .x
+    };
+
+    harness.advance(common::TIMESTEP * 2.0);
+    let before = x(&harness);
+    harness.advance(common::TIMESTEP);
+    let cruising = x(&harness) - before;
+
+    harness.run_until(2.0, |world| {
+        world.get::<Transform>(villager).unwrap().translation.x > 1.6
+    });
+    let before = x(&harness);
+    harness.advance(common::TIMESTEP);
+    assert!(x(&harness) - before < cruising * 0.8);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Harness;
+use village::fsm::components::*;
+use village::harvestable::*;
+use village::structure::registry::StructureKind;
+use village::villager::needs::*;
+
+fn set_needs(harness: &mut Harness, villager: Entity, needs: Needs) {
+    *harness.world_mut().get_mut::<Needs>(villager).unwrap() = needs;
+}
+
+#[test]
+fn penalties_only_apply_to_low_needs() {
+    let rested = Needs::default();
+    assert_eq!(rested.movement_penalty(), 1.0);
+    assert_eq!(rested.harvesting_penalty(), 1.0);
+
+    let exhausted = Needs {
+        hunger: 1.0,
+        energy: 0.0,
+    };
+    assert!(exhausted.movement_penalty() < 1.0);
+    assert!(exhausted.harvesting_penalty() < 1.0);
+    assert_eq!(exhausted.critical(), Some(Need::Energy));
+}
+
+#[test]
+fn critical_hunger_interrupts_harvesting_and_sends_villager_home() {
+    let mut harness = Harness::new();
+    let house = harness.spawn_structure(StructureKind::House, Vec3::new(3.0, 0.0, 0.0));
+    let tree = harness.spawn_tree(Vec3::new(0.1, 0.0, 0.0));
+    harness
+        .world_mut()
+        .get_mut::<Harvestable>(tree)
+        .unwrap()
+        .health = 100.0;
+    let villager = harness.spawn_villager_in(Vec3::ZERO, FSMHarvesting { target: tree });
+    set_needs(
+        &mut harness,
+        villager,
+        Needs {
+            hunger: 0.1,
+            energy: 1.0,
+        },
+    );
+
+    harness.app.update();
+    harness.app.update();
+
+    let walking_home = harness.world().get::<FSMWalkingHome>(villager).unwrap();
+    assert_eq!(walking_home.target, house);
+    assert_eq!(walking_home.need, Need::Hunger);
+}
+
+#[test]
+fn eating_restores_hunger_then_goes_idle() {
+    let mut harness = Harness::new();
+    let house = harness.spawn_structure(StructureKind::House, Vec3::ZERO);
+    let villager = harness.spawn_villager_in(Vec3::ZERO, FSMEating { house });
+    set_needs(
+        &mut harness,
+        villager,
+        Needs {
+            hunger: 0.5,
+            energy: 1.0,
+        },
+    );
+
+    harness.app.update();
+    assert_eq!(harness.state(villager), VillagerState::Eating);
+    harness.advance(5.0);
+
+    assert!(harness.world().get::<Needs>(villager).unwrap().hunger > 0.9);
+    assert_ne!(harness.state(villager), VillagerState::Eating);
+}
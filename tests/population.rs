@@ -0,0 +1,219 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Harness;
+use village::item::ItemType;
+use village::structure::registry::*;
+use village::villager::needs::Needs;
+use village::villager::population::*;
+use village::villager::villager::Villager;
+
+fn home_of(harness: &Harness, villager: Entity) -> Option<Entity> {
+    harness.world().get::<Home>(villager).map(|home| home.house)
+}
+
+fn birth_now(harness: &mut Harness) {
+    harness
+        .world_mut()
+        .resource_mut::<Population>()
+        .until_next_birth = 0.0;
+}
+
+/// A store of food for newborns, somewhere out of the way.
+fn spawn_pantry(harness: &mut Harness, food: u32) -> Entity {
+    let pantry = harness.spawn_storage(Vec3::new(-6.0, 0.0, 6.0), ItemType::Food, 32);
+    harness.stock(pantry, food);
+    pantry
+}
+
+#[test]
+fn villagers_move_into_the_nearest_house_with_room() {
+    let mut harness = Harness::new();
+    let near = harness.spawn_structure(StructureKind::House, Vec3::new(2.0, 0.0, 0.0));
+    let far = harness.spawn_structure(StructureKind::House, Vec3::new(-8.0, 0.0, 0.0));
+    let capacity = harness
+        .world()
+        .resource::<StructureRegistry>()
+        .get(StructureKind::House)
+        .housing;
+    let villagers = (0..=capacity)
+        .map(|_| harness.spawn_villager(Vec3::ZERO))
+        .collect::<Vec<_>>();
+    harness.advance(0.1);
+
+    let homes = villagers
+        .iter()
+        .map(|villager| home_of(&harness, *villager))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        homes.iter().filter(|home| **home == Some(near)).count() as u32,
+        capacity
+    );
+    assert_eq!(homes.iter().filter(|home| **home == Some(far)).count(), 1);
+}
+
+#[test]
+fn villagers_lose_their_home_when_it_is_gone() {
+    let mut harness = Harness::new();
+    let house = harness.spawn_structure(StructureKind::House, Vec3::new(2.0, 0.0, 0.0));
+    let villager = harness.spawn_villager(Vec3::ZERO);
+    harness.advance(0.1);
+    assert_eq!(home_of(&harness, villager), Some(house));
+
+    harness.world_mut().entity_mut(house).despawn_recursive();
+    harness.advance(0.1);
+    assert_eq!(home_of(&harness, villager), None);
+}
+
+#[test]
+fn well_fed_households_with_room_grow() {
+    let mut harness = Harness::new();
+    let pantry = spawn_pantry(&mut harness, 20);
+    let house = harness.spawn_structure(StructureKind::House, Vec3::new(2.0, 0.0, 0.0));
+    harness.spawn_villager(Vec3::ZERO);
+    harness.advance(0.1);
+    assert_eq!(harness.world().resource::<Population>().count, 1);
+
+    birth_now(&mut harness);
+    harness.advance(0.1);
+
+    assert_eq!(harness.count::<With<Villager>>(), 2);
+    assert_eq!(harness.world().resource::<Population>().count, 2);
+    assert!(harness.stored(pantry) < 20);
+    let world = harness.world_mut();
+    assert!(world
+        .query::<&Home>()
+        .iter(world)
+        .all(|home| home.house == house));
+    let events = world.resource::<Events<PopulationChanged>>();
+    assert!(events.get_cursor().read(events).any(|event| *event
+        == PopulationChanged {
+            previous: 1,
+            population: 2,
+        }));
+}
+
+#[test]
+fn no_births_when_hungry_full_or_capped() {
+    // Hungry
+    let mut harness = Harness::new();
+    spawn_pantry(&mut harness, 20);
+    harness.spawn_structure(StructureKind::House, Vec3::new(2.0, 0.0, 0.0));
+    let villager = harness.spawn_villager(Vec3::ZERO);
+    harness
+        .world_mut()
+        .get_mut::<Needs>(villager)
+        .unwrap()
+        .hunger = 0.3;
+    harness.advance(0.1);
+    birth_now(&mut harness);
+    harness.advance(0.1);
+    assert_eq!(harness.count::<With<Villager>>(), 1);
+    // The wait doesn't keep running down while nobody can have a child
+    assert_eq!(
+        harness.world().resource::<Population>().until_next_birth,
+        0.0
+    );
+
+    // No room
+    let mut harness = Harness::new();
+    spawn_pantry(&mut harness, 20);
+    harness.spawn_structure(StructureKind::House, Vec3::new(2.0, 0.0, 0.0));
+    let capacity = harness
+        .world()
+        .resource::<StructureRegistry>()
+        .get(StructureKind::House)
+        .housing;
+    for _ in 0..capacity {
+        harness.spawn_villager(Vec3::ZERO);
+    }
+    harness.advance(0.1);
+    birth_now(&mut harness);
+    harness.advance(0.1);
+    assert_eq!(harness.count::<With<Villager>>(), capacity as usize);
+
+    // Capped
+    let mut harness = Harness::new();
+    spawn_pantry(&mut harness, 20);
+    harness.spawn_structure(StructureKind::House, Vec3::new(2.0, 0.0, 0.0));
+    harness.spawn_villager(Vec3::ZERO);
+    harness.world_mut().resource_mut::<Population>().cap = 1;
+    harness.advance(0.1);
+    birth_now(&mut harness);
+    harness.advance(0.1);
+    assert_eq!(harness.count::<With<Villager>>(), 1);
+}
+
+#[test]
+fn births_use_up_food_and_stop_without_it() {
+    // No food at all
+    let mut harness = Harness::new();
+    harness.spawn_structure(StructureKind::House, Vec3::new(2.0, 0.0, 0.0));
+    harness.spawn_villager(Vec3::ZERO);
+    harness.advance(0.1);
+    birth_now(&mut harness);
+    harness.advance(0.1);
+    assert_eq!(harness.count::<With<Villager>>(), 1);
+    assert_eq!(
+        harness.world().resource::<Population>().until_next_birth,
+        0.0
+    );
+
+    // Enough for a birth or two, then none
+    let mut harness = Harness::new();
+    let pantry = spawn_pantry(&mut harness, 10);
+    harness.spawn_structure(StructureKind::House, Vec3::new(2.0, 0.0, 0.0));
+    harness.spawn_villager(Vec3::ZERO);
+    harness.advance(0.1);
+    let mut births = Vec::new();
+    for _ in 0..4 {
+        let before = harness.stored(pantry);
+        birth_now(&mut harness);
+        harness.advance(0.1);
+        births.push(before - harness.stored(pantry));
+    }
+    let born = harness.count::<With<Villager>>() - 1;
+    assert!(born >= 1);
+    assert!(born < 4, "births should stop once the food runs out");
+    // Every birth took the same food, and what's left isn't enough for another
+    let cost = births[0];
+    assert!(cost > 0);
+    assert_eq!(births.iter().filter(|used| **used == cost).count(), born);
+    assert_eq!(births.iter().filter(|used| **used == 0).count(), 4 - born);
+    assert!(harness.stored(pantry) < cost);
+}
+
+#[test]
+fn births_go_to_the_house_with_the_most_room() {
+    let mut harness = Harness::new();
+    spawn_pantry(&mut harness, 20);
+    let crowded = harness.spawn_structure(StructureKind::House, Vec3::new(2.0, 0.0, 0.0));
+    let roomy = harness.spawn_structure(StructureKind::House, Vec3::new(-8.0, 0.0, 0.0));
+    let capacity = harness
+        .world()
+        .resource::<StructureRegistry>()
+        .get(StructureKind::House)
+        .housing;
+    for _ in 0..capacity - 1 {
+        harness.spawn_villager(Vec3::new(2.0, 0.0, 1.0));
+    }
+    harness.spawn_villager(Vec3::new(-8.0, 0.0, 1.0));
+    harness.advance(0.1);
+    let residents = |harness: &mut Harness, house: Entity| {
+        let world = harness.world_mut();
+        world
+            .query::<&Home>()
+            .iter(world)
+            .filter(|home| home.house == house)
+            .count()
+    };
+    assert_eq!(residents(&mut harness, crowded), capacity as usize - 1);
+    assert_eq!(residents(&mut harness, roomy), 1);
+
+    birth_now(&mut harness);
+    harness.advance(0.1);
+
+    assert_eq!(residents(&mut harness, crowded), capacity as usize - 1);
+    assert_eq!(residents(&mut harness, roomy), 2);
+}
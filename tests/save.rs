@@ -0,0 +1,395 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Harness;
+use village::assets::*;
+use village::fsm::components::*;
+use village::fsm::machine::*;
+use village::goap::*;
+use village::harvestable::{tree::*, *};
+use village::inventory::*;
+use village::item::*;
+use village::item_drop::*;
+use village::jobs::*;
+use village::random::*;
+use village::save::*;
+use village::structure::{construction::*, registry::*, stockpile::*};
+use village::villager::villager::Villager;
+use village::world::*;
+
+/// The harness with saving and loading.
+fn test_harness() -> Harness {
+    let mut harness = Harness::new();
+    harness.app.add_plugins(SavePlugin);
+    harness
+}
+
+struct Village {
+    villager: Entity,
+    wood: Entity,
+    tree: Entity,
+}
+
+/// A villager on its way to pick up wood it has claimed, next to a chopped-at tree, a
+/// hut that already holds some wood and a half-built house.
+fn build_village(harness: &mut Harness) -> Village {
+    harness.spawn_structure(StructureKind::House, Vec3::new(3.0, 0.0, 1.0));
+    let wood_hut = harness.spawn_wood_hut(Vec3::new(-2.0, 0.0, 0.0));
+    harness.stock(wood_hut, 7);
+    let tree = harness.spawn(|commands, scene_assets, _, _| {
+        spawn_tree_of_kind(
+            commands,
+            scene_assets,
+            SceneAssetId::new("tree_round"),
+            Vec3::new(4.0, 0.0, 4.0),
+        )
+    });
+    harness
+        .world_mut()
+        .get_mut::<Harvestable>(tree)
+        .unwrap()
+        .health = 1.25;
+    let wood = harness.spawn_wood_drop(Vec3::new(2.0, 0.0, -1.0), 3);
+    let site = harness.spawn_construction_site(StructureKind::House, Vec3::new(-4.0, 0.0, 3.0));
+    let mut construction_site = harness
+        .world_mut()
+        .get_mut::<ConstructionSite>(site)
+        .unwrap();
+    construction_site.delivered = 5;
+    construction_site.progress = 0.5;
+
+    let villager = harness.spawn_villager_in(
+        Vec3::new(0.5, 0.0, 0.5),
+        FSMPickingUp {
+            target: wood,
+            proximity: 0.2,
+        },
+    );
+    harness.give(villager, ItemType::Wood, 2);
+    harness
+        .world_mut()
+        .entity_mut(villager)
+        .insert(GoapAgent::new(GoapGoal::HutWoodAtLeast(20)));
+    let mut job_board = harness.world_mut().resource_mut::<JobBoard>();
+    job_board.post(JobKind::Haul, wood);
+    job_board.claim(wood, villager);
+
+    Village {
+        villager,
+        wood,
+        tree,
+    }
+}
+
+fn single<F: bevy::ecs::query::QueryFilter>(harness: &mut Harness) -> Entity {
+    let world = harness.world_mut();
+    world.query_filtered::<Entity, F>().single(world)
+}
+
+#[test]
+fn village_survives_a_round_trip() {
+    let mut harness = test_harness();
+    let village = build_village(&mut harness);
+    let save = capture(harness.world_mut());
+    assert_eq!(save.version, SAVE_VERSION);
+    assert_eq!(save.villagers.len(), 1);
+
+    let text = to_ron(&save).unwrap();
+    let parsed = migrate(&text).unwrap();
+    assert_eq!(parsed, save);
+
+    // Shift entity ids, so references only line up if they are remapped
+    let mut other = test_harness();
+    for _ in 0..5 {
+        other.world_mut().spawn_empty();
+    }
+    restore(other.world_mut(), &parsed);
+
+    let villager = single::<With<Villager>>(&mut other);
+    let wood = single::<With<ItemDrop>>(&mut other);
+    let tree = single::<With<Tree>>(&mut other);
+    assert_ne!(villager, village.villager);
+    assert_ne!(wood, village.wood);
+    assert_ne!(tree, village.tree);
+
+    let world = other.world();
+    assert_eq!(
+        world.get::<FSM<VillagerState>>(villager).unwrap().current(),
+        VillagerState::PickingUp
+    );
+    assert_eq!(world.get::<FSMPickingUp>(villager).unwrap().target, wood);
+    assert!(world.get::<FSMIdle>(villager).is_none());
+    assert_eq!(
+        world.get::<GoapAgent>(villager).unwrap().goal,
+        GoapGoal::HutWoodAtLeast(20)
+    );
+    assert_eq!(
+        world
+            .get::<Inventory>(villager)
+            .unwrap()
+            .count(ItemType::Wood),
+        2
+    );
+    assert!(world.resource::<JobBoard>().is_reserved_by(wood, villager));
+    assert_eq!(world.get::<ItemStack>(wood).unwrap().count, 3);
+
+    assert_eq!(world.get::<Harvestable>(tree).unwrap().health, 1.25);
+    assert_eq!(
+        world.get::<Tree>(tree).unwrap().kind,
+        SceneAssetId::new("tree_round")
+    );
+    let stored = other
+        .world_mut()
+        .query_filtered::<&ItemStack, With<Stockpile>>()
+        .single(other.world())
+        .count;
+    assert_eq!(stored, 7);
+    assert_eq!(other.count_structures(StructureKind::House), 1);
+    let site = single::<With<ConstructionSite>>(&mut other);
+    let construction_site = other.world().get::<ConstructionSite>(site).unwrap();
+    assert_eq!(construction_site.kind, StructureKind::House);
+    assert_eq!(construction_site.delivered, 5);
+    assert_eq!(construction_site.progress, 0.5);
+    assert_eq!(
+        other.world().get::<Transform>(site).unwrap().translation,
+        Vec3::new(-4.0, 0.0, 3.0)
+    );
+    assert_eq!(
+        other.world().resource::<JobBoard>().get(site).unwrap().kind,
+        JobKind::Build
+    );
+
+    // Carries on where it left off
+    other.app.update();
+    assert!(other.world().get::<FSM<VillagerState>>(villager).is_some());
+}
+
+#[test]
+fn loading_replaces_the_current_village() {
+    let mut harness = test_harness();
+    build_village(&mut harness);
+    let save = capture(harness.world_mut());
+
+    build_village(&mut harness);
+    restore(harness.world_mut(), &save);
+
+    assert_eq!(harness.count::<With<Villager>>(), 1);
+    assert_eq!(harness.count_structures(StructureKind::WoodHut), 1);
+}
+
+#[test]
+fn save_and_load_requests_go_through_files() {
+    let path = std::env::temp_dir().join(format!("village-save-{}.ron", std::process::id()));
+    let mut harness = test_harness();
+    build_village(&mut harness);
+    harness
+        .world_mut()
+        .send_event(SaveGame { path: path.clone() });
+    harness.app.update();
+    assert!(path.exists());
+
+    harness.world_mut().insert_resource(SimulationRng::new(99));
+    harness
+        .world_mut()
+        .send_event(LoadGame { path: path.clone() });
+    harness.app.update();
+    assert_eq!(harness.world().resource::<SimulationRng>().seed(), 1);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn saves_from_unknown_versions_are_rejected() {
+    let mut harness = test_harness();
+    let mut save = capture(harness.world_mut());
+    save.version = SAVE_VERSION + 1;
+
+    let error = migrate(&to_ron(&save).unwrap()).unwrap_err();
+    assert!(matches!(error, SaveError::UnsupportedVersion(version) if version == SAVE_VERSION + 1));
+}
+
+/// The save as version 3 wrote it, without the trees of unloaded chunks.
+fn as_version_3(save: &SaveFile) -> String {
+    assert!(save.unloaded_trees.is_empty());
+    to_ron(save)
+        .unwrap()
+        .replace(&format!("version: {}", SAVE_VERSION), "version: 3")
+        .replace("unloaded_trees: [],", "")
+}
+
+/// The save as version 2 wrote it, with a list each for houses and wood huts.
+fn as_version_2(save: &SaveFile) -> String {
+    let transform = |structure: &SavedStructure| ron::to_string(&structure.transform).unwrap();
+    let houses = save
+        .structures
+        .iter()
+        .filter(|structure| structure.kind == StructureKind::House)
+        .map(|house| format!("(id: {}, transform: {})", house.id, transform(house)))
+        .collect::<Vec<_>>();
+    let wood_huts = save
+        .structures
+        .iter()
+        .filter(|structure| structure.kind == StructureKind::WoodHut)
+        .map(|wood_hut| {
+            let storage = wood_hut.storage.as_ref().unwrap();
+            format!(
+                "(id: {}, transform: {}, item_type: {:?}, stored: {}, capacity: {})",
+                wood_hut.id,
+                transform(wood_hut),
+                storage.item_type,
+                storage.stored,
+                storage.capacity
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let without_structures = SaveFile {
+        structures: Vec::new(),
+        ..save.clone()
+    };
+    as_version_3(&without_structures)
+        .replace("version: 3", "version: 2")
+        .replace(
+            "structures: [],",
+            &format!(
+                "houses: [{}], wood_huts: [{}],",
+                houses.join(", "),
+                wood_huts.join(", ")
+            ),
+        )
+}
+
+/// Houses first, then wood huts, like a migrated save has them.
+fn by_kind(mut save: SaveFile) -> SaveFile {
+    save.structures
+        .sort_by_key(|structure| structure.kind != StructureKind::House);
+    save
+}
+
+#[test]
+fn version_3_saves_have_no_unloaded_trees() {
+    let mut harness = test_harness();
+    build_village(&mut harness);
+    let save = capture(harness.world_mut());
+
+    let text = as_version_3(&save);
+    assert!(!text.contains("unloaded_trees"));
+
+    assert_eq!(migrate(&text).unwrap(), save);
+}
+
+#[test]
+fn version_2_saves_list_houses_and_wood_huts_apart() {
+    let mut harness = test_harness();
+    build_village(&mut harness);
+    let save = by_kind(capture(harness.world_mut()));
+
+    let text = as_version_2(&save);
+    assert!(text.contains("wood_huts: [(id:"));
+
+    assert_eq!(migrate(&text).unwrap(), save);
+}
+
+#[test]
+fn version_1_saves_name_trees_by_their_old_model() {
+    let mut harness = test_harness();
+    build_village(&mut harness);
+    let save = by_kind(capture(harness.world_mut()));
+
+    // Version 1 wrote the model as an enum variant instead of a manifest id
+    let text = as_version_2(&save)
+        .replace("version: 2", "version: 1")
+        .replace("kind: \"tree_round\"", "kind: TreeRound");
+    assert!(text.contains("kind: TreeRound"));
+
+    assert_eq!(migrate(&text).unwrap(), save);
+}
+
+fn trees_in(harness: &mut Harness, coord: ChunkCoord) -> Vec<(Vec3, f32)> {
+    let world = harness.world_mut();
+    let mut trees = world
+        .query_filtered::<(&Transform, &Harvestable), With<Tree>>()
+        .iter(world)
+        .filter(|(transform, _)| ChunkCoord::from_position(transform.translation) == coord)
+        .map(|(transform, harvestable)| (transform.translation, harvestable.health))
+        .collect::<Vec<_>>();
+    trees.sort_by(|a, b| a.0.x.total_cmp(&b.0.x));
+    trees
+}
+
+fn move_anchor(harness: &mut Harness, anchor: Entity, x: f32) {
+    harness
+        .world_mut()
+        .get_mut::<Transform>(anchor)
+        .unwrap()
+        .translation
+        .x = x;
+    harness.app.update();
+    harness.app.update();
+}
+
+#[test]
+fn unloaded_chunks_keep_their_trees_through_a_save() {
+    let mut harness = Harness::with_forest();
+    let anchor = harness
+        .world_mut()
+        .spawn((Transform::default(), ChunkAnchor))
+        .id();
+    harness.app.update();
+    harness.app.update();
+    let before_unloading = capture(harness.world_mut());
+
+    // The most wooded chunk, whichever that is for this seed
+    let mut per_chunk = std::collections::BTreeMap::<(i32, i32), Vec<Entity>>::new();
+    for (entity, transform) in harness
+        .world_mut()
+        .query_filtered::<(Entity, &Transform), With<Tree>>()
+        .iter(harness.world())
+    {
+        let coord = ChunkCoord::from_position(transform.translation).0;
+        per_chunk
+            .entry((coord.x, coord.y))
+            .or_default()
+            .push(entity);
+    }
+    let ((x, z), trees) = per_chunk
+        .into_iter()
+        .max_by_key(|(_, trees)| trees.len())
+        .unwrap();
+    let coord = ChunkCoord::new(x, z);
+    assert!(trees.len() >= 2, "some chunk should grow a few trees");
+    harness.world_mut().entity_mut(trees[0]).despawn_recursive();
+    harness
+        .world_mut()
+        .get_mut::<Harvestable>(trees[1])
+        .unwrap()
+        .health = 0.5;
+    let expected = trees_in(&mut harness, coord);
+
+    move_anchor(&mut harness, anchor, 10.0 * CHUNK_SIZE);
+    assert!(trees_in(&mut harness, coord).is_empty());
+    let save = migrate(&to_ron(&capture(harness.world_mut())).unwrap()).unwrap();
+    assert!(save
+        .unloaded_trees
+        .iter()
+        .any(|chunk| chunk.coord == (x, z) && chunk.trees.len() == expected.len()));
+
+    let mut other = Harness::with_forest();
+    restore(other.world_mut(), &save);
+    let anchor = other
+        .world_mut()
+        .spawn((
+            Transform::from_xyz(10.0 * CHUNK_SIZE, 0.0, 0.0),
+            ChunkAnchor,
+        ))
+        .id();
+    other.app.update();
+    move_anchor(&mut other, anchor, 0.0);
+    // Felled stays felled and the damage is kept, instead of a new forest growing
+    assert_eq!(trees_in(&mut other, coord), expected);
+
+    // Loading a save from before the chunk was unloaded forgets what this village left
+    restore(harness.world_mut(), &before_unloading);
+    assert!(capture(harness.world_mut()).unloaded_trees.is_empty());
+}
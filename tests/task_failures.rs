@@ -0,0 +1,331 @@
+mod common;
+
+use bevy::prelude::*;
+
+use common::Harness;
+use village::fsm::components::*;
+use village::fsm::failure::*;
+use village::harvestable::*;
+use village::inventory::*;
+use village::item::*;
+use village::item_drop::*;
+use village::jobs::*;
+use village::structure::registry::{Structure, StructureKind};
+
+fn spawn_at(harness: &mut Harness, position: Vec3) -> Entity {
+    harness
+        .world_mut()
+        .spawn(Transform::from_translation(position))
+        .id()
+}
+
+fn assert_failed(
+    harness: &Harness,
+    villager: Entity,
+    state: VillagerState,
+    reason: TaskFailureReason,
+) {
+    let failures = harness.events::<TaskFailed>();
+    assert_eq!(
+        failures.len(),
+        1,
+        "expected one failure, got {:?}",
+        failures
+    );
+    assert_eq!(failures[0].entity, villager);
+    assert_eq!(failures[0].state, state);
+    assert_eq!(failures[0].reason, reason);
+    assert_eq!(harness.state(villager), VillagerState::Idle);
+}
+
+#[test]
+fn idle_without_any_targets_stays_idle() {
+    let mut harness = Harness::new();
+    let villager = harness.spawn_villager(Vec3::ZERO);
+
+    for _ in 0..3 {
+        harness.app.update();
+    }
+
+    assert_eq!(harness.state(villager), VillagerState::Idle);
+    assert!(harness.events::<TaskFailed>().is_empty());
+}
+
+#[test]
+fn walking_to_despawned_target_fails() {
+    let mut harness = Harness::new();
+    let house = spawn_at(&mut harness, Vec3::new(3.0, 0.0, 0.0));
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMWalkingTo {
+            target: house,
+            proximity: 0.2,
+        },
+    );
+    harness.world_mut().despawn(house);
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::WalkingTo,
+        TaskFailureReason::TargetDespawned,
+    );
+}
+
+#[test]
+fn walking_to_harvest_despawned_target_fails() {
+    let mut harness = Harness::new();
+    let tree = harness.spawn_tree(Vec3::new(3.0, 0.0, 0.0));
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMWalkingToHarvest {
+            target: tree,
+            proximity: 0.2,
+        },
+    );
+    harness.world_mut().entity_mut(tree).despawn_recursive();
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::WalkingToHarvest,
+        TaskFailureReason::TargetDespawned,
+    );
+}
+
+#[test]
+fn walking_to_harvest_non_harvestable_fails() {
+    let mut harness = Harness::new();
+    let rock = spawn_at(&mut harness, Vec3::new(3.0, 0.0, 0.0));
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMWalkingToHarvest {
+            target: rock,
+            proximity: 0.2,
+        },
+    );
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::WalkingToHarvest,
+        TaskFailureReason::InvalidTarget,
+    );
+}
+
+#[test]
+fn walking_to_harvest_destroyed_target_fails() {
+    let mut harness = Harness::new();
+    let tree = harness.spawn_tree(Vec3::new(3.0, 0.0, 0.0));
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMWalkingToHarvest {
+            target: tree,
+            proximity: 0.2,
+        },
+    );
+    harness
+        .world_mut()
+        .send_event(HarvestableDestroyed { entity: tree });
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::WalkingToHarvest,
+        TaskFailureReason::TargetDestroyed,
+    );
+}
+
+#[test]
+fn walking_to_harvest_tree_reserved_by_someone_else_fails() {
+    let mut harness = Harness::new();
+    let tree = harness.spawn_tree(Vec3::new(3.0, 0.0, 0.0));
+    let other_villager = spawn_at(&mut harness, Vec3::ZERO);
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMWalkingToHarvest {
+            target: tree,
+            proximity: 0.2,
+        },
+    );
+    let mut job_board = harness.world_mut().resource_mut::<JobBoard>();
+    job_board.post(JobKind::Harvest, tree);
+    job_board.claim(tree, other_villager);
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::WalkingToHarvest,
+        TaskFailureReason::TargetReserved,
+    );
+}
+
+#[test]
+fn harvesting_despawned_target_fails() {
+    let mut harness = Harness::new();
+    let tree = harness.spawn_tree(Vec3::new(0.1, 0.0, 0.0));
+    let villager = harness.spawn_villager_in(Vec3::ZERO, FSMHarvesting { target: tree });
+    harness.world_mut().entity_mut(tree).despawn_recursive();
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::Harvesting,
+        TaskFailureReason::TargetDespawned,
+    );
+}
+
+#[test]
+fn picking_up_despawned_wood_fails() {
+    let mut harness = Harness::new();
+    let wood = harness.spawn_wood_drop(Vec3::new(3.0, 0.0, 0.0), 3);
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMPickingUp {
+            target: wood,
+            proximity: 0.2,
+        },
+    );
+    harness.world_mut().entity_mut(wood).despawn_recursive();
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::PickingUp,
+        TaskFailureReason::TargetDespawned,
+    );
+}
+
+#[test]
+fn picking_up_without_wood_hut_fails_and_leaves_wood() {
+    let mut harness = Harness::new();
+    let wood = harness.spawn_wood_drop(Vec3::new(3.0, 0.0, 0.0), 3);
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMPickingUp {
+            target: wood,
+            proximity: 0.2,
+        },
+    );
+    let mut job_board = harness.world_mut().resource_mut::<JobBoard>();
+    job_board.post(JobKind::Haul, wood);
+    job_board.claim(wood, villager);
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::PickingUp,
+        TaskFailureReason::NoStorage,
+    );
+    assert!(harness.world().get_entity(wood).is_ok());
+}
+
+#[test]
+fn picking_up_wood_reserved_by_someone_else_fails() {
+    let mut harness = Harness::new();
+    let wood = harness.spawn_wood_drop(Vec3::new(3.0, 0.0, 0.0), 3);
+    let other_villager = spawn_at(&mut harness, Vec3::ZERO);
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMPickingUp {
+            target: wood,
+            proximity: 0.2,
+        },
+    );
+    let mut job_board = harness.world_mut().resource_mut::<JobBoard>();
+    job_board.post(JobKind::Haul, wood);
+    job_board.claim(wood, other_villager);
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::PickingUp,
+        TaskFailureReason::TargetReserved,
+    );
+}
+
+#[test]
+fn bringing_to_despawned_hut_fails_and_drops_wood() {
+    let mut harness = Harness::new();
+    let hut = harness.spawn_wood_hut(Vec3::new(3.0, 0.0, 0.0));
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMBringingTo {
+            target: hut,
+            proximity: 0.2,
+        },
+    );
+    harness.give(villager, ItemType::Wood, 3);
+    harness.world_mut().entity_mut(hut).despawn_recursive();
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::BringingTo,
+        TaskFailureReason::TargetDespawned,
+    );
+    let dropped = harness
+        .world_mut()
+        .query_filtered::<&ItemStack, With<ItemDrop>>()
+        .iter(harness.world())
+        .map(|item_stack| item_stack.count)
+        .collect::<Vec<_>>();
+    assert_eq!(dropped, vec![3]);
+    assert!(harness
+        .world()
+        .get::<Inventory>(villager)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn bringing_to_hut_without_storage_fails() {
+    let mut harness = Harness::new();
+    // A structure whose stockpile is gone
+    let hut = harness
+        .world_mut()
+        .spawn((
+            Transform::from_xyz(3.0, 0.0, 0.0),
+            Structure {
+                kind: StructureKind::WoodHut,
+            },
+        ))
+        .id();
+    let villager = harness.spawn_villager_in(
+        Vec3::ZERO,
+        FSMBringingTo {
+            target: hut,
+            proximity: 0.2,
+        },
+    );
+    harness.give(villager, ItemType::Wood, 2);
+
+    harness.app.update();
+
+    assert_failed(
+        &harness,
+        villager,
+        VillagerState::BringingTo,
+        TaskFailureReason::NoStorage,
+    );
+}
@@ -0,0 +1,238 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::CollisionEvent;
+
+use village::assets::SceneAssets;
+use village::harvestable::{tree::Tree, ForestPlugin, Harvestable};
+use village::random::{RandomPlugin, SimulationRng};
+use village::world::*;
+
+fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .init_resource::<Assets<Mesh>>()
+        .init_resource::<Assets<StandardMaterial>>()
+        .add_event::<CollisionEvent>()
+        .add_plugins(WorldPlugin);
+    app
+}
+
+fn forest_app() -> App {
+    let mut app = test_app();
+    app.add_plugins((ForestPlugin, RandomPlugin))
+        .insert_resource(SimulationRng::new(4))
+        .insert_resource(Terrain::new(4))
+        .insert_resource(SceneAssets::placeholder());
+    app
+}
+
+fn trees_in(app: &mut App, coord: ChunkCoord) -> Vec<(Entity, Vec3, f32)> {
+    app.world_mut()
+        .query_filtered::<(Entity, &Transform, &Harvestable), With<Tree>>()
+        .iter(app.world())
+        .filter(|(_, transform, _)| ChunkCoord::from_position(transform.translation) == coord)
+        .map(|(entity, transform, harvestable)| (entity, transform.translation, harvestable.health))
+        .collect()
+}
+
+fn loaded_chunks(app: &App) -> usize {
+    app.world().resource::<ChunkMap>().len()
+}
+
+#[test]
+fn positions_map_to_chunks() {
+    assert_eq!(ChunkCoord::from_position(Vec3::ZERO), ChunkCoord::new(0, 0));
+    assert_eq!(
+        ChunkCoord::from_position(Vec3::new(-0.1, 5.0, CHUNK_SIZE)),
+        ChunkCoord::new(-1, 1)
+    );
+    assert_eq!(
+        ChunkCoord::new(2, -1).center(),
+        Vec3::new(2.5 * CHUNK_SIZE, 0.0, -0.5 * CHUNK_SIZE)
+    );
+    assert_eq!(ChunkCoord::new(0, 0).distance(ChunkCoord::new(-2, 1)), 2);
+}
+
+#[test]
+fn chunks_load_around_anchors() {
+    let mut app = test_app();
+    app.world_mut().spawn((Transform::default(), ChunkAnchor));
+
+    app.update();
+
+    assert_eq!(loaded_chunks(&app), 25);
+    let chunk_map = app.world().resource::<ChunkMap>();
+    assert!(chunk_map.is_loaded(ChunkCoord::new(-2, 2)));
+    assert!(!chunk_map.is_loaded(ChunkCoord::new(3, 0)));
+}
+
+#[test]
+fn far_chunks_unload_with_what_stands_in_them() {
+    let mut app = test_app();
+    let anchor = app
+        .world_mut()
+        .spawn((Transform::default(), ChunkAnchor))
+        .id();
+    let local = app
+        .world_mut()
+        .spawn((Transform::from_xyz(1.0, 0.0, 1.0), ChunkLocal))
+        .id();
+    app.update();
+
+    // Still close enough to keep the chunks around the start loaded
+    app.world_mut()
+        .get_mut::<Transform>(anchor)
+        .unwrap()
+        .translation
+        .x = 3.0 * CHUNK_SIZE;
+    app.update();
+    assert!(app
+        .world()
+        .resource::<ChunkMap>()
+        .is_loaded(ChunkCoord::new(0, 0)));
+    assert!(app.world().get_entity(local).is_ok());
+
+    app.world_mut()
+        .get_mut::<Transform>(anchor)
+        .unwrap()
+        .translation
+        .x = 10.0 * CHUNK_SIZE;
+    app.update();
+    assert_eq!(loaded_chunks(&app), 25);
+    assert!(!app
+        .world()
+        .resource::<ChunkMap>()
+        .is_loaded(ChunkCoord::new(0, 0)));
+    assert!(app.world().get_entity(local).is_err());
+}
+
+#[test]
+fn terrain_is_deterministic_per_seed() {
+    let terrain = Terrain::new(7);
+    let same = Terrain::new(7);
+    let other = Terrain::new(8);
+
+    let points = [(0.0, 0.0), (3.3, -12.5), (40.0, 17.25)];
+    for (x, z) in points {
+        assert_eq!(terrain.height_at(x, z), same.height_at(x, z));
+        assert!((0.0..=terrain.height_scale).contains(&terrain.height_at(x, z)));
+        assert!((0.0..=1.0).contains(&terrain.forest_density(x, z)));
+    }
+    assert!(points
+        .iter()
+        .any(|(x, z)| terrain.height_at(*x, *z) != other.height_at(*x, *z)));
+}
+
+#[test]
+fn entities_are_placed_on_the_surface() {
+    let terrain = Terrain::new(3);
+    let position = terrain.on_surface(Vec3::new(5.0, 10.0, -2.0));
+    assert_eq!(position.y, terrain.height_at(5.0, -2.0));
+
+    assert_eq!(
+        Terrain::flat().on_surface(Vec3::new(5.0, 10.0, -2.0)),
+        Vec3::new(5.0, 0.0, -2.0)
+    );
+}
+
+#[test]
+fn rays_hit_the_ground_where_it_is_not_at_sea_level() {
+    let terrain = Terrain::new(3);
+    let origin = Vec3::new(-6.0, 12.0, 4.0);
+    let ray = Ray3d::new(origin, Dir3::new(Vec3::new(1.0, -1.0, 0.5)).unwrap());
+
+    let hit = terrain.cast_ray(ray, 100.0).unwrap();
+    assert!((hit.y - terrain.height_at(hit.x, hit.z)).abs() < 1e-3);
+    assert!(ray.get_point(ray.direction.dot(hit - origin)).distance(hit) < 0.01);
+    // Lifting where the ray meets y = 0 onto the ground would be off by a long way
+    let flat_hit = ray.get_point(
+        ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))
+            .unwrap(),
+    );
+    assert!(hit.xz().distance(flat_hit.xz()) > 0.5);
+
+    let up = Ray3d::new(origin, Dir3::Y);
+    assert_eq!(terrain.cast_ray(up, 100.0), None);
+}
+
+#[test]
+fn chunk_mesh_follows_the_heightmap() {
+    let terrain = Terrain::new(5);
+    let coord = ChunkCoord::new(1, -1);
+    let mesh = terrain.chunk_mesh(coord);
+
+    let side = CHUNK_RESOLUTION + 1;
+    assert_eq!(mesh.count_vertices(), side * side);
+
+    // The first vertex is the chunk's corner, relative to its center
+    let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        panic!("chunk mesh has no positions");
+    };
+    let origin = coord.origin();
+    assert_eq!(
+        positions[0],
+        [
+            -CHUNK_SIZE / 2.0,
+            terrain.height_at(origin.x, origin.z),
+            -CHUNK_SIZE / 2.0
+        ]
+    );
+}
+
+#[test]
+fn reloaded_chunks_keep_felled_and_damaged_trees() {
+    let mut app = forest_app();
+    let anchor = app
+        .world_mut()
+        .spawn((Transform::default(), ChunkAnchor))
+        .id();
+    app.update();
+    app.update();
+
+    let coord = ChunkCoord::new(0, 0);
+    let trees = trees_in(&mut app, coord);
+    assert!(
+        trees.len() >= 2,
+        "seed 4 should grow a few trees at the origin"
+    );
+    let (felled, _, _) = trees[0];
+    let (damaged, damaged_position, _) = trees[1];
+    app.world_mut().entity_mut(felled).despawn_recursive();
+    app.world_mut()
+        .get_mut::<Harvestable>(damaged)
+        .unwrap()
+        .health = 0.5;
+
+    app.world_mut()
+        .get_mut::<Transform>(anchor)
+        .unwrap()
+        .translation
+        .x = 10.0 * CHUNK_SIZE;
+    app.update();
+    assert!(trees_in(&mut app, coord).is_empty());
+
+    app.world_mut()
+        .get_mut::<Transform>(anchor)
+        .unwrap()
+        .translation
+        .x = 0.0;
+    app.update();
+    app.update();
+
+    let reloaded = trees_in(&mut app, coord);
+    let mut expected = trees[1..]
+        .iter()
+        .map(|(_, position, _)| *position)
+        .collect::<Vec<_>>();
+    let mut positions = reloaded
+        .iter()
+        .map(|(_, position, _)| *position)
+        .collect::<Vec<_>>();
+    expected.sort_by(|a, b| a.x.total_cmp(&b.x));
+    positions.sort_by(|a, b| a.x.total_cmp(&b.x));
+    assert_eq!(positions, expected);
+    assert!(reloaded
+        .iter()
+        .any(|(_, position, health)| *position == damaged_position && *health == 0.5));
+}